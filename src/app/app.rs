@@ -15,35 +15,136 @@ use pretty_date::pretty_date_formatter::PrettyDateFormatter;
 use ratatui::{ prelude::{ Backend, CrosstermBackend }, Terminal };
 use crate::ui::{
   home::{ self, screen::{ EditorFocusable, Focusable as HomeFocusable, HomeScreen } },
-  lyrics::{ self, screen::Focusable as LyricsFocusable },
-  modals::{ modal::enums::Modal, save_result::SaveTagsResultModal },
+  comments::screen::{ CommentsScreen, Focusable as CommentsFocusable },
+  frames::screen::{ FramesScreen, Focusable as FramesFocusable },
+  history::screen::HistoryScreen,
+  lyrics::{ self, screen::{ Focusable as LyricsFocusable, Pane as LyricsPane } },
+  settings::screen::Row as SettingsRow,
+  modals::{
+    external_changes::ExternalChangesModal,
+    modal::enums::Modal,
+    quit::QuitModal,
+    save_result::SaveTagsResultModal,
+  },
   text_area::TextAreaTrait,
   ui::Ui,
   ui_enums::{ self, Screen },
 };
-use super::{ state::{ Source, State }, tag::SongTags };
+use super::{
+  backup::Backup,
+  genre,
+  history::History,
+  scan::{ self, Scanned },
+  state::{ Source, State },
+  tag::{ Editable, Id3v1Action, SongTags },
+};
 
 pub enum Command {
+  /// Quits, asking first when there are unsaved edits.
   Quit,
+  /// Quits dropping unsaved edits.
+  ForceQuit,
+  SaveAllAndQuit,
 
   OpenModal(Modal),
   CloseLastModal,
   SetModalOption(usize),
   ExecuteModalOption(usize),
+  HandleModalInput(KeyEvent),
+  SetModalCursor(usize),
 
   SetScreen(ui_enums::Screen),
 
   FocusHomeElement(HomeFocusable),
+  ToggleSelected(usize),
+  /// Adds both files to the selection and moves the focus to the second.
+  ExtendSelection(usize, usize),
+  /// Selects the files shown in the table, or clears the selection when they already are.
+  SelectShown,
   FocusLyricsElement(LyricsFocusable),
+  FocusCommentsElement(CommentsFocusable),
+  FocusFramesElement(FramesFocusable),
 
   ResetHomeScreenTag(HomeFocusable),
   ResetLyricsScreenTag(LyricsFocusable),
+  ResetCommentsScreenTag(CommentsFocusable),
+  ResetFramesScreenTag(FramesFocusable),
 
   HandleHomeScreenInput(KeyEvent, HomeFocusable),
   HandleLyricsScreenInput(KeyEvent, LyricsFocusable),
+  HandleCommentsScreenInput(KeyEvent, CommentsFocusable),
+  HandleFramesScreenInput(KeyEvent, FramesFocusable),
 
-  SaveTags(usize),
+  SaveTags(Vec<usize>, Id3v1Action),
   TagsSaved(Result<(), String>),
+  /// Reports how reading or writing a file other than a song went, with the message shown when it
+  /// went fine and the one shown above the error otherwise.
+  ShowResult(&'static str, &'static str, Result<(), String>),
+  FilesSaved(Vec<(String, Result<(), String>)>),
+  /// Reads the files changed on disk again and applies their edits on top.
+  ReloadTags(Vec<usize>),
+  /// Saves the files changed on disk over the changes.
+  OverwriteTags(Vec<usize>, Id3v1Action),
+
+  Undo,
+  Redo,
+
+  SetPendingCursor(usize),
+  TogglePendingMark(usize),
+  /// Marks every pending file, or clears the marks when they all are.
+  MarkAllPending,
+  /// Saves the first file and queues the rest so that the progress is drawn between files.
+  SavePending(Vec<usize>),
+  RevertPending(Vec<usize>),
+
+  ScrollLog(usize),
+
+  /// Files found by the library scan since the last report.
+  FilesFound(usize),
+  /// Files read by the library scan, added to the table as they come.
+  FilesScanned(Vec<Scanned>),
+  /// A directory the library scan could not list.
+  ScanFailed(String),
+  /// Comes after the last file read by the library scan.
+  ScanFinished,
+
+  SetHistoryCursor(usize),
+  ScrollHistory(usize),
+  /// Puts the tags of a backup back into the file.
+  RestoreBackup(usize, Backup),
+
+  CopyToId3v1(usize),
+  CopyFromId3v1(usize),
+
+  /// Sets the genre of a file or adds it to the file's genres.
+  PickGenre(usize, String, bool),
+
+  SelectPicture(usize),
+  ImportPicture(usize, Option<usize>, String),
+  ExportPicture(usize, usize, String),
+  RemovePicture(usize, usize),
+  CyclePictureType(usize, usize),
+
+  SetLyricsFrameCursor(usize),
+  OpenLyricsFrame(LyricsPane, Option<usize>),
+  AddLyricsFrame,
+  RemoveLyricsFrame(usize),
+  ToggleSyncedLyrics,
+  ImportLrc(usize, Option<usize>, String),
+  ExportLrc(usize, usize, String),
+
+  SelectComment(usize),
+  AddComment,
+  RemoveComment(usize),
+
+  SelectFrame(usize),
+  AddFrame(String),
+  RemoveFrame(usize),
+
+  SelectSettingsRow(SettingsRow),
+  CycleSetting(SettingsRow, bool),
+  ResetSetting(SettingsRow),
+  UseGlobalWriteOptions,
 }
 
 #[derive(Clone)]
@@ -52,7 +153,11 @@ pub struct Mp3File {
   pub path: String,
   pub source: Source,
   pub modified_date: String,
+  /// Position of the file in the library scan, the files are shown in this order.
+  pub position: usize,
   pub tags: SongTags,
+  /// Edits of `tags` since the file was loaded or saved.
+  pub history: History<SongTags>,
 }
 
 pub struct App {
@@ -63,25 +168,137 @@ pub struct App {
 
 impl App {
   pub fn new() -> Self {
-    Self {
+    let new = Self {
       commands_channel: channel(),
       state: State::new(),
       ui: Ui::new(),
-    }
+    };
+    scan::start(
+      new.state.directories.clone(),
+      new.state.library_cache_path(),
+      new.commands_channel.0.clone()
+    );
+    new
   }
   pub fn poll(&mut self) {
-    if let Ok(cmd) = self.commands_channel.1.try_recv() {
+    let cmd = self.commands_channel.1.try_recv();
+    let busy = cmd.is_ok();
+    if let Ok(cmd) = cmd {
       self.handle_command(cmd, self.commands_channel.0.clone());
     }
-    if poll(Duration::from_millis(100)).unwrap() {
+    // queued commands go on right after the next draw
+    let timeout = if busy { Duration::ZERO } else { Duration::from_millis(100) };
+    if poll(timeout).unwrap() {
       let event = read().unwrap();
       self.ui.handle_input(&self.state, event, self.commands_channel.0.clone());
     }
+    // tags taken from the library cache read their pictures once the file is shown
+    if let Some(i) = self.ui.selected_song_index() {
+      self.state.get_file_mut(i).tags.load_pictures();
+    }
     self.ui.render(&self.state);
   }
+  /// Runs a command and records the tags it changes as a step in the file's history. Only the
+  /// commands editing tags are recorded, the tags are copied before they run.
   fn handle_command(&mut self, cmd: Command, sender: Sender<Command>) {
+    let group = match &cmd {
+      // typing into one input is undone at once
+      Command::HandleHomeScreenInput(_, el) => Some(format!("{:?}", el)),
+      Command::HandleLyricsScreenInput(_, el) => Some(format!("{:?}", el)),
+      Command::HandleCommentsScreenInput(_, el) => Some(format!("{:?}", el)),
+      Command::HandleFramesScreenInput(_, el) => Some(format!("{:?}", el)),
+      Command::ResetHomeScreenTag(..) |
+      Command::ResetLyricsScreenTag(..) |
+      Command::ResetCommentsScreenTag(..) |
+      Command::ResetFramesScreenTag(..) |
+      Command::PickGenre(..) |
+      Command::CopyToId3v1(..) |
+      Command::CopyFromId3v1(..) |
+      Command::ImportPicture(..) |
+      Command::RemovePicture(..) |
+      Command::CyclePictureType(..) |
+      Command::AddLyricsFrame |
+      Command::RemoveLyricsFrame(..) |
+      Command::ImportLrc(..) |
+      Command::AddComment |
+      Command::RemoveComment(..) |
+      Command::AddFrame(..) |
+      Command::RemoveFrame(..) |
+      Command::CycleSetting(..) |
+      Command::ResetSetting(..) |
+      Command::UseGlobalWriteOptions => None,
+      // the scan adds files without touching the ones being edited, the others keep or clear the
+      // history themselves
+      Command::FilesFound(..) |
+      Command::FilesScanned(..) |
+      Command::ScanFailed(..) |
+      Command::ScanFinished |
+      Command::Undo |
+      Command::Redo |
+      Command::SaveTags(..) |
+      Command::ReloadTags(..) |
+      Command::OverwriteTags(..) |
+      Command::RevertPending(..) |
+      Command::RestoreBackup(..) => {
+        return self.execute(cmd, sender);
+      }
+      _ => {
+        let targets = self.targets();
+        self.execute(cmd, sender);
+        // moving elsewhere ends the edit being typed
+        for i in targets {
+          self.state.get_file_mut(i).history.seal();
+        }
+        return;
+      }
+    };
+    let touched = match &cmd {
+      // pictures are edited one file at a time
+      Command::ImportPicture(i, ..) |
+      Command::RemovePicture(i, _) |
+      Command::CyclePictureType(i, _) => vec![*i],
+      _ => self.targets(),
+    };
+    // typing on into the same input needs no copy, the step already holds the tags before it
+    let before = touched
+      .into_iter()
+      .filter(|&i| !self.state.get_file(i).history.continues(&group))
+      .map(|i| (i, self.state.get_file(i).tags.clone()))
+      .collect::<Vec<_>>();
+    self.execute(cmd, sender);
+    for (i, before) in before {
+      let file = self.state.get_file_mut(i);
+      if file.tags != before {
+        file.history.record(before, group.clone());
+      } else if group.is_none() {
+        file.history.seal();
+      }
+    }
+  }
+  fn execute(&mut self, cmd: Command, sender: Sender<Command>) {
     match cmd {
       Command::Quit => {
+        let unsaved = self.state.pending().len();
+        if unsaved > 0 {
+          self.ui.state.modals.open(Modal::Quit(QuitModal::new(unsaved)));
+          return;
+        }
+        self.handle_command(Command::ForceQuit, sender);
+      }
+      Command::SaveAllAndQuit => {
+        let (indexes, changed) = self.split_changed_on_disk(self.state.pending());
+        let results = self.save_files(indexes, Id3v1Action::Update);
+        // files that failed stay open to be fixed
+        if changed.is_empty() && results.iter().all(|(_, res)| res.is_ok()) {
+          self.handle_command(Command::ForceQuit, sender);
+          return;
+        }
+        if !results.is_empty() {
+          sender.send(Command::FilesSaved(results));
+        }
+        self.ask_about_changes_on_disk(changed, Id3v1Action::Update, sender);
+      }
+      Command::ForceQuit => {
         self.state.running = false;
         match &mut self.ui.state.screen {
           Screen::Home(_) => {}
@@ -90,17 +307,46 @@ impl App {
             self.ui.state.screen = Screen::Home(
               HomeScreen::new(
                 HomeFocusable::Editor(screen.index, EditorFocusable::LyricsButton),
-                Some({
-                  let tags = &mut self.state.get_file_mut(screen.index).tags;
-                  tags.lyrics = screen.lyrics.clone();
-                  tags
-                })
+                Some(&self.state.get_file(screen.index).tags)
+              )
+            );
+          }
+          Screen::Comments(screen) => {
+            self.ui.state.screen = Screen::Home(
+              HomeScreen::new(
+                HomeFocusable::Editor(screen.index, EditorFocusable::CommentsButton),
+                Some(&self.state.get_file(screen.index).tags)
+              )
+            );
+          }
+          Screen::Frames(FramesScreen { index, .. }) |
+          Screen::History(HistoryScreen { index, .. }) => {
+            let index = *index;
+            self.ui.state.screen = Screen::Home(
+              HomeScreen::new(
+                HomeFocusable::Table(index),
+                Some(&self.state.get_file(index).tags)
               )
             );
           }
+          Screen::Settings(screen) => {
+            self.ui.state.screen = Screen::Home(
+              HomeScreen::new(
+                screen.index.map(HomeFocusable::Table).unwrap_or(HomeFocusable::Search),
+                screen.index.map(|i| &self.state.get_file(i).tags)
+              )
+            );
+          }
+          Screen::Pending(_) | Screen::Log(_) => {
+            self.ui.state.screen = Screen::Home(HomeScreen::new(HomeFocusable::Search, None));
+          }
         }
       }
       Command::SetScreen(screen) => {
+        // the selection belongs to the home screen
+        if !matches!(screen, Screen::Home(_)) {
+          self.state.selection.clear();
+        }
         self.ui.state.screen = screen;
       }
       Command::SetModalOption(i) => {
@@ -121,23 +367,31 @@ impl App {
         let cmd = cmd.list_mut()[i].cmd.take().unwrap();
         self.handle_command(cmd, sender);
       }
+      Command::HandleModalInput(key_event) => {
+        let Some(modal) = self.ui.state.modals.last_mut() else {
+          return;
+        };
+        let changed = modal.input_mut().is_some_and(|i| i.input_for_humans(key_event, false));
+        // entries following the input start over from the top
+        if let Some(cursor) = modal.cursor_mut().filter(|_| changed) {
+          *cursor = 0;
+        }
+      }
+      Command::SetModalCursor(j) => {
+        if let Some(cursor) = self.ui.state.modals.last_mut().and_then(|m| m.cursor_mut()) {
+          *cursor = j;
+        }
+      }
       Command::FocusHomeElement(el) => {
         match &mut self.ui.state.screen {
           ui_enums::Screen::Home(screen) => {
             screen.focused_el = el;
             match el {
               HomeFocusable::Search => {
-                screen.title_input.clear();
-                screen.artist_input.clear();
-                screen.year_input.clear();
-                screen.genre_input.clear();
+                screen.set_inputs(&[]);
               }
-              HomeFocusable::Table(i) => {
-                let tags = &self.state.get_file(i).tags;
-                screen.title_input.set_text(tags.title.0.to_string());
-                screen.artist_input.set_text(tags.artist.0.to_string());
-                screen.year_input.set_text(tags.year.0.to_string());
-                screen.genre_input.set_text(tags.genre.0.to_string());
+              HomeFocusable::Table(_) => {
+                self.sync_inputs();
               }
               HomeFocusable::Editor(..) => {}
             }
@@ -145,6 +399,26 @@ impl App {
           _ => {}
         }
       }
+      Command::ToggleSelected(i) => {
+        self.state.toggle_selected(i);
+        self.sync_inputs();
+      }
+      Command::ExtendSelection(i, next) => {
+        self.state.select(i);
+        self.state.select(next);
+        self.handle_command(Command::FocusHomeElement(HomeFocusable::Table(next)), sender);
+      }
+      Command::SelectShown => {
+        let shown = &self.state.shown_indexes;
+        if shown.iter().all(|i| self.state.is_selected(*i)) {
+          self.state.selection.clear();
+        } else {
+          for i in shown.clone() {
+            self.state.select(i);
+          }
+        }
+        self.sync_inputs();
+      }
       Command::FocusLyricsElement(el) => {
         match &mut self.ui.state.screen {
           ui_enums::Screen::Lyrics(screen) => {
@@ -153,17 +427,431 @@ impl App {
           _ => {}
         }
       }
-      Command::SaveTags(i) => {
-        let audio = &mut self.state.get_file_mut(i).tags;
-        let res = audio.save();
-        sender.send(Command::TagsSaved(res));
+      Command::FocusCommentsElement(el) => {
+        if let Screen::Comments(screen) = &mut self.ui.state.screen {
+          screen.focused_el = el;
+        }
+      }
+      Command::FocusFramesElement(el) => {
+        if let Screen::Frames(screen) = &mut self.ui.state.screen {
+          screen.focused_el = el;
+        }
+      }
+      Command::SaveTags(indexes, id3v1_action) => {
+        let (indexes, changed) = self.split_changed_on_disk(indexes);
+        let results = self.save_files(indexes, id3v1_action);
+        if !results.is_empty() {
+          sender.send(Command::FilesSaved(results));
+        }
+        self.ask_about_changes_on_disk(changed, id3v1_action, sender);
+      }
+      Command::ReloadTags(indexes) => {
+        for i in indexes {
+          let file = self.state.get_file_mut(i);
+          file.tags = file.tags.rebase(SongTags::new(file.tags.path().to_string()));
+          // steps would bring back values read before the reload
+          file.history.clear();
+        }
+        self.sync_inputs();
+      }
+      Command::OverwriteTags(indexes, id3v1_action) => {
+        for &i in &indexes {
+          self.state.get_file_mut(i).tags.accept_disk();
+        }
+        sender.send(Command::SaveTags(indexes, id3v1_action));
       }
       Command::TagsSaved(res) => {
+        if let Err(e) = &res {
+          self.state.log.error(e);
+        }
         self.handle_command(
           Command::OpenModal(Modal::SaveTagsResult(SaveTagsResultModal::new(res))),
           sender
         );
       }
+      Command::ShowResult(done, failed, res) => {
+        if let Err(e) = &res {
+          self.state.log.error(format!("{}: {}", failed, e));
+        }
+        let modal = SaveTagsResultModal::message(done, failed, res);
+        self.handle_command(Command::OpenModal(Modal::SaveTagsResult(modal)), sender);
+      }
+      Command::FilesSaved(mut results) => {
+        let modal = if results.len() == 1 {
+          SaveTagsResultModal::new(results.remove(0).1)
+        } else {
+          SaveTagsResultModal::batch(results)
+        };
+        self.handle_command(Command::OpenModal(Modal::SaveTagsResult(modal)), sender);
+      }
+      Command::Undo | Command::Redo => {
+        let mut stepped = false;
+        for i in self.targets() {
+          let file = self.state.get_file_mut(i);
+          let current = file.tags.clone();
+          let step = match cmd {
+            Command::Undo => file.history.undo(current),
+            _ => file.history.redo(current),
+          };
+          if let Some(tags) = step {
+            file.tags = tags;
+            stepped = true;
+          }
+        }
+        if stepped {
+          self.sync_inputs();
+        }
+      }
+      Command::PickGenre(i, picked, add) => {
+        for i in self.state.targets(i) {
+          let tags = &mut self.state.get_file_mut(i).tags;
+          let mut genres = if add { genre::split(&tags.genre.0.to_string()) } else { Vec::new() };
+          if !genres.iter().any(|g| g.eq_ignore_ascii_case(&picked)) {
+            genres.push(picked.clone());
+          }
+          tags.genre.0.edit(genres.join(genre::SEPARATOR));
+        }
+        self.sync_inputs();
+      }
+      Command::FilesFound(found) => {
+        self.state.scan.found += found;
+      }
+      Command::FilesScanned(scanned) => {
+        self.state.add_files(scanned);
+      }
+      Command::ScanFailed(error) => {
+        self.state.log.error(error);
+        self.state.scan.errors += 1;
+      }
+      Command::ScanFinished => {
+        self.state.finish_scan();
+      }
+      Command::ScrollLog(scroll) => {
+        if let Screen::Log(screen) = &mut self.ui.state.screen {
+          screen.scroll = scroll;
+        }
+      }
+      Command::SetHistoryCursor(j) => {
+        if let Screen::History(screen) = &mut self.ui.state.screen {
+          screen.cursor = j;
+          screen.scroll = 0;
+        }
+      }
+      Command::ScrollHistory(scroll) => {
+        if let Screen::History(screen) = &mut self.ui.state.screen {
+          screen.scroll = scroll;
+        }
+      }
+      Command::RestoreBackup(i, backup) => {
+        let path = self.state.get_file(i).tags.path().to_string();
+        let keep_times = self.state.settings.keep_times;
+        self.state.forget_cached(i);
+        if let Err(e) = self.state.backups.restore(&path, &backup, keep_times) {
+          self.handle_command(Command::TagsSaved(Err(e.to_string())), sender);
+          return;
+        }
+        let file = self.state.get_file_mut(i);
+        let write_options = file.tags.write_options.current().copied();
+        file.tags = SongTags::new(path.clone());
+        file.tags.write_options = Editable::new(write_options);
+        // steps would bring back values read before the restore
+        file.history.clear();
+        let mut screen = HistoryScreen::new(i, &path, &self.state.backups);
+        screen.restored = Some(backup.time.format("%Y-%m-%d %H:%M:%S").to_string());
+        self.ui.state.screen = Screen::History(screen);
+      }
+      Command::SetPendingCursor(j) => {
+        if let Screen::Pending(screen) = &mut self.ui.state.screen {
+          screen.cursor = j;
+        }
+      }
+      Command::TogglePendingMark(i) => {
+        if let Screen::Pending(screen) = &mut self.ui.state.screen {
+          match screen.marked.iter().position(|m| *m == i) {
+            Some(j) => {
+              screen.marked.remove(j);
+            }
+            None => screen.marked.push(i),
+          }
+        }
+      }
+      Command::MarkAllPending => {
+        if let Screen::Pending(screen) = &mut self.ui.state.screen {
+          let pending = self.state.pending();
+          if pending.iter().all(|i| screen.marked.contains(i)) {
+            screen.marked.clear();
+          } else {
+            screen.marked = pending;
+          }
+        }
+      }
+      Command::SavePending(mut indexes) => {
+        let total = indexes.len();
+        let saved = (!indexes.is_empty()).then(|| {
+          let i = indexes.remove(0);
+          (i, self.save_files(vec![i], Id3v1Action::Update).remove(0))
+        });
+        let pending = self.state.pending().len();
+        let Screen::Pending(screen) = &mut self.ui.state.screen else {
+          return;
+        };
+        // the first file starts a new report
+        let (done, total) = screen.progress.unwrap_or_else(|| {
+          screen.report.clear();
+          (0, total)
+        });
+        if let Some((i, (name, res))) = saved {
+          if res.is_ok() {
+            screen.marked.retain(|m| *m != i);
+          }
+          screen.report.push((name, res));
+        }
+        if indexes.is_empty() {
+          screen.progress = None;
+          screen.cursor = screen.cursor.min(pending.saturating_sub(1));
+        } else {
+          screen.progress = Some((done + 1, total));
+          sender.send(Command::SavePending(indexes));
+        }
+      }
+      Command::RevertPending(indexes) => {
+        let Screen::Pending(screen) = &mut self.ui.state.screen else {
+          return;
+        };
+        for i in indexes {
+          // reverting can be undone on the home screen
+          let file = self.state.get_file_mut(i);
+          let before = file.tags.clone();
+          file.tags.reset();
+          file.history.record(before, None);
+          screen.marked.retain(|m| *m != i);
+        }
+        screen.report.clear();
+        screen.cursor = screen.cursor.min(self.state.pending().len().saturating_sub(1));
+      }
+      Command::CopyToId3v1(i) => {
+        for i in self.state.targets(i) {
+          self.state.get_file_mut(i).tags.copy_to_id3v1();
+        }
+      }
+      Command::CopyFromId3v1(i) => {
+        for i in self.state.targets(i) {
+          self.state.get_file_mut(i).tags.copy_from_id3v1();
+        }
+        self.sync_inputs();
+      }
+      Command::SelectPicture(j) => {
+        if let Screen::Home(screen) = &mut self.ui.state.screen {
+          screen.selected_picture = j;
+        }
+      }
+      Command::ImportPicture(i, replace, path) => {
+        let tags = &mut self.state.get_file_mut(i).tags;
+        match tags.import_picture(&path, replace) {
+          Ok(_) => {
+            let j = replace.unwrap_or(tags.pictures.len() - 1);
+            self.handle_command(Command::SelectPicture(j), sender);
+          }
+          Err(e) => {
+            let res = Err(e);
+            self.handle_command(Command::ShowResult("", "Cannot import the picture", res), sender);
+          }
+        }
+      }
+      Command::ExportPicture(i, j, path) => {
+        let res = self.state.get_file(i).tags.export_picture(j, &path);
+        let cmd = Command::ShowResult("Picture exported", "Cannot export the picture", res);
+        self.handle_command(cmd, sender);
+      }
+      Command::RemovePicture(i, j) => {
+        let tags = &mut self.state.get_file_mut(i).tags;
+        tags.remove_picture(j);
+        let j = j.min(tags.pictures.len().saturating_sub(1));
+        self.handle_command(Command::SelectPicture(j), sender);
+      }
+      Command::CyclePictureType(i, j) => {
+        self.state.get_file_mut(i).tags.cycle_picture_type(j);
+      }
+      Command::SetLyricsFrameCursor(j) => {
+        if let Screen::Lyrics(screen) = &mut self.ui.state.screen {
+          screen.frame_cursor = j;
+        }
+      }
+      Command::OpenLyricsFrame(pane, j) => {
+        if let Screen::Lyrics(screen) = &mut self.ui.state.screen {
+          let lyrics = &self.state.get_file(screen.index).tags.lyrics;
+          let other = match pane {
+            LyricsPane::Left => LyricsPane::Right,
+            LyricsPane::Right => LyricsPane::Left,
+          };
+          // the same frame is never edited in both panes at once
+          if j.is_some() && screen.pane(other).frame == j {
+            let frame = screen.pane(pane).frame;
+            screen.pane_mut(other).set_frame(frame, lyrics);
+          }
+          screen.pane_mut(pane).set_frame(j, lyrics);
+          screen.sync(lyrics, None);
+        }
+      }
+      Command::AddLyricsFrame => {
+        if let Screen::Lyrics(screen) = &mut self.ui.state.screen {
+          let tags = &mut self.state.get_file_mut(screen.index).tags;
+          let j = tags.add_lyrics();
+          screen.frame_cursor = j;
+          screen.pane_mut(LyricsPane::Left).set_frame(Some(j), &tags.lyrics);
+          screen.focused_el = LyricsFocusable::Text(LyricsPane::Left);
+        }
+      }
+      Command::RemoveLyricsFrame(j) => {
+        if let Screen::Lyrics(screen) = &mut self.ui.state.screen {
+          let tags = &mut self.state.get_file_mut(screen.index).tags;
+          let len = tags.lyrics.len();
+          tags.remove_lyrics(j);
+          let dropped = if tags.lyrics.len() < len { Some(j) } else { None };
+          screen.sync(&tags.lyrics, dropped);
+        }
+      }
+      Command::ToggleSyncedLyrics => {
+        if let Screen::Lyrics(screen) = &mut self.ui.state.screen {
+          screen.synced = !screen.synced;
+          screen.focused_el = match screen.focused_el {
+            LyricsFocusable::Text(pane) | LyricsFocusable::Synced(pane) => screen.text_el(pane),
+            el => el,
+          };
+        }
+      }
+      Command::ImportLrc(i, j, path) => {
+        let tags = &mut self.state.get_file_mut(i).tags;
+        match tags.import_lrc(j, &path) {
+          Ok(j) => {
+            if let Screen::Lyrics(screen) = &mut self.ui.state.screen {
+              if screen.panes.iter().all(|p| p.frame != Some(j)) {
+                screen.pane_mut(LyricsPane::Left).frame = Some(j);
+              }
+              screen.frame_cursor = j;
+              screen.synced = true;
+              screen.sync(&tags.lyrics, None);
+            }
+          }
+          Err(e) => {
+            let res = Err(e);
+            self.handle_command(Command::ShowResult("", "Cannot import the LRC file", res), sender);
+          }
+        }
+      }
+      Command::ExportLrc(i, j, path) => {
+        let res = self.state.get_file(i).tags.export_lrc(j, &path);
+        let cmd = Command::ShowResult("Lyrics exported", "Cannot export the lyrics", res);
+        self.handle_command(cmd, sender);
+      }
+      Command::SelectComment(j) => {
+        if let Screen::Comments(screen) = &mut self.ui.state.screen {
+          screen.select(Some(j), &self.state.get_file(screen.index).tags.comments);
+        }
+      }
+      Command::AddComment => {
+        if let Screen::Comments(screen) = &mut self.ui.state.screen {
+          let tags = &mut self.state.get_file_mut(screen.index).tags;
+          let j = tags.add_comment();
+          screen.select(Some(j), &tags.comments);
+          screen.focused_el = CommentsFocusable::Text;
+        }
+      }
+      Command::RemoveComment(j) => {
+        if let Screen::Comments(screen) = &mut self.ui.state.screen {
+          let tags = &mut self.state.get_file_mut(screen.index).tags;
+          tags.remove_comment(j);
+          let j = j.min(tags.comments.len().saturating_sub(1));
+          screen.select(Some(j), &tags.comments);
+        }
+      }
+      Command::SelectFrame(j) => {
+        if let Screen::Frames(screen) = &mut self.ui.state.screen {
+          screen.select(Some(j), &self.state.get_file(screen.index).tags.frames);
+        }
+      }
+      Command::AddFrame(id) => {
+        if let Screen::Frames(screen) = &mut self.ui.state.screen {
+          let tags = &mut self.state.get_file_mut(screen.index).tags;
+          let j = tags.add_frame(&id);
+          screen.select(Some(j), &tags.frames);
+          screen.focused_el = if id.is_empty() {
+            FramesFocusable::Id
+          } else {
+            FramesFocusable::Desc
+          };
+        }
+      }
+      Command::RemoveFrame(j) => {
+        if let Screen::Frames(screen) = &mut self.ui.state.screen {
+          let tags = &mut self.state.get_file_mut(screen.index).tags;
+          tags.remove_frame(j);
+          let j = j.min(tags.frames.len().saturating_sub(1));
+          screen.select(Some(j), &tags.frames);
+        }
+      }
+      Command::SelectSettingsRow(row) => {
+        if let Screen::Settings(screen) = &mut self.ui.state.screen {
+          screen.row = row;
+        }
+      }
+      Command::CycleSetting(row, forward) => {
+        if let Screen::Settings(screen) = &self.ui.state.screen {
+          let defaults = self.state.settings.write_options;
+          match screen.index {
+            Some(i) if row.per_file() => {
+              let tags = &mut self.state.get_file_mut(i).tags;
+              // the first change starts from the global options
+              let mut options = tags.write_options.current().copied().unwrap_or(defaults);
+              row.cycle(&mut options, forward);
+              tags.write_options.set(Some(options));
+            }
+            _ => {
+              let settings = &mut self.state.settings;
+              match row {
+                SettingsRow::KeepTimes => {
+                  settings.keep_times = !settings.keep_times;
+                }
+                _ => row.cycle(&mut settings.write_options, forward),
+              }
+              let res = self.state.settings.save(&self.state.settings_path());
+              if let Err(e) = res {
+                self.handle_command(Command::TagsSaved(Err(e.to_string())), sender);
+              }
+            }
+          }
+        }
+      }
+      Command::ResetSetting(row) => {
+        if let Screen::Settings(screen) = &self.ui.state.screen {
+          match screen.index {
+            Some(i) if row.per_file() => {
+              self.state.get_file_mut(i).tags.write_options.reset();
+            }
+            _ => {
+              match row {
+                SettingsRow::KeepTimes => {
+                  self.state.settings.keep_times = false;
+                }
+                _ => {
+                  self.state.settings.write_options = Default::default();
+                }
+              }
+              let res = self.state.settings.save(&self.state.settings_path());
+              if let Err(e) = res {
+                self.handle_command(Command::TagsSaved(Err(e.to_string())), sender);
+              }
+            }
+          }
+        }
+      }
+      Command::UseGlobalWriteOptions => {
+        if let Screen::Settings(screen) = &self.ui.state.screen {
+          if let Some(i) = screen.index {
+            self.state.get_file_mut(i).tags.write_options.set(None);
+          }
+        }
+      }
       Command::ResetHomeScreenTag(el) => {
         match &mut self.ui.state.screen {
           ui_enums::Screen::Home(screen) => {
@@ -174,28 +862,36 @@ impl App {
               }
               HomeFocusable::Table(_) => {}
               HomeFocusable::Editor(i, f_ed_el) => {
-                let mut tags = &mut self.state.get_file_mut(i).tags;
+                let targets = self.state.targets(i);
                 match f_ed_el {
-                  EditorFocusable::TitleInput => {
-                    tags.title.0.reset();
-                    screen.title_input.set_text(tags.title.0.to_string())
-                  }
-                  EditorFocusable::ArtistInput => {
-                    tags.artist.0.reset();
-                    screen.artist_input.set_text(tags.artist.0.to_string())
+                  EditorFocusable::LyricsButton => {
+                    for i in targets {
+                      self.state.get_file_mut(i).tags.reset_lyrics();
+                    }
                   }
-                  EditorFocusable::YearInput => {
-                    tags.year.0.reset();
-                    screen.year_input.set_text(tags.year.0.to_string())
+                  EditorFocusable::CommentsButton => {
+                    for i in targets {
+                      self.state.get_file_mut(i).tags.reset_comments();
+                    }
                   }
-                  EditorFocusable::GenreInput => {
-                    tags.genre.0.reset();
-                    screen.genre_input.set_text(tags.genre.0.to_string())
+                  EditorFocusable::ArtworkList => {
+                    for i in targets {
+                      self.state.get_file_mut(i).tags.reset_pictures();
+                    }
+                    screen.selected_picture = 0;
                   }
-                  EditorFocusable::LyricsButton => {
-                    tags.lyrics.lang.reset();
-                    tags.lyrics.desc.reset();
-                    tags.lyrics.text.reset();
+                  input_el => {
+                    for i in targets {
+                      let tags = &mut self.state.get_file_mut(i).tags;
+                      // the field's ID3v1 counterpart goes back along with it
+                      if let Some(f) = input_el.id3v1_field() {
+                        tags.id3v1.field_mut(f).reset();
+                      }
+                      if let Some(tag) = input_el.tag_mut(tags) {
+                        tag.0.reset();
+                      }
+                    }
+                    self.sync_inputs();
                   }
                 }
               }
@@ -205,52 +901,111 @@ impl App {
         }
       }
       Command::ResetLyricsScreenTag(el) => {
-        match &self.ui.state.screen {
+        match &mut self.ui.state.screen {
           Screen::Lyrics(screen) => {
-            let mut tags = &mut self.state.get_file_mut(screen.index).tags;
-            match screen.focused_el {
-              LyricsFocusable::Lang => tags.lyrics.lang.reset(),
-              LyricsFocusable::Desc => tags.lyrics.desc.reset(),
-              LyricsFocusable::Text => tags.lyrics.text.reset(),
+            let tags = &mut self.state.get_file_mut(screen.index).tags;
+            match el.pane().and_then(|pane| screen.pane(pane).frame) {
+              Some(j) => {
+                let lyrics = &mut tags.lyrics[j];
+                let text = match el {
+                  LyricsFocusable::Lang(_) => {
+                    lyrics.lang.reset();
+                    lyrics.lang.to_string()
+                  }
+                  LyricsFocusable::Desc(_) => {
+                    lyrics.desc.reset();
+                    lyrics.desc.to_string()
+                  }
+                  LyricsFocusable::Synced(_) => {
+                    lyrics.synced.reset();
+                    lyrics.synced_lines().join("\n")
+                  }
+                  _ => {
+                    lyrics.text.reset();
+                    lyrics.text.to_string()
+                  }
+                };
+                screen.pane_mut(el.pane().unwrap()).input_mut(el).unwrap().set_text(text);
+              }
+              None => {
+                tags.reset_lyrics();
+                screen.sync(&tags.lyrics, None);
+              }
             }
           }
           _ => {}
         }
       }
+      Command::ResetCommentsScreenTag(el) => {
+        if let Screen::Comments(screen) = &mut self.ui.state.screen {
+          let tags = &mut self.state.get_file_mut(screen.index).tags;
+          match (el, screen.selected) {
+            (CommentsFocusable::List, _) | (_, None) => {
+              tags.reset_comments();
+              screen.select(screen.selected, &tags.comments);
+            }
+            (el, Some(j)) => {
+              let comment = &mut tags.comments[j];
+              let tag = match el {
+                CommentsFocusable::Lang => &mut comment.lang,
+                CommentsFocusable::Desc => &mut comment.desc,
+                _ => &mut comment.text,
+              };
+              tag.reset();
+              let text = tag.to_string();
+              screen.input_mut(el).unwrap().set_text(text);
+            }
+          }
+        }
+      }
+      Command::ResetFramesScreenTag(el) => {
+        if let Screen::Frames(screen) = &mut self.ui.state.screen {
+          let tags = &mut self.state.get_file_mut(screen.index).tags;
+          match (el, screen.selected) {
+            (FramesFocusable::List, _) | (_, None) => {
+              tags.reset_frames();
+              screen.select(screen.selected, &tags.frames);
+            }
+            (el, Some(j)) => {
+              let frame = &mut tags.frames[j];
+              // the ID of an added frame is all there is to it
+              if !el.editable(frame) || el == FramesFocusable::Id {
+                return;
+              }
+              let tag = match el {
+                FramesFocusable::Desc => &mut frame.desc,
+                _ => &mut frame.value,
+              };
+              tag.reset();
+              let text = tag.to_string();
+              screen.input_mut(el).unwrap().set_text(text);
+            }
+          }
+        }
+      }
       Command::HandleHomeScreenInput(key_event, el) => {
         match &mut self.ui.state.screen {
           ui_enums::Screen::Home(screen) => {
             match el {
               HomeFocusable::Search => {
                 if screen.search_input.input_for_humans(key_event, false) {
+                  // files still coming from the scan are matched against it too
+                  self.state.search = screen.search_input.lines()[0].clone();
                   self.state.search_mp3_files(self.state.search.clone());
                 }
-                self.state.search = screen.search_input.lines()[0].clone();
               }
               HomeFocusable::Editor(i, editor_section) => {
-                let tags = &mut self.state.get_file_mut(i).tags;
-                match editor_section {
-                  EditorFocusable::TitleInput => {
-                    if screen.title_input.input_for_humans(key_event, false) {
-                      tags.title.0.edit(screen.title_input.lines()[0].clone());
-                    }
-                  }
-                  EditorFocusable::ArtistInput => {
-                    if screen.artist_input.input_for_humans(key_event, false) {
-                      tags.artist.0.edit(screen.artist_input.lines()[0].clone());
-                    }
-                  }
-                  EditorFocusable::YearInput => {
-                    if screen.year_input.input_for_humans(key_event, false) {
-                      tags.year.0.edit(screen.year_input.lines()[0].clone());
-                    }
-                  }
-                  EditorFocusable::GenreInput => {
-                    if screen.genre_input.input_for_humans(key_event, false) {
-                      tags.genre.0.edit(screen.genre_input.lines()[0].clone());
+                // buttons and the artwork list have no input
+                if let Some(input) = screen.input_mut(editor_section) {
+                  if input.input_for_humans(key_event, false) {
+                    let text = input.lines()[0].clone();
+                    for i in self.state.targets(i) {
+                      let tags = &mut self.state.get_file_mut(i).tags;
+                      if let Some(tag) = editor_section.tag_mut(tags) {
+                        tag.0.edit(text.clone());
+                      }
                     }
                   }
-                  EditorFocusable::LyricsButton => {}
                 }
               }
               _ => {}
@@ -263,27 +1018,175 @@ impl App {
         match &mut self.ui.state.screen {
           ui_enums::Screen::Lyrics(screen) => {
             let tags = &mut self.state.get_file_mut(screen.index).tags;
-            match el {
-              LyricsFocusable::Lang => {
-                if screen.lang_input.input_for_humans(key_event, false) {
-                  tags.lyrics.lang.edit(screen.lang_input.lines()[0].clone());
-                }
-              }
-              LyricsFocusable::Desc => {
-                if screen.desc_input.input_for_humans(key_event, false) {
-                  tags.lyrics.desc.edit(screen.desc_input.lines()[0].clone());
-                }
-              }
-              LyricsFocusable::Text => {
-                if screen.text_textarea.input_for_humans(key_event, true) {
-                  tags.lyrics.text.edit(screen.text_textarea.lines()[0].clone());
+            let Some(pane) = el.pane() else {
+              return;
+            };
+            let pane = screen.pane_mut(pane);
+            let multiline = matches!(el, LyricsFocusable::Text(_) | LyricsFocusable::Synced(_));
+            if pane.input_mut(el).unwrap().input_for_humans(key_event, multiline) {
+              let lines = pane.input_mut(el).unwrap().lines().to_vec();
+              // typing into an empty pane creates a new frame
+              let j = match pane.frame {
+                Some(j) => j,
+                None => {
+                  let j = tags.add_lyrics();
+                  pane.set_frame(Some(j), &tags.lyrics);
+                  pane.input_mut(el).unwrap().set_text(lines.join("\n"));
+                  j
                 }
+              };
+              let lyrics = &mut tags.lyrics[j];
+              match el {
+                LyricsFocusable::Lang(_) => lyrics.lang.edit(lines[0].clone()),
+                LyricsFocusable::Desc(_) => lyrics.desc.edit(lines[0].clone()),
+                // lines without a timestamp are flagged by the screen until fixed
+                LyricsFocusable::Synced(_) => lyrics.edit_synced_lines(&lines).unwrap_or_default(),
+                _ => lyrics.edit_text_lines(&lines),
               }
             }
           }
           _ => {}
         }
       }
+      Command::HandleCommentsScreenInput(key_event, el) => {
+        if let Screen::Comments(screen) = &mut self.ui.state.screen {
+          let tags = &mut self.state.get_file_mut(screen.index).tags;
+          let multiline = el == CommentsFocusable::Text;
+          let Some(input) = screen.input_mut(el) else {
+            return;
+          };
+          if input.input_for_humans(key_event, multiline) {
+            let lines = input.lines().to_vec();
+            // typing without a selected comment creates a new one
+            let j = match screen.selected {
+              Some(j) => j,
+              None => {
+                let j = tags.add_comment();
+                screen.select(Some(j), &tags.comments);
+                screen.input_mut(el).unwrap().set_text(lines.join("\n"));
+                j
+              }
+            };
+            let comment = &mut tags.comments[j];
+            match el {
+              CommentsFocusable::Lang => comment.lang.edit(lines[0].clone()),
+              CommentsFocusable::Desc => comment.desc.edit(lines[0].clone()),
+              _ => comment.edit_text_lines(&lines),
+            }
+          }
+        }
+      }
+      Command::HandleFramesScreenInput(key_event, el) => {
+        if let Screen::Frames(screen) = &mut self.ui.state.screen {
+          let tags = &mut self.state.get_file_mut(screen.index).tags;
+          let Some(j) = screen.selected else {
+            return;
+          };
+          let frame = &mut tags.frames[j];
+          if !el.editable(frame) {
+            return;
+          }
+          let Some(input) = screen.input_mut(el) else {
+            return;
+          };
+          if input.input_for_humans(key_event, false) {
+            let text = input.lines()[0].clone();
+            match el {
+              FramesFocusable::Id => frame.edit_id(text),
+              FramesFocusable::Desc => frame.desc.edit(text),
+              _ => frame.value.edit(text),
+            }
+          }
+        }
+      }
+    }
+  }
+  /// Saves the files, returning the name of each file with its result.
+  fn save_files(
+    &mut self,
+    indexes: Vec<usize>,
+    id3v1_action: Id3v1Action
+  ) -> Vec<(String, Result<(), String>)> {
+    let settings = self.state.settings.clone();
+    let backups = self.state.backups.clone();
+    indexes
+      .into_iter()
+      .map(|i| {
+        self.state.forget_cached(i);
+        let file = self.state.get_file_mut(i);
+        let res = file.tags.save(&settings, id3v1_action, Some(&backups));
+        // steps would bring back values read before the save
+        if res.is_ok() {
+          file.history.clear();
+        }
+        let name = file.name.clone();
+        if let Err(e) = &res {
+          self.state.log.error(format!("{}: {}", name, e));
+        }
+        (name, res.map_err(|e| e.to_string()))
+      })
+      .collect()
+  }
+  /// Splits the files into the ones that can be saved and the ones something else changed since
+  /// they were read.
+  fn split_changed_on_disk(&self, indexes: Vec<usize>) -> (Vec<usize>, Vec<usize>) {
+    indexes.into_iter().partition(|&i| !self.state.get_file(i).tags.changed_on_disk())
+  }
+  /// Asks whether to reload or overwrite the files changed on disk, showing what changed.
+  fn ask_about_changes_on_disk(
+    &self,
+    indexes: Vec<usize>,
+    id3v1_action: Id3v1Action,
+    sender: Sender<Command>
+  ) {
+    if indexes.is_empty() {
+      return;
+    }
+    let changes = indexes
+      .iter()
+      .map(|&i| {
+        let file = self.state.get_file(i);
+        let (external, conflicts) = file.tags.merge(&SongTags::new(file.tags.path().into()));
+        (file.name.clone(), external, conflicts)
+      })
+      .collect::<Vec<_>>();
+    let modal = ExternalChangesModal::new(indexes, id3v1_action, &changes);
+    sender.send(Command::OpenModal(Modal::ExternalChanges(modal)));
+  }
+  /// Files the commands of the current screen apply to, the selected ones on the home screen.
+  fn targets(&self) -> Vec<usize> {
+    match (&self.ui.state.screen, self.ui.selected_song_index()) {
+      (Screen::Home(_), Some(i)) => self.state.targets(i),
+      (_, i) => i.into_iter().collect(),
+    }
+  }
+  /// Refills the inputs of the screen after the tags were replaced.
+  fn sync_inputs(&mut self) {
+    let Some(i) = self.ui.selected_song_index() else {
+      return;
+    };
+    let tags = &self.state.get_file(i).tags;
+    match &mut self.ui.state.screen {
+      Screen::Home(screen) => {
+        let targets = self.state.targets(i);
+        let edited = targets.iter().map(|&i| &self.state.get_file(i).tags).collect::<Vec<_>>();
+        screen.set_inputs(&edited);
+        screen.selected_picture = screen.selected_picture.min(
+          tags.pictures.len().saturating_sub(1)
+        );
+      }
+      Screen::Lyrics(screen) => {
+        screen.sync(&tags.lyrics, None);
+      }
+      Screen::Comments(screen) => {
+        let j = screen.selected.map(|j| j.min(tags.comments.len().saturating_sub(1)));
+        screen.select(j, &tags.comments);
+      }
+      Screen::Frames(screen) => {
+        let j = screen.selected.map(|j| j.min(tags.frames.len().saturating_sub(1)));
+        screen.select(j, &tags.frames);
+      }
+      Screen::Settings(_) | Screen::Pending(_) | Screen::Log(_) | Screen::History(_) => {}
     }
   }
 }
@@ -0,0 +1,183 @@
+use std::{
+  fs::{ self, File, FileTimes, Metadata, OpenOptions },
+  io,
+  path::{ Path, PathBuf },
+};
+use super::error::TagError;
+
+/// Changes the file at `path` through a copy next to it: `write` gets the path of the copy, which
+/// is synced to disk and renamed over the file. A crash leaves either the old or the new file,
+/// never a truncated one. `keep_times` puts the access and modification times of the file back
+/// on the copy. A symbolic link is followed so that the file it points to is the one replaced.
+///
+/// The copy is a new file, so a file with other hard links, or whose owner cannot be given to the
+/// copy, is written in place instead, without the protection against crashes.
+pub fn replace(
+  path: &str,
+  keep_times: bool,
+  write: impl FnOnce(&str) -> Result<(), TagError>
+) -> Result<(), TagError> {
+  let io_error = |e| TagError::from_io(e, path);
+  let original = fs::canonicalize(path).map_err(io_error)?;
+  let metadata = fs::metadata(&original).map_err(io_error)?;
+  if hard_linked(&metadata) {
+    return write_in_place(path, &original, &metadata, keep_times, write);
+  }
+  let temp = temp_path(&original);
+  fs::copy(&original, &temp).map_err(io_error)?;
+  if !keep_owner(&temp, &metadata) {
+    let _ = fs::remove_file(&temp);
+    return write_in_place(path, &original, &metadata, keep_times, write);
+  }
+  let res = write(&temp.to_string_lossy()).and_then(|_| {
+    finish(&temp, &metadata, keep_times).map_err(io_error)?;
+    fs::rename(&temp, &original).map_err(io_error)
+  });
+  match res {
+    Ok(_) => {
+      // the rename is on disk once the directory is, directories cannot be opened everywhere
+      if let Some(dir) = original.parent().and_then(|dir| File::open(dir).ok()) {
+        let _ = dir.sync_all();
+      }
+      Ok(())
+    }
+    Err(e) => {
+      let _ = fs::remove_file(&temp);
+      Err(e)
+    }
+  }
+}
+
+/// Lets `write` change the file at `original`, the resolved `path`, itself.
+fn write_in_place(
+  path: &str,
+  original: &Path,
+  metadata: &Metadata,
+  keep_times: bool,
+  write: impl FnOnce(&str) -> Result<(), TagError>
+) -> Result<(), TagError> {
+  write(&original.to_string_lossy())?;
+  finish(original, metadata, keep_times).map_err(|e| TagError::from_io(e, path))
+}
+
+/// Puts the times of `metadata` back on the written file at `path` when `keep_times` is set and
+/// syncs it to disk.
+fn finish(path: &Path, metadata: &Metadata, keep_times: bool) -> io::Result<()> {
+  let file = OpenOptions::new().write(true).open(path)?;
+  if keep_times {
+    let times = FileTimes::new()
+      .set_accessed(metadata.accessed()?)
+      .set_modified(metadata.modified()?);
+    file.set_times(times)?;
+  }
+  file.sync_all()
+}
+
+#[cfg(unix)]
+fn hard_linked(metadata: &Metadata) -> bool {
+  use std::os::unix::fs::MetadataExt;
+  metadata.nlink() > 1
+}
+
+#[cfg(not(unix))]
+fn hard_linked(_: &Metadata) -> bool {
+  false
+}
+
+/// Gives the file at `path` the owner and group of `metadata`, which takes root when the file
+/// belongs to someone else.
+#[cfg(unix)]
+fn keep_owner(path: &Path, metadata: &Metadata) -> bool {
+  use std::os::unix::fs::{ chown, MetadataExt };
+  chown(path, Some(metadata.uid()), Some(metadata.gid())).is_ok()
+}
+
+#[cfg(not(unix))]
+fn keep_owner(_: &Path, _: &Metadata) -> bool {
+  true
+}
+
+/// Hidden file next to `path` the new content is written to.
+fn temp_path(path: &Path) -> PathBuf {
+  let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+  path.with_file_name(format!(".{}.tagchr-tmp", name))
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{ fs::{ self, File, FileTimes }, path::Path, time::{ Duration, SystemTime } };
+  use crate::app::{ error::TagError, testing::TempDir };
+  use super::{ replace, temp_path };
+
+  #[test]
+  fn replaces_through_a_copy() {
+    let dir = TempDir::new("atomic");
+    let path = dir.join("song.mp3");
+    let song_path = path.to_str().unwrap();
+    fs::write(&path, "old").unwrap();
+    let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+    File::options()
+      .write(true)
+      .open(&path)
+      .unwrap()
+      .set_times(FileTimes::new().set_accessed(old).set_modified(old))
+      .unwrap();
+
+    replace(song_path, true, |temp| {
+      assert_eq!(fs::read_to_string(temp).unwrap(), "old");
+      fs::write(temp, "new").map_err(|e| TagError::from_io(e, temp))
+    }).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+    assert_eq!(fs::metadata(&path).unwrap().modified().unwrap(), old);
+    assert!(!temp_path(&path).exists());
+
+    // a failed write leaves the file alone
+    let failed = replace(song_path, false, |temp| {
+      fs::write(temp, "broken").unwrap();
+      Err(TagError::Io("write failed".into()))
+    });
+    assert!(failed.is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+    assert!(!temp_path(&path).exists());
+
+    replace(song_path, false, |temp| {
+      fs::write(temp, "newer").map_err(|e| TagError::from_io(e, temp))
+    }).unwrap();
+    assert_ne!(fs::metadata(&path).unwrap().modified().unwrap(), old);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn replaces_the_target_of_a_link() {
+    let dir = TempDir::new("atomic");
+    fs::create_dir_all(dir.join("music")).unwrap();
+    let target = dir.join("music/song.mp3");
+    let link = dir.join("song.mp3");
+    fs::write(&target, "old").unwrap();
+    std::os::unix::fs::symlink(&target, &link).unwrap();
+
+    replace(link.to_str().unwrap(), false, |temp| {
+      fs::write(temp, "new").map_err(|e| TagError::from_io(e, temp))
+    }).unwrap();
+    assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+    assert_eq!(fs::read_to_string(&target).unwrap(), "new");
+    assert!(!temp_path(&target).exists() && !temp_path(&link).exists());
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn writes_hard_linked_files_in_place() {
+    let dir = TempDir::new("atomic");
+    let path = dir.join("song.mp3");
+    let link = dir.join("link.mp3");
+    fs::write(&path, "old").unwrap();
+    fs::hard_link(&path, &link).unwrap();
+
+    replace(path.to_str().unwrap(), false, |temp| {
+      assert_eq!(Path::new(temp), fs::canonicalize(&path).unwrap());
+      fs::write(temp, "new").map_err(|e| TagError::from_io(e, temp))
+    }).unwrap();
+    assert_eq!(fs::read_to_string(&link).unwrap(), "new");
+    assert!(!temp_path(&path).exists());
+  }
+}
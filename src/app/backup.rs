@@ -0,0 +1,242 @@
+use std::{ fs, io::{ self, Cursor }, path::PathBuf };
+use chrono::{ Local, NaiveDateTime, TimeDelta };
+use id3::{ v1, Content, Tag };
+use super::{ atomic, disk::{ self, tag_sizes }, error::TagError, id3v1, lrc, tag::Change };
+
+const TIME_FORMAT: &str = "%Y%m%dT%H%M%S%.3f";
+const EXTENSION: &str = "id3";
+/// Backups kept per file, the oldest are dropped as new ones are stored.
+const KEPT: usize = 20;
+/// File in the directory of the backups of a file holding the path of the file.
+const PATH_FILE: &str = "path";
+
+/// Tags of the files as they were before being overwritten, one directory per file named after
+/// a hash of its path and one backup per save named after its time.
+#[derive(Clone)]
+pub struct Backups {
+  dir: PathBuf,
+}
+
+/// A stored tag of a file.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Backup {
+  pub path: PathBuf,
+  pub time: NaiveDateTime,
+}
+
+impl Backups {
+  pub fn new(dir: PathBuf) -> Self {
+    Self { dir }
+  }
+  /// Directory of the backups of the file at `song_path`, named after a hash of the path so that
+  /// the name stays short however deep the file is.
+  fn file_dir(&self, song_path: &str) -> PathBuf {
+    self.dir.join(format!("{:016x}", disk::hash(song_path.as_bytes())))
+  }
+  /// Stores the ID3v2 and ID3v1 tags of the file at `song_path` as they are on disk, an empty
+  /// backup stands for a file without tags. Only the latest `KEPT` backups of the file are kept.
+  pub fn store(&self, song_path: &str) -> io::Result<Backup> {
+    let tags = disk::read_tags(song_path)?;
+    let dir = self.file_dir(song_path);
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(PATH_FILE), song_path)?;
+    let name = |time: NaiveDateTime| format!("{}.{}", time.format(TIME_FORMAT), EXTENSION);
+    // saves within the same millisecond keep apart
+    let mut time = Local::now().naive_local();
+    while dir.join(name(time)).exists() {
+      time += TimeDelta::milliseconds(1);
+    }
+    let path = dir.join(name(time));
+    fs::write(&path, tags)?;
+    for old in self.list(song_path).into_iter().skip(KEPT) {
+      // a backup left behind is dropped with the next one
+      let _ = fs::remove_file(old.path);
+    }
+    Ok(Backup { path, time })
+  }
+  /// Backups of the file at `song_path`, the latest first.
+  pub fn list(&self, song_path: &str) -> Vec<Backup> {
+    let Ok(entries) = fs::read_dir(self.file_dir(song_path)) else {
+      return Vec::new();
+    };
+    let mut backups = entries
+      .filter_map(Result::ok)
+      .filter_map(|entry| {
+        let path = entry.path();
+        let stem = path.file_stem()?.to_str()?;
+        let time = NaiveDateTime::parse_from_str(stem, TIME_FORMAT).ok()?;
+        Some(Backup { path, time })
+      })
+      .collect::<Vec<_>>();
+    backups.sort_by(|a, b| b.time.cmp(&a.time));
+    backups
+  }
+  /// Puts the tags of `backup` back into the file at `song_path`, leaving the audio alone. The tags
+  /// being replaced are backed up first so the restore can be undone the same way. `keep_times`
+  /// keeps the modification time of the file.
+  pub fn restore(
+    &self,
+    song_path: &str,
+    backup: &Backup,
+    keep_times: bool
+  ) -> Result<(), TagError> {
+    let stored = fs::read(&backup.path).map_err(|e| {
+      TagError::from_io(e, &backup.path.to_string_lossy())
+    })?;
+    let io_error = |e| TagError::from_io(e, song_path);
+    self.store(song_path).map_err(io_error)?;
+    let data = fs::read(song_path).map_err(io_error)?;
+    let (v2, v1) = tag_sizes(&data);
+    let (stored_v2, _) = tag_sizes(&stored);
+    let audio = &data[v2..data.len() - v1];
+    let restored = [&stored[..stored_v2], audio, &stored[stored_v2..]].concat();
+    atomic::replace(song_path, keep_times, |temp| {
+      fs::write(temp, restored).map_err(|e| TagError::from_io(e, song_path))
+    })
+  }
+}
+
+impl Backup {
+  /// What restoring the backup changes in the tags `current` read from the file.
+  pub fn changes(&self, current: &[u8]) -> io::Result<Vec<Change>> {
+    let stored = fields(&fs::read(&self.path)?);
+    let current = fields(current);
+    let mut changes = Vec::new();
+    for (field, value) in &current {
+      let restored = stored.iter().find(|(f, _)| f == field).map(|(_, v)| v.clone());
+      if restored.as_ref() != Some(value) {
+        changes.push(Change {
+          field: field.clone(),
+          original: Some(value.clone()),
+          current: restored,
+        });
+      }
+    }
+    for (field, value) in stored {
+      if !current.iter().any(|(f, _)| *f == field) {
+        changes.push(Change { field, original: None, current: Some(value) });
+      }
+    }
+    Ok(changes)
+  }
+}
+
+/// Frames of the ID3v2 tag and fields of the ID3v1 tag in `data` as named values, repeated
+/// frames are numbered.
+fn fields(data: &[u8]) -> Vec<(String, String)> {
+  let (v2, v1) = tag_sizes(data);
+  let mut fields: Vec<(String, String)> = Vec::new();
+  if let Ok(tag) = Tag::read_from2(Cursor::new(&data[..v2])) {
+    for frame in tag.frames() {
+      let value = match frame.content() {
+        Content::Picture(p) => {
+          let size = p.data.len();
+          format!("{} {} ({}, {} bytes)", p.picture_type, p.description, p.mime_type, size)
+        }
+        Content::SynchronisedLyrics(lyrics) => lrc::format(&lyrics.content),
+        content => content.to_string(),
+      };
+      let same = fields
+        .iter()
+        .filter(|(f, _)| f.split(" #").next() == Some(frame.id()))
+        .count();
+      let field = match same {
+        0 => frame.id().to_string(),
+        n => format!("{} #{}", frame.id(), n + 1),
+      };
+      fields.push((field, value));
+    }
+  }
+  if let Ok(tag) = v1::Tag::read_from(Cursor::new(&data[data.len() - v1..])) {
+    for (field, value) in [
+      ("Title", tag.title),
+      ("Artist", tag.artist),
+      ("Album", tag.album),
+      ("Year", tag.year),
+      ("Comment", tag.comment),
+      ("Track", tag.track.map(|t| t.to_string()).unwrap_or_default()),
+      ("Genre", id3v1::genre_name(tag.genre_id).unwrap_or_default()),
+    ] {
+      if !value.is_empty() {
+        fields.push((format!("ID3v1 {}", field), value));
+      }
+    }
+  }
+  fields
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{ fs, path::PathBuf };
+  use id3::{ Tag, TagLike, Version };
+  use crate::app::{
+    settings::Settings,
+    tag::{ Id3v1Action, SongTags },
+    testing::{ TempDir, AUDIO },
+  };
+  use super::{ Backups, KEPT, PATH_FILE };
+
+  #[test]
+  fn backs_up_and_restores_tags() {
+    let dir = TempDir::new("backups");
+    let backups = Backups::new(dir.join("backups"));
+    let path = dir.mp3("song.mp3");
+    let song_path = path.to_str().unwrap();
+    let mut tag = Tag::new();
+    tag.set_title("Old Title");
+    tag.set_artist("Artist");
+    tag.write_to_path(&path, Version::Id3v24).unwrap();
+
+    let mut tags = SongTags::new(song_path.into());
+    tags.title.0.edit("New Title".into());
+    tags.copy_to_id3v1();
+    tags.save(&Settings::default(), Id3v1Action::Write, Some(&backups)).unwrap();
+    let listed = backups.list(song_path);
+    assert_eq!(listed.len(), 1);
+
+    let changes = listed[0].changes(&fs::read(&path).unwrap()).unwrap();
+    let title = changes.iter().find(|c| c.field == "TIT2").unwrap();
+    assert_eq!(title.original.as_deref(), Some("New Title"));
+    assert_eq!(title.current.as_deref(), Some("Old Title"));
+    // the ID3v1 tag did not exist before the save
+    assert!(changes.iter().any(|c| c.field == "ID3v1 Title" && c.current.is_none()));
+
+    backups.restore(song_path, &listed[0], false).unwrap();
+    assert_eq!(Tag::read_from_path(&path).unwrap().title(), Some("Old Title"));
+    assert!(id3::v1::Tag::read_from_path(&path).is_err());
+    assert!(fs::read(&path).unwrap().ends_with(&AUDIO.repeat(64)));
+    // the restore is backed up too
+    assert_eq!(backups.list(song_path).len(), 2);
+  }
+
+  #[test]
+  fn keeps_the_latest_backups() {
+    let dir = TempDir::new("backups");
+    let backups = Backups::new(dir.join("backups"));
+    let path = dir.mp3("song.mp3");
+    let song_path = path.to_str().unwrap();
+
+    let stored = (0..KEPT + 2).map(|_| backups.store(song_path).unwrap()).collect::<Vec<_>>();
+    let listed = backups.list(song_path);
+    assert_eq!(listed.len(), KEPT);
+    assert_eq!(listed[0].path, stored[KEPT + 1].path);
+    assert!(!stored[0].path.exists() && !stored[1].path.exists());
+  }
+
+  #[test]
+  fn backs_up_files_with_long_paths() {
+    let dir = TempDir::new("backups");
+    let backups = Backups::new(dir.join("backups"));
+    let song_dir = (0..4).fold(PathBuf::new(), |path, i| path.join(format!("{:0>80}", i)));
+    fs::create_dir_all(dir.join(&song_dir)).unwrap();
+    let path = dir.mp3(song_dir.join("song.mp3"));
+    let song_path = path.to_str().unwrap();
+    assert!(song_path.len() > 255);
+
+    let stored = backups.store(song_path).unwrap();
+    let listed = backups.list(song_path);
+    assert_eq!(listed.iter().map(|b| &b.path).collect::<Vec<_>>(), [&stored.path]);
+    let path_file = stored.path.with_file_name(PATH_FILE);
+    assert_eq!(fs::read_to_string(path_file).unwrap(), song_path);
+  }
+}
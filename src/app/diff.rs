@@ -0,0 +1,59 @@
+/// A line of a line-level diff.
+#[derive(Clone, PartialEq, Debug)]
+pub enum DiffLine {
+  Same(String),
+  Removed(String),
+  Added(String),
+}
+
+/// Line-level diff of `original` and `current` built on their longest common subsequence,
+/// removed lines come before the lines added in their place.
+pub fn lines(original: &str, current: &str) -> Vec<DiffLine> {
+  let a = original.split('\n').collect::<Vec<_>>();
+  let b = current.split('\n').collect::<Vec<_>>();
+  // common[i][j] is the length of the common subsequence of a[i..] and b[j..]
+  let mut common = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+  for i in (0..a.len()).rev() {
+    for j in (0..b.len()).rev() {
+      common[i][j] = if a[i] == b[j] {
+        common[i + 1][j + 1] + 1
+      } else {
+        common[i + 1][j].max(common[i][j + 1])
+      };
+    }
+  }
+  let (mut i, mut j) = (0, 0);
+  let mut diff = Vec::new();
+  while i < a.len() || j < b.len() {
+    if i < a.len() && j < b.len() && a[i] == b[j] {
+      diff.push(DiffLine::Same(a[i].to_string()));
+      i += 1;
+      j += 1;
+    } else if i < a.len() && (j == b.len() || common[i + 1][j] >= common[i][j + 1]) {
+      diff.push(DiffLine::Removed(a[i].to_string()));
+      i += 1;
+    } else {
+      diff.push(DiffLine::Added(b[j].to_string()));
+      j += 1;
+    }
+  }
+  diff
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{ lines, DiffLine::{ Added, Removed, Same } };
+
+  #[test]
+  fn diffs_lines() {
+    assert_eq!(lines("a\nb\nc", "a\nx\nc\nd"), vec![
+      Same("a".into()),
+      Removed("b".into()),
+      Added("x".into()),
+      Same("c".into()),
+      Added("d".into()),
+    ]);
+    assert_eq!(lines("same", "same"), vec![Same("same".into())]);
+    assert_eq!(lines("", "new"), vec![Removed("".into()), Added("new".into())]);
+  }
+}
@@ -0,0 +1,137 @@
+use std::{
+  fs::{ self, File },
+  hash::Hasher,
+  io::{ self, Read, Seek, SeekFrom },
+  time::SystemTime,
+};
+
+/// Size of an ID3v1 tag, kept at the end of a file.
+const V1_SIZE: usize = 128;
+const HEADER_SIZE: usize = 10;
+
+/// A file as it was when its tags were read, to tell whether something else changed it since.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Fingerprint {
+  pub modified: SystemTime,
+  pub size: u64,
+  /// Hash of the ID3v2 and ID3v1 tags as stored in the file.
+  pub tag_hash: u64,
+}
+
+impl Fingerprint {
+  /// A file last modified at `modified` holding `size` bytes with the tags `tags`, as returned by
+  /// `read_tags`.
+  pub fn new(modified: SystemTime, size: u64, tags: &[u8]) -> Self {
+    Self { modified, size, tag_hash: hash(tags) }
+  }
+  pub fn of(path: &str) -> io::Result<Self> {
+    let metadata = fs::metadata(path)?;
+    Ok(Self::new(metadata.modified()?, metadata.len(), &read_tags(path)?))
+  }
+}
+
+/// FNV-1a, stable across builds unlike the hasher of the standard library.
+pub struct Fnv(u64);
+
+impl Default for Fnv {
+  fn default() -> Self {
+    Self(0xcbf29ce484222325)
+  }
+}
+
+impl Hasher for Fnv {
+  fn write(&mut self, bytes: &[u8]) {
+    self.0 = bytes.iter().fold(self.0, |hash, &b| (hash ^ (b as u64)).wrapping_mul(0x100000001b3));
+  }
+  fn finish(&self) -> u64 {
+    self.0
+  }
+}
+
+/// Hash of `data` with [`Fnv`], stable across builds.
+pub fn hash(data: &[u8]) -> u64 {
+  let mut hasher = Fnv::default();
+  hasher.write(data);
+  hasher.finish()
+}
+
+/// Size of the ID3v2 tag starting with `header`, 0 when it is not an ID3v2 header.
+fn v2_size(header: &[u8]) -> usize {
+  match header {
+    [b'I', b'D', b'3', _, _, flags, s0, s1, s2, s3, ..] => {
+      // the size is syncsafe, 7 bits per byte, and leaves out the header and the footer
+      let size = [s0, s1, s2, s3]
+        .iter()
+        .fold(0, |size, &&b| (size << 7) | ((b & 0x7f) as usize));
+      let footer = if flags & 0x10 != 0 { HEADER_SIZE } else { 0 };
+      HEADER_SIZE + size + footer
+    }
+    _ => 0,
+  }
+}
+
+/// Sizes of the ID3v2 tag at the start of `data` and of the ID3v1 tag at its end, 0 when missing.
+pub fn tag_sizes(data: &[u8]) -> (usize, usize) {
+  let v2 = v2_size(data).min(data.len());
+  let rest = &data[v2..];
+  let v1 = if rest.len() >= V1_SIZE && rest[rest.len() - V1_SIZE..].starts_with(b"TAG") {
+    V1_SIZE
+  } else {
+    0
+  };
+  (v2, v1)
+}
+
+/// The ID3v2 tag followed by the ID3v1 tag of the file at `path`, the audio is not read.
+pub fn read_tags(path: &str) -> io::Result<Vec<u8>> {
+  let mut file = File::open(path)?;
+  let len = file.metadata()?.len() as usize;
+  let mut header = Vec::new();
+  (&mut file).take(HEADER_SIZE as u64).read_to_end(&mut header)?;
+  let mut tags = header;
+  let v2 = v2_size(&tags).min(len);
+  (&mut file).take((v2 - tags.len().min(v2)) as u64).read_to_end(&mut tags)?;
+  tags.truncate(v2);
+  if len - v2 >= V1_SIZE {
+    let mut v1 = vec![0; V1_SIZE];
+    file.seek(SeekFrom::End(-(V1_SIZE as i64)))?;
+    file.read_exact(&mut v1)?;
+    if v1.starts_with(b"TAG") {
+      tags.extend(v1);
+    }
+  }
+  Ok(tags)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs;
+  use id3::{ v1, Tag, TagLike, Version };
+  use crate::app::{ id3v1, testing::TempDir };
+  use super::{ read_tags, tag_sizes, Fingerprint };
+
+  #[test]
+  fn reads_tags_and_fingerprints() {
+    let dir = TempDir::new("disk");
+    let path = dir.mp3("song.mp3");
+    let song_path = path.to_str().unwrap();
+    assert!(read_tags(song_path).unwrap().is_empty());
+
+    let mut tag = Tag::new();
+    tag.set_title("Title");
+    tag.write_to_path(&path, Version::Id3v24).unwrap();
+    id3v1::write_to_path(song_path, &v1::Tag { title: "Title".into(), ..Default::default() })
+      .unwrap();
+    let tags = read_tags(song_path).unwrap();
+    let data = fs::read(&path).unwrap();
+    let (v2, v1) = tag_sizes(&data);
+    assert_eq!(v1, 128);
+    assert_eq!(tags, [&data[..v2], &data[data.len() - v1..]].concat());
+
+    let before = Fingerprint::of(song_path).unwrap();
+    assert_eq!(Fingerprint::of(song_path).unwrap(), before);
+    tag.set_title("Other");
+    tag.write_to_path(&path, Version::Id3v24).unwrap();
+    assert_ne!(Fingerprint::of(song_path).unwrap().tag_hash, before.tag_hash);
+  }
+}
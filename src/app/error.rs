@@ -0,0 +1,85 @@
+use std::{ fmt, io };
+use id3::ErrorKind;
+
+/// Why a tag could not be read or written.
+#[derive(Clone, PartialEq, Debug)]
+pub enum TagError {
+  /// The file at the path cannot be opened by the user.
+  PermissionDenied(String),
+  /// Nothing is at the path anymore.
+  NotFound(String),
+  /// A frame or feature the tag library cannot read or write.
+  UnsupportedFrame(String),
+  /// The tag is damaged.
+  Malformed(String),
+  /// Edited fields that cannot be saved, one reason per field.
+  Invalid(Vec<String>),
+  /// Something else changed the file since its tags were read.
+  ChangedOnDisk(String),
+  Io(String),
+}
+
+impl TagError {
+  /// Error of reading or writing the tag of the file at `path`.
+  pub fn from_id3(e: id3::Error, path: &str) -> Self {
+    match e.kind {
+      ErrorKind::Io(e) => Self::from_io(e, path),
+      ErrorKind::UnsupportedFeature => TagError::UnsupportedFrame(e.description),
+      ErrorKind::StringDecoding(_) |
+      ErrorKind::NoTag |
+      ErrorKind::Parsing |
+      ErrorKind::InvalidInput => TagError::Malformed(e.description),
+    }
+  }
+  pub fn from_io(e: io::Error, path: &str) -> Self {
+    match e.kind() {
+      io::ErrorKind::PermissionDenied => TagError::PermissionDenied(path.into()),
+      io::ErrorKind::NotFound => TagError::NotFound(path.into()),
+      _ => TagError::Io(format!("{}: {}", path, e)),
+    }
+  }
+}
+
+impl fmt::Display for TagError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      TagError::PermissionDenied(path) => write!(f, "Permission denied: {}", path),
+      TagError::NotFound(path) => write!(f, "File not found: {}", path),
+      TagError::UnsupportedFrame(desc) => write!(f, "Unsupported frame: {}", desc),
+      TagError::Malformed(desc) => write!(f, "Malformed tag: {}", desc),
+      TagError::Invalid(errors) => write!(f, "{}", errors.join("\n")),
+      TagError::ChangedOnDisk(path) => write!(f, "Changed on disk since it was read: {}", path),
+      TagError::Io(desc) => write!(f, "IO error: {}", desc),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::io;
+  use id3::{ Error, ErrorKind };
+  use super::TagError;
+
+  #[test]
+  fn maps_errors() {
+    let io_error = |kind| Error::from(io::Error::from(kind));
+    assert_eq!(
+      TagError::from_id3(io_error(io::ErrorKind::PermissionDenied), "a.mp3"),
+      TagError::PermissionDenied("a.mp3".into())
+    );
+    assert_eq!(
+      TagError::from_id3(io_error(io::ErrorKind::NotFound), "a.mp3").to_string(),
+      "File not found: a.mp3"
+    );
+    assert_eq!(
+      TagError::from_id3(Error::new(ErrorKind::UnsupportedFeature, "ID3v2.2 writing"), "a.mp3"),
+      TagError::UnsupportedFrame("ID3v2.2 writing".into())
+    );
+    assert!(
+      matches!(
+        TagError::from_id3(Error::new(ErrorKind::Parsing, "bad frame"), "a.mp3"),
+        TagError::Malformed(_)
+      )
+    );
+  }
+}
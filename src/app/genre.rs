@@ -0,0 +1,119 @@
+use super::id3v1;
+
+/// Separates genres in the Genre input.
+pub const SEPARATOR: &str = "; ";
+
+/// Decodes the genres of a TCON frame. ID3v2.4 separates genres with nulls and may use plain
+/// ID3v1 numbers, ID3v2.3 references them as "(17)", optionally refined by a name as in
+/// "(17)Rock", the refinement then takes the place of the genre it refines.
+pub fn decode(tcon: &str) -> Vec<String> {
+  let mut genres = Vec::new();
+  for value in tcon.split('\0') {
+    let mut rest = value.trim();
+    let mut decoded = Vec::new();
+    while rest.starts_with('(') && !rest.starts_with("((") {
+      let Some((reference, after)) = rest[1..].split_once(')') else {
+        break;
+      };
+      decoded.push(reference_name(reference));
+      rest = after.trim_start();
+    }
+    let refinement = rest.strip_prefix('(').filter(|r| r.starts_with('(')).unwrap_or(rest);
+    if !refinement.is_empty() {
+      let refinement = if decoded.is_empty() {
+        reference_name(refinement)
+      } else {
+        refinement.to_string()
+      };
+      decoded.pop();
+      decoded.push(refinement);
+    }
+    genres.extend(decoded);
+  }
+  let mut unique = Vec::<String>::new();
+  for genre in genres {
+    if !unique.iter().any(|g| g.eq_ignore_ascii_case(&genre)) {
+      unique.push(genre);
+    }
+  }
+  unique
+}
+
+/// Name of an ID3v1 genre number or of the "RX" and "CR" keywords, other text is kept.
+fn reference_name(reference: &str) -> String {
+  match reference {
+    "RX" => "Remix".into(),
+    "CR" => "Cover".into(),
+    r => {
+      r.parse::<u8>()
+        .ok()
+        .and_then(id3v1::genre_name)
+        .unwrap_or_else(|| {
+          // unknown references stay recognizable
+          if r.chars().all(|c| c.is_ascii_digit()) { format!("({})", r) } else { r.to_string() }
+        })
+    }
+  }
+}
+
+/// Genres typed into the Genre input.
+pub fn split(s: &str) -> Vec<String> {
+  s.split(SEPARATOR.trim())
+    .map(|g| g.trim().to_string())
+    .filter(|g| !g.is_empty())
+    .collect()
+}
+
+/// Standard and Winamp extension genres in the order of their numbers.
+pub fn standard() -> Vec<String> {
+  (0..=u8::MAX).map_while(id3v1::genre_name).collect()
+}
+
+/// Genres containing `query` with the ones starting with it first, then the ones used most in the
+/// library followed by the standard ones. `library` holds genres with the number of files using
+/// them.
+pub fn suggest(query: &str, library: &[(String, usize)]) -> Vec<(String, usize)> {
+  let query = query.trim().to_lowercase();
+  let mut genres = library.to_vec();
+  for genre in standard() {
+    if !genres.iter().any(|(g, _)| g.eq_ignore_ascii_case(&genre)) {
+      genres.push((genre, 0));
+    }
+  }
+  let mut genres = genres
+    .into_iter()
+    .filter(|(g, _)| g.to_lowercase().contains(&query))
+    .collect::<Vec<_>>();
+  genres.sort_by_key(|(g, count)| (!g.to_lowercase().starts_with(&query), usize::MAX - count));
+  genres
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{ decode, split, suggest };
+
+  #[test]
+  fn decodes_references_and_values() {
+    assert_eq!(decode("Rock"), vec!["Rock"]);
+    assert_eq!(decode("(17)"), vec!["Rock"]);
+    assert_eq!(decode("(17)Rock"), vec!["Rock"]);
+    assert_eq!(decode("(4)Eurodisco"), vec!["Eurodisco"]);
+    assert_eq!(decode("(51)(39)"), vec!["Techno-Industrial", "Noise"]);
+    assert_eq!(decode("(RX)(CR)"), vec!["Remix", "Cover"]);
+    assert_eq!(decode("((Foo)"), vec!["(Foo)"]);
+    assert_eq!(decode("17\0Jazz\0Rock"), vec!["Rock", "Jazz"]);
+    assert_eq!(decode("(250)"), vec!["(250)"]);
+    assert!(decode("").is_empty());
+  }
+
+  #[test]
+  fn splits_and_suggests() {
+    assert_eq!(split(" Rock ;Pop;; "), vec!["Rock", "Pop"]);
+    let library = vec![("Synthwave".to_string(), 3), ("Pop".to_string(), 5)];
+    let suggested = suggest("p", &library);
+    assert_eq!(suggested[0], ("Pop".to_string(), 5));
+    assert!(suggested.iter().all(|(g, _)| g.to_lowercase().contains('p')));
+    assert_eq!(suggest("synth", &library)[0].0, "Synthwave");
+    assert_eq!(suggest("", &library).len(), super::standard().len() + 1);
+  }
+}
@@ -0,0 +1,91 @@
+/// Most steps kept per file, the oldest are dropped first.
+const LIMIT: usize = 100;
+
+/// Undo and redo steps of a file, each step is the value before an edit.
+#[derive(Clone, Debug)]
+pub struct History<T> {
+  undo: Vec<T>,
+  redo: Vec<T>,
+  /// Edits of the same group in a row, such as typing into one input, make up a single step.
+  group: Option<String>,
+}
+
+impl<T> Default for History<T> {
+  fn default() -> Self {
+    Self { undo: Vec::new(), redo: Vec::new(), group: None }
+  }
+}
+
+impl<T> History<T> {
+  /// Records `before` as a step unless it continues the last edit of the same `group`.
+  pub fn record(&mut self, before: T, group: Option<String>) {
+    self.redo.clear();
+    if self.continues(&group) {
+      return;
+    }
+    self.group = group;
+    self.undo.push(before);
+    if self.undo.len() > LIMIT {
+      self.undo.remove(0);
+    }
+  }
+  /// Whether an edit of `group` would be part of the last step, which already holds the value
+  /// before it.
+  pub fn continues(&self, group: &Option<String>) -> bool {
+    group.is_some() && *group == self.group && !self.undo.is_empty()
+  }
+  /// Ends the current group so that the next edit is a step of its own.
+  pub fn seal(&mut self) {
+    self.group = None;
+  }
+  /// Steps back, `current` is kept for redo.
+  pub fn undo(&mut self, current: T) -> Option<T> {
+    let before = self.undo.pop()?;
+    self.redo.push(current);
+    self.group = None;
+    Some(before)
+  }
+  /// Steps forward again, `current` is kept for undo.
+  pub fn redo(&mut self, current: T) -> Option<T> {
+    let after = self.redo.pop()?;
+    self.undo.push(current);
+    self.group = None;
+    Some(after)
+  }
+  pub fn can_undo(&self) -> bool {
+    !self.undo.is_empty()
+  }
+  pub fn can_redo(&self) -> bool {
+    !self.redo.is_empty()
+  }
+  pub fn clear(&mut self) {
+    *self = Self::default();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::History;
+
+  #[test]
+  fn groups_undo_and_redo() {
+    let mut history = History::default();
+    let mut value = "";
+    for (next, group) in [("a", Some("title")), ("ab", Some("title")), ("ab!", None)] {
+      history.record(value, group.map(String::from));
+      value = next;
+    }
+    assert!(!history.continues(&Some("title".into())));
+    value = history.undo(value).unwrap();
+    assert_eq!(value, "ab");
+    value = history.undo(value).unwrap();
+    assert_eq!(value, "");
+    assert!(history.undo(value).is_none());
+    value = history.redo(value).unwrap();
+    assert_eq!(value, "ab");
+    history.record(value, Some("title".into()));
+    assert!(history.continues(&Some("title".into())));
+    assert!(!history.can_redo());
+    assert_eq!(history.undo("abc"), Some("ab"));
+  }
+}
@@ -0,0 +1,51 @@
+use std::{ fs::OpenOptions, io::Write };
+use id3::v1;
+
+/// Encodes text as ISO-8859-1 padded with nulls to `len` bytes, other characters become '?'.
+fn latin1(s: &str, len: usize) -> Vec<u8> {
+  let mut bytes = s
+    .chars()
+    .take(len)
+    .map(|c| if (c as u32) < 0x100 { c as u8 } else { b'?' })
+    .collect::<Vec<_>>();
+  bytes.resize(len, 0);
+  bytes
+}
+
+/// Name of a standard or Winamp extension genre.
+pub fn genre_name(id: u8) -> Option<String> {
+  // the genre list of the id3 crate is private, names can only be reached through a tag
+  let mut tag = v1::Tag::new();
+  tag.genre_id = id;
+  tag.genre().map(String::from)
+}
+
+/// Looks up the genre byte by name, case insensitive.
+pub fn genre_id(name: &str) -> Option<u8> {
+  (0..=u8::MAX).find(|id| genre_name(*id).is_some_and(|g| g.eq_ignore_ascii_case(name.trim())))
+}
+
+/// Encodes an ID3v1.1 tag, the track number takes the last two bytes of the comment.
+pub fn encode(tag: &v1::Tag) -> Vec<u8> {
+  let mut bytes = b"TAG".to_vec();
+  bytes.extend(latin1(&tag.title, 30));
+  bytes.extend(latin1(&tag.artist, 30));
+  bytes.extend(latin1(&tag.album, 30));
+  bytes.extend(latin1(&tag.year, 4));
+  match tag.track {
+    Some(track) => {
+      bytes.extend(latin1(&tag.comment, 28));
+      bytes.extend([0, track]);
+    }
+    None => bytes.extend(latin1(&tag.comment, 30)),
+  }
+  bytes.push(tag.genre_id);
+  bytes
+}
+
+/// Replaces the ID3v1 tag at the end of the file.
+pub fn write_to_path(path: &str, tag: &v1::Tag) -> id3::Result<()> {
+  v1::Tag::remove_from_path(path)?;
+  OpenOptions::new().append(true).open(path)?.write_all(&encode(tag))?;
+  Ok(())
+}
@@ -0,0 +1,311 @@
+use std::{
+  collections::HashMap,
+  fs::{ self, OpenOptions },
+  io::{ self, Read, Write },
+  path::Path,
+  time::{ Duration, SystemTime },
+};
+use super::{ disk::Fingerprint, tag::SongTags };
+
+/// Start of a cache file, a cache of another format is dropped.
+const MAGIC: &[u8] = b"TAGCHR-LIBRARY 3\n";
+/// Extension of the list of files written since the cache was saved, next to the cache.
+const WRITTEN_EXTENSION: &str = "written";
+
+/// Tags of the files found in the last scan, so that unchanged files are not read again at
+/// startup. A file is taken as unchanged when its modification time and size are. Only the fields
+/// read from the tags are kept, pictures are read from the file when it is shown.
+#[derive(Default)]
+pub struct LibraryCache {
+  entries: HashMap<String, SongTags>,
+}
+
+/// A value stored in the cache file.
+pub trait Encode: Sized {
+  fn encode(&self, data: &mut Vec<u8>);
+  fn decode(reader: &mut &[u8]) -> io::Result<Self>;
+}
+
+impl LibraryCache {
+  /// Reads the cache at `path`, a missing or damaged cache is empty. Files written since the cache
+  /// was saved are left out, their times may have been kept.
+  pub fn load(path: &Path) -> Self {
+    let mut entries: HashMap<String, SongTags> = fs::read(path)
+      .ok()
+      .and_then(|data| decode(&data).ok())
+      .unwrap_or_default();
+    let written = fs::read_to_string(path.with_extension(WRITTEN_EXTENSION)).unwrap_or_default();
+    for song_path in written.lines() {
+      entries.remove(song_path);
+    }
+    Self { entries }
+  }
+  /// Tags of the file at `song_path` when it was not modified since they were cached.
+  pub fn get(&self, song_path: &str, modified: SystemTime, size: u64) -> Option<SongTags> {
+    self.entries
+      .get(song_path)
+      .filter(|t| t.disk.is_some_and(|d| d.modified == modified && d.size == size))
+      .cloned()
+  }
+  /// Caches tags read from a file, with their pictures left out.
+  pub fn insert(&mut self, tags: SongTags) {
+    self.entries.insert(tags.path().to_string(), tags);
+  }
+  pub fn remove(&mut self, song_path: &str) {
+    self.entries.remove(song_path);
+  }
+  /// Writes the cache next to `path` first so that a crash leaves the previous one.
+  pub fn save(&self, path: &Path) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+      fs::create_dir_all(dir)?;
+    }
+    let temp = path.with_extension("tmp");
+    fs::write(&temp, self.encode())?;
+    fs::rename(temp, path)?;
+    match fs::remove_file(path.with_extension(WRITTEN_EXTENSION)) {
+      Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+      _ => Ok(()),
+    }
+  }
+  fn encode(&self) -> Vec<u8> {
+    let mut data = MAGIC.to_vec();
+    for tags in self.entries.values() {
+      tags.encode(&mut data);
+    }
+    data
+  }
+}
+
+/// Records that the file at `song_path` is about to be written, so that the cache at `path` is not
+/// trusted for it even when its modification time and size stay the same.
+pub fn forget(path: &Path, song_path: &str) -> io::Result<()> {
+  if let Some(dir) = path.parent() {
+    fs::create_dir_all(dir)?;
+  }
+  OpenOptions::new()
+    .create(true)
+    .append(true)
+    .open(path.with_extension(WRITTEN_EXTENSION))?
+    .write_all(format!("{}\n", song_path).as_bytes())
+}
+
+fn decode(data: &[u8]) -> io::Result<HashMap<String, SongTags>> {
+  let mut reader = data.strip_prefix(MAGIC).ok_or(io::ErrorKind::InvalidData)?;
+  let mut entries = HashMap::new();
+  while !reader.is_empty() {
+    let tags = SongTags::decode(&mut reader)?;
+    entries.insert(tags.path().to_string(), tags);
+  }
+  Ok(entries)
+}
+
+fn read_array<const N: usize>(reader: &mut &[u8]) -> io::Result<[u8; N]> {
+  let mut array = [0; N];
+  reader.read_exact(&mut array)?;
+  Ok(array)
+}
+
+/// Bytes preceded by their length.
+fn read_bytes(reader: &mut &[u8]) -> io::Result<Vec<u8>> {
+  let len = u32::decode(reader)? as usize;
+  if reader.len() < len {
+    return Err(io::ErrorKind::UnexpectedEof.into());
+  }
+  let (bytes, rest) = reader.split_at(len);
+  *reader = rest;
+  Ok(bytes.to_vec())
+}
+
+impl Encode for bool {
+  fn encode(&self, data: &mut Vec<u8>) {
+    data.push(*self as u8);
+  }
+  fn decode(reader: &mut &[u8]) -> io::Result<Self> {
+    Ok(read_array::<1>(reader)?[0] != 0)
+  }
+}
+
+impl Encode for u8 {
+  fn encode(&self, data: &mut Vec<u8>) {
+    data.push(*self);
+  }
+  fn decode(reader: &mut &[u8]) -> io::Result<Self> {
+    Ok(read_array::<1>(reader)?[0])
+  }
+}
+
+impl Encode for u32 {
+  fn encode(&self, data: &mut Vec<u8>) {
+    data.extend(self.to_le_bytes());
+  }
+  fn decode(reader: &mut &[u8]) -> io::Result<Self> {
+    Ok(u32::from_le_bytes(read_array(reader)?))
+  }
+}
+
+impl Encode for u64 {
+  fn encode(&self, data: &mut Vec<u8>) {
+    data.extend(self.to_le_bytes());
+  }
+  fn decode(reader: &mut &[u8]) -> io::Result<Self> {
+    Ok(u64::from_le_bytes(read_array(reader)?))
+  }
+}
+
+impl Encode for String {
+  fn encode(&self, data: &mut Vec<u8>) {
+    (self.len() as u32).encode(data);
+    data.extend(self.as_bytes());
+  }
+  fn decode(reader: &mut &[u8]) -> io::Result<Self> {
+    String::from_utf8(read_bytes(reader)?).map_err(|_| io::ErrorKind::InvalidData.into())
+  }
+}
+
+impl<T: Encode> Encode for Option<T> {
+  fn encode(&self, data: &mut Vec<u8>) {
+    self.is_some().encode(data);
+    if let Some(value) = self {
+      value.encode(data);
+    }
+  }
+  fn decode(reader: &mut &[u8]) -> io::Result<Self> {
+    match bool::decode(reader)? {
+      true => Ok(Some(T::decode(reader)?)),
+      false => Ok(None),
+    }
+  }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+  fn encode(&self, data: &mut Vec<u8>) {
+    (self.len() as u32).encode(data);
+    for value in self {
+      value.encode(data);
+    }
+  }
+  fn decode(reader: &mut &[u8]) -> io::Result<Self> {
+    let len = u32::decode(reader)?;
+    (0..len).map(|_| T::decode(reader)).collect()
+  }
+}
+
+impl<A: Encode, B: Encode> Encode for (A, B) {
+  fn encode(&self, data: &mut Vec<u8>) {
+    self.0.encode(data);
+    self.1.encode(data);
+  }
+  fn decode(reader: &mut &[u8]) -> io::Result<Self> {
+    Ok((A::decode(reader)?, B::decode(reader)?))
+  }
+}
+
+impl Encode for SystemTime {
+  fn encode(&self, data: &mut Vec<u8>) {
+    let since_epoch = self.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+    since_epoch.as_secs().encode(data);
+    since_epoch.subsec_nanos().encode(data);
+  }
+  fn decode(reader: &mut &[u8]) -> io::Result<Self> {
+    let (secs, nanos) = <(u64, u32)>::decode(reader)?;
+    Ok(SystemTime::UNIX_EPOCH + Duration::new(secs, nanos))
+  }
+}
+
+impl Encode for Fingerprint {
+  fn encode(&self, data: &mut Vec<u8>) {
+    self.modified.encode(data);
+    self.size.encode(data);
+    self.tag_hash.encode(data);
+  }
+  fn decode(reader: &mut &[u8]) -> io::Result<Self> {
+    Ok(Self {
+      modified: SystemTime::decode(reader)?,
+      size: u64::decode(reader)?,
+      tag_hash: u64::decode(reader)?,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{ fs, time::{ Duration, SystemTime } };
+  use id3::{
+    frame::{
+      Comment,
+      ExtendedText,
+      Lyrics,
+      Picture,
+      PictureType,
+      SynchronisedLyrics,
+      SynchronisedLyricsType,
+      TimestampFormat,
+    },
+    Tag,
+    TagLike,
+    Version,
+  };
+  use crate::app::{ disk::Fingerprint, tag::SongTags, testing::TempDir };
+  use super::{ forget, LibraryCache };
+
+  #[test]
+  fn keeps_tags_of_unchanged_files() {
+    let dir = TempDir::new("library");
+    let path = dir.join("library.cache");
+    let song = dir.mp3("a.mp3");
+    let song_path = song.to_str().unwrap();
+    let mut tag = Tag::new();
+    tag.set_title("Title");
+    tag.set_genre("Jazz");
+    tag.set_date_recorded("2001-02-03".parse().unwrap());
+    tag.add_frame(Lyrics { lang: "eng".into(), description: "".into(), text: "Line".into() });
+    tag.add_frame(SynchronisedLyrics {
+      lang: "eng".into(),
+      timestamp_format: TimestampFormat::Ms,
+      content_type: SynchronisedLyricsType::Lyrics,
+      description: "".into(),
+      content: vec![(1000, "Line".into())],
+    });
+    tag.add_frame(Comment { lang: "eng".into(), description: "".into(), text: "Nice".into() });
+    tag.add_frame(ExtendedText { description: "MOOD".into(), value: "Calm".into() });
+    tag.add_frame(Picture {
+      mime_type: "image/png".into(),
+      picture_type: PictureType::CoverFront,
+      description: "".into(),
+      data: vec![1, 2, 3],
+    });
+    tag.write_to_path(&song, Version::Id3v23).unwrap();
+    let read = SongTags::new(song_path.into());
+    let Fingerprint { modified, size, .. } = read.disk.unwrap();
+    let mut other = SongTags::new(dir.join("b.mp3").to_str().unwrap().into());
+    let other_modified = SystemTime::UNIX_EPOCH + Duration::new(1_000_000_000, 123);
+    other.disk = Some(Fingerprint { modified: other_modified, size: 20, tag_hash: 0 });
+    let mut cache = LibraryCache::default();
+    cache.insert(read.without_pictures());
+    cache.insert(other.without_pictures());
+    cache.save(&path).unwrap();
+
+    let loaded = LibraryCache::load(&path);
+    assert_eq!(loaded.entries.len(), 2);
+    let mut cached = loaded.get(song_path, modified, size).unwrap();
+    assert!(cached.pictures.is_empty());
+    assert_eq!(cached.changes(), []);
+    // the pictures are read from the file once it is shown
+    cached.load_pictures();
+    assert_eq!(cached, read);
+    assert!(loaded.get(other.path(), other_modified, 20).is_some());
+    // a file changed since is read again
+    assert_eq!(loaded.get(song_path, modified, size + 1), None);
+    assert_eq!(loaded.get(song_path, modified + Duration::from_secs(1), size), None);
+
+    // a file written with its times kept is read again until the cache is saved
+    forget(&path, song_path).unwrap();
+    let loaded = LibraryCache::load(&path);
+    assert_eq!(loaded.get(song_path, modified, size), None);
+    loaded.save(&path).unwrap();
+    assert_eq!(LibraryCache::load(&path).entries.len(), 1);
+
+    fs::write(&path, &fs::read(&path).unwrap()[..30]).unwrap();
+    assert_eq!(LibraryCache::load(&path).entries.len(), 0);
+  }
+}
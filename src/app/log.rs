@@ -0,0 +1,88 @@
+use std::{ fmt, fs::{ self, OpenOptions }, io::{ self, Write }, path::PathBuf };
+use chrono::Local;
+
+/// Size after which the log is moved aside and a new one is started.
+const MAX_SIZE: u64 = 256 * 1024;
+/// Rotated logs kept next to the current one, "tagchr.1.log" being the latest.
+const KEPT: usize = 3;
+const FILE_NAME: &str = "tagchr.log";
+
+/// Error log in a directory, rotated by size. Entries of the current log are kept for the log
+/// screen.
+pub struct Log {
+  dir: PathBuf,
+  max_size: u64,
+  entries: Vec<String>,
+}
+
+impl Log {
+  pub fn new(dir: PathBuf) -> Self {
+    let entries = fs::read_to_string(dir.join(FILE_NAME))
+      .map(|log| log.lines().map(String::from).collect())
+      .unwrap_or_default();
+    Self { dir, max_size: MAX_SIZE, entries }
+  }
+  pub fn path(&self) -> PathBuf {
+    self.dir.join(FILE_NAME)
+  }
+  /// Entries from the oldest to the latest.
+  pub fn entries(&self) -> &[String] {
+    &self.entries
+  }
+  /// Adds a timestamped entry. Failing to write the log is ignored as there is nowhere left to
+  /// report it, the entry is still shown on the log screen.
+  pub fn error(&mut self, message: impl fmt::Display) {
+    let entry = format!(
+      "{} ERROR {}",
+      Local::now().format("%Y-%m-%d %H:%M:%S"),
+      message.to_string().replace('\n', "; ")
+    );
+    let _ = self.append(&entry);
+    self.entries.push(entry);
+  }
+  fn append(&self, entry: &str) -> io::Result<()> {
+    fs::create_dir_all(&self.dir)?;
+    if fs::metadata(self.path()).is_ok_and(|m| m.len() >= self.max_size) {
+      self.rotate()?;
+    }
+    OpenOptions::new().create(true).append(true).open(self.path())?.write_all(
+      format!("{}\n", entry).as_bytes()
+    )
+  }
+  /// Shifts the rotated logs by one, dropping the oldest, and moves the current log aside.
+  fn rotate(&self) -> io::Result<()> {
+    let rotated = |n: usize| self.dir.join(format!("tagchr.{}.log", n));
+    for n in (1..KEPT).rev() {
+      if rotated(n).exists() {
+        fs::rename(rotated(n), rotated(n + 1))?;
+      }
+    }
+    fs::rename(self.path(), rotated(1))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs;
+  use crate::app::testing::TempDir;
+  use super::{ Log, KEPT };
+
+  #[test]
+  fn rotates_by_size() {
+    let temp = TempDir::new("logs");
+    let dir = temp.join("logs");
+    let mut log = Log::new(dir.clone());
+    log.max_size = 1;
+    for n in 0..KEPT + 2 {
+      log.error(format!("error {}\nsecond line", n));
+    }
+    assert_eq!(log.entries().len(), KEPT + 2);
+    assert!(log.entries()[0].ends_with("ERROR error 0; second line"));
+    let current = fs::read_to_string(log.path()).unwrap();
+    assert!(current.trim_end().ends_with(&format!("error {}; second line", KEPT + 1)));
+    assert!(dir.join(format!("tagchr.{}.log", KEPT)).exists());
+    assert!(!dir.join(format!("tagchr.{}.log", KEPT + 1)).exists());
+    // a new session shows the current log only
+    assert_eq!(Log::new(dir).entries().len(), 1);
+  }
+}
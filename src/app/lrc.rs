@@ -0,0 +1,107 @@
+/// Formats milliseconds as an LRC `[mm:ss.xx]` timestamp.
+pub fn format_timestamp(ms: u32) -> String {
+  format!("[{:02}:{:02}.{:02}]", ms / 60_000, (ms / 1000) % 60, (ms % 1000) / 10)
+}
+
+/// Parses the inside of a `[mm:ss.xx]` timestamp, fractions of any precision are accepted.
+fn parse_timestamp(s: &str) -> Option<u32> {
+  let (minutes, seconds) = s.split_once(':')?;
+  let minutes = minutes.trim().parse::<u32>().ok()?;
+  let (seconds, fraction) = seconds.split_once('.').unwrap_or((seconds, ""));
+  let seconds = seconds.trim().parse::<u32>().ok()?;
+  if seconds >= 60 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+    return None;
+  }
+  let ms = format!("{:0<3}", fraction)[..3].parse::<u32>().ok()?;
+  Some(minutes * 60_000 + seconds * 1000 + ms)
+}
+
+/// Parses LRC lines into lines timed in milliseconds, sorted by time. Lines may carry several
+/// timestamps, blank lines and `[key:value]` metadata are skipped and `[offset:±ms]` is applied.
+/// Fails with the number of the first line that has no timestamp.
+pub fn parse(text: &str) -> Result<Vec<(u32, String)>, usize> {
+  let mut offset = 0i64;
+  let mut lines = Vec::new();
+  for (n, line) in text.lines().enumerate() {
+    let mut rest = line.trim();
+    if rest.is_empty() {
+      continue;
+    }
+    let mut times = Vec::new();
+    while let Some(tag) = rest.strip_prefix('[').and_then(|r| r.split_once(']')) {
+      match parse_timestamp(tag.0) {
+        Some(ms) => times.push(ms),
+        None if times.is_empty() => {
+          if let Some(("offset", value)) = tag.0.split_once(':').map(|(k, v)| (k.trim(), v)) {
+            offset = value.trim().parse().map_err(|_| n + 1)?;
+          }
+          break;
+        }
+        None => {
+          break;
+        }
+      }
+      rest = tag.1;
+    }
+    if times.is_empty() {
+      if line.trim_start().starts_with('[') && line.trim_end().ends_with(']') {
+        continue;
+      }
+      return Err(n + 1);
+    }
+    for ms in times {
+      // a positive offset shows the lyrics sooner
+      let ms = ((ms as i64) - offset).clamp(0, u32::MAX as i64) as u32;
+      lines.push((ms, rest.to_string()));
+    }
+  }
+  lines.sort_by_key(|(ms, _)| *ms);
+  Ok(lines)
+}
+
+/// Formats timed lines as LRC, one `[mm:ss.xx]text` per line.
+pub fn format(lines: &[(u32, String)]) -> String {
+  lines
+    .iter()
+    .map(|(ms, text)| format!("{}{}", format_timestamp(*ms), text))
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{ format, format_timestamp, parse };
+
+  #[test]
+  fn timestamps() {
+    assert_eq!(format_timestamp(0), "[00:00.00]");
+    assert_eq!(format_timestamp(83_456), "[01:23.45]");
+    assert_eq!(format_timestamp(6_000_000), "[100:00.00]");
+  }
+
+  #[test]
+  fn parses_lrc() {
+    let lrc = "[ar:Artist]\r\n[ti:Title]\n\n[00:12.00]first\n[00:05.5][01:00.123]chorus\n[00:20]";
+    assert_eq!(
+      parse(lrc),
+      Ok(
+        Vec::from([
+          (5_500, "chorus".to_string()),
+          (12_000, "first".to_string()),
+          (20_000, "".to_string()),
+          (60_123, "chorus".to_string()),
+        ])
+      )
+    );
+    assert_eq!(parse("[offset:+500]\n[00:01.00]a"), Ok(Vec::from([(500, "a".to_string())])));
+    assert_eq!(parse("[00:01.00]a\nno timestamp"), Err(2));
+    assert_eq!(parse("[00:61.00]a"), Err(1));
+  }
+
+  #[test]
+  fn round_trip() {
+    let lines = Vec::from([(1_230, "one".to_string()), (62_500, "two".to_string())]);
+    assert_eq!(format(&lines), "[00:01.23]one\n[01:02.50]two");
+    assert_eq!(parse(&format(&lines)), Ok(lines));
+  }
+}
@@ -1,3 +1,19 @@
 pub mod app;
+pub mod atomic;
+pub mod backup;
+pub mod diff;
+pub mod disk;
+pub mod error;
+pub mod genre;
+pub mod history;
+pub mod id3v1;
+pub mod library;
+pub mod log;
+pub mod lrc;
+pub mod scan;
+pub mod settings;
 pub mod tag;
+#[cfg(test)]
+pub mod testing;
 pub mod state;
+pub mod validation;
@@ -0,0 +1,279 @@
+use std::{
+  cmp::Reverse,
+  fs,
+  path::{ Path, PathBuf },
+  sync::{ mpsc::{ channel, Receiver, Sender }, Arc, Mutex },
+  thread,
+  time::{ Duration, Instant, SystemTime },
+};
+use chrono::{ DateTime, Local };
+use pretty_date::pretty_date_formatter::PrettyDateFormatter;
+use super::{
+  app::{ Command, Mp3File },
+  disk::{ self, Fingerprint },
+  history::History,
+  library::LibraryCache,
+  state::Source,
+  tag::SongTags,
+};
+
+/// How long found and read files are held before being sent, so that the UI is not redrawn for
+/// every file.
+const BATCH_TIME: Duration = Duration::from_millis(50);
+
+/// Progress of the library scan, the files are added as they are read.
+#[derive(Default)]
+pub struct Scan {
+  pub found: usize,
+  pub read: usize,
+  /// Files whose tags could not be read in full or whose path is not valid UTF-8, and
+  /// directories that could not be listed.
+  pub errors: usize,
+  pub done: bool,
+  /// Tags of the files read so far, saved once the scan is done.
+  pub cache: LibraryCache,
+}
+
+/// A file read by the scan with what to cache of it, `None` when it could not be read in full.
+pub type Scanned = (Mp3File, Option<SongTags>);
+
+/// A file to read with its position in the walk.
+type Job = (usize, PathBuf, Source);
+
+/// Lists the MP3 files under `directories` on one thread and reads their tags on a pool of
+/// others, taking the tags of the files not modified since from the library cache at
+/// `cache_path`. The files are sent through `sender` as they are read, `ScanFinished` comes after
+/// the last one.
+pub fn start(directories: Vec<(PathBuf, Source)>, cache_path: PathBuf, sender: Sender<Command>) {
+  thread::spawn(move || {
+    let cached = Arc::new(LibraryCache::load(&cache_path));
+    let (jobs, queue) = channel::<Job>();
+    let queue = Arc::new(Mutex::new(queue));
+    let workers = (0..thread::available_parallelism().map_or(4, |n| n.get()))
+      .map(|_| {
+        let (queue, cached, sender) = (queue.clone(), cached.clone(), sender.clone());
+        thread::spawn(move || read_files(&queue, &cached, &sender))
+      })
+      .collect::<Vec<_>>();
+    let mut walk = Walk {
+      jobs,
+      sender: sender.clone(),
+      queued: 0,
+      found: 0,
+      last_sent: Instant::now(),
+    };
+    for (dir, source) in directories {
+      match dir.canonicalize() {
+        Ok(dir) => walk.list(dir, source),
+        Err(e) => {
+          let error = format!("Cannot canonicalize path {}: {}", dir.display(), e);
+          let _ = sender.send(Command::ScanFailed(error));
+        }
+      }
+    }
+    walk.flush();
+    // the workers stop once the queue is empty and closed
+    drop(walk);
+    for worker in workers {
+      let _ = worker.join();
+    }
+    let _ = sender.send(Command::ScanFinished);
+  });
+}
+
+/// Lists directories, queueing the files to read and reporting how many were found.
+struct Walk {
+  jobs: Sender<Job>,
+  sender: Sender<Command>,
+  /// Files queued so far.
+  queued: usize,
+  /// Files found since the last report.
+  found: usize,
+  last_sent: Instant,
+}
+
+impl Walk {
+  /// Queues the MP3 files under `dir`, the latest modified first.
+  fn list(&mut self, dir: PathBuf, source: Source) {
+    let entries = match fs::read_dir(&dir) {
+      Ok(entries) => entries,
+      Err(e) => {
+        let error = format!("Cannot list directory {}: {}", dir.display(), e);
+        let _ = self.sender.send(Command::ScanFailed(error));
+        return;
+      }
+    };
+    let mut entries = entries.filter_map(Result::ok).collect::<Vec<_>>();
+    entries.sort_by_key(|e| Reverse(e.metadata().and_then(|m| m.modified()).ok()));
+    for entry in entries {
+      let path = entry.path();
+      if path.is_dir() {
+        self.list(path, source);
+      } else if path.extension().map_or(false, |ext| ext == "mp3") {
+        let _ = self.jobs.send((self.queued, path, source));
+        self.queued += 1;
+        self.found += 1;
+        if self.last_sent.elapsed() >= BATCH_TIME {
+          self.flush();
+        }
+      }
+    }
+  }
+  fn flush(&mut self) {
+    if self.found > 0 {
+      let _ = self.sender.send(Command::FilesFound(self.found));
+    }
+    self.found = 0;
+    self.last_sent = Instant::now();
+  }
+}
+
+/// Reads the queued files until the queue is closed, sending them in batches.
+fn read_files(
+  queue: &Mutex<Receiver<Job>>,
+  cached: &LibraryCache,
+  sender: &Sender<Command>
+) {
+  let mut batch = Vec::new();
+  let mut last_sent = Instant::now();
+  loop {
+    // the lock is released before reading the file
+    let job = queue.lock().map_or(None, |queue| queue.recv().ok());
+    let Some((position, path, source)) = job else {
+      break;
+    };
+    match read_file(position, &path, source, cached) {
+      Some(scanned) => batch.push(scanned),
+      None => {
+        let error = format!("Cannot read the path {}: not valid UTF-8", path.display());
+        let _ = sender.send(Command::ScanFailed(error));
+      }
+    }
+    if last_sent.elapsed() >= BATCH_TIME && !batch.is_empty() {
+      let _ = sender.send(Command::FilesScanned(std::mem::take(&mut batch)));
+      last_sent = Instant::now();
+    }
+  }
+  if !batch.is_empty() {
+    let _ = sender.send(Command::FilesScanned(batch));
+  }
+}
+
+/// The file at `path`, `None` when its path is not valid UTF-8.
+fn read_file(
+  position: usize,
+  path: &Path,
+  source: Source,
+  cached: &LibraryCache
+) -> Option<Scanned> {
+  let song_path = path.to_str()?.to_string();
+  let name = path.file_name()?.to_str()?.to_string();
+  let metadata = fs::metadata(path).ok();
+  let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+  let (tags, entry) = match (modified, metadata.map(|m| m.len())) {
+    (Some(modified), Some(size)) => read_song_tags(&song_path, modified, size, cached),
+    _ => (SongTags::new(song_path.clone()), None),
+  };
+  let modified_date = modified.map_or(String::new(), |modified| {
+    let modified: DateTime<Local> = DateTime::from(modified);
+    modified.naive_local().format_pretty()
+  });
+  let file = Mp3File {
+    tags,
+    history: History::default(),
+    name,
+    path: song_path.replace("\\", "/"),
+    source,
+    modified_date,
+    position,
+  };
+  Some((file, entry))
+}
+
+/// Tags of the file at `song_path` from `cached` when the file was not modified since, read from
+/// the file otherwise, with what to cache of them.
+fn read_song_tags(
+  song_path: &str,
+  modified: SystemTime,
+  size: u64,
+  cached: &LibraryCache
+) -> (SongTags, Option<SongTags>) {
+  if let Some(tags) = cached.get(song_path, modified, size) {
+    return (tags.clone(), Some(tags));
+  }
+  let tags = match disk::read_tags(song_path) {
+    Ok(tags) => tags,
+    Err(_) => {
+      return (SongTags::new(song_path.into()), None);
+    }
+  };
+  let disk = Some(Fingerprint::new(modified, size, &tags));
+  let song_tags = SongTags::from_tags(song_path.into(), &tags, disk);
+  // a damaged tag is read again, the error is reported on every scan
+  let entry = song_tags.read_error.is_none().then(|| song_tags.without_pictures());
+  (song_tags, entry)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{ fs, path::PathBuf, sync::mpsc::channel, time::Duration };
+  use id3::{ Tag, TagLike, Version };
+  use uuid::Uuid;
+  use crate::app::{ app::Command, state::Source, testing::TempDir };
+  use super::start;
+
+  #[test]
+  fn streams_files_until_finished() {
+    let temp = TempDir::new("scan");
+    let dir = temp.path().to_path_buf();
+    fs::create_dir_all(dir.join("album")).unwrap();
+    for (i, path) in ["one.mp3", "album/two.mp3", "album/three.mp3"].iter().enumerate() {
+      let path = temp.mp3(path);
+      let mut tag = Tag::new();
+      tag.set_title(format!("Title {}", i));
+      tag.write_to_path(&path, Version::Id3v24).unwrap();
+    }
+    fs::write(dir.join("cover.jpg"), "").unwrap();
+    // a path that is not valid UTF-8 is reported as an error
+    #[cfg(unix)]
+    {
+      use std::{ ffi::OsStr, os::unix::ffi::OsStrExt };
+      fs::write(dir.join(OsStr::from_bytes(b"bad\xff.mp3")), "").unwrap();
+    }
+
+    let (sender, receiver) = channel();
+    let missing = PathBuf::from(format!("tagchr-missing-{}", Uuid::new_v4()));
+    start(
+      Vec::from([(dir.clone(), Source::Custom), (missing, Source::Music)]),
+      dir.join("library.cache"),
+      sender
+    );
+    let (mut found, mut files, mut failed) = (0, Vec::new(), 0);
+    loop {
+      match receiver.recv_timeout(Duration::from_secs(10)).unwrap() {
+        Command::FilesFound(n) => {
+          found += n;
+        }
+        Command::FilesScanned(scanned) => files.extend(scanned),
+        Command::ScanFailed(_) => {
+          failed += 1;
+        }
+        Command::ScanFinished => {
+          break;
+        }
+        _ => panic!("unexpected command"),
+      }
+    }
+    let bad = if cfg!(unix) { 1 } else { 0 };
+    assert_eq!((found, files.len(), failed), (3 + bad, 3, 1 + bad));
+    let mut names = files.iter().map(|(f, _)| f.name.clone()).collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["one.mp3", "three.mp3", "two.mp3"]);
+    // every file has its own position in the walk
+    let mut positions = files.iter().map(|(f, _)| f.position).collect::<Vec<_>>();
+    positions.sort();
+    positions.dedup();
+    assert_eq!(positions.len(), 3);
+    assert!(files.iter().all(|(f, entry)| f.tags.title.0.original.is_some() && entry.is_some()));
+  }
+}
@@ -0,0 +1,55 @@
+use std::{ fs, io };
+use super::tag::{ EncodingSetting, VersionSetting, WriteOptions };
+
+/// Settings shared by all files, stored as `key=value` lines.
+#[derive(Clone, Default, Debug)]
+pub struct Settings {
+  pub write_options: WriteOptions,
+  /// Whether saving puts back the access and modification times the file had.
+  pub keep_times: bool,
+}
+
+impl Settings {
+  /// Reads settings from `path`, unknown keys and values are ignored.
+  pub fn load(path: &str) -> Self {
+    let mut settings = Self::default();
+    for line in fs::read_to_string(path).unwrap_or_default().lines() {
+      let Some((key, value)) = line.split_once('=') else {
+        continue;
+      };
+      match key.trim() {
+        "version" => {
+          if let Some(version) = VersionSetting::from_key(value.trim()) {
+            settings.write_options.version = version;
+          }
+        }
+        "encoding" => {
+          if let Some(encoding) = EncodingSetting::from_key(value.trim()) {
+            settings.write_options.encoding = encoding;
+          }
+        }
+        "keep_times" => {
+          if let Ok(keep_times) = value.trim().parse() {
+            settings.keep_times = keep_times;
+          }
+        }
+        _ => {}
+      }
+    }
+    settings
+  }
+  pub fn save(&self, path: &str) -> io::Result<()> {
+    if let Some(dir) = std::path::Path::new(path).parent() {
+      fs::create_dir_all(dir)?;
+    }
+    fs::write(
+      path,
+      format!(
+        "version={}\nencoding={}\nkeep_times={}\n",
+        self.write_options.version.key(),
+        self.write_options.encoding.key(),
+        self.keep_times
+      )
+    )
+  }
+}
@@ -1,8 +1,14 @@
 use std::{ fmt, fs, path::PathBuf, string };
-use chrono::{ DateTime, Local };
 use id3::Error;
-use pretty_date::pretty_date_formatter::PrettyDateFormatter;
-use super::{ app::Mp3File, tag::SongTags };
+use super::{
+  app::Mp3File,
+  backup::Backups,
+  genre,
+  library::{ self, LibraryCache },
+  log::Log,
+  scan::{ Scan, Scanned },
+  settings::Settings,
+};
 
 #[derive(Clone, Copy)]
 pub enum Source {
@@ -21,7 +27,7 @@ impl fmt::Display for Source {
   }
 }
 
-fn read_lines(filename: &str) -> Vec<String> {
+fn read_lines(filename: &str, log: &mut Log) -> Vec<String> {
     let mut result = Vec::new();
 
     let mut file_result = fs::read_to_string(filename);
@@ -32,8 +38,10 @@ fn read_lines(filename: &str) -> Vec<String> {
           result.push(line.to_string())
         }
       }
+      // no file is the same as an empty one
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
       Err(e) => {
-        println!("Error while trying to open file {0} : {1}", filename, e);
+        log.error(format!("Error while trying to open file {0} : {1}", filename, e));
       }
     }
       
@@ -41,6 +49,10 @@ fn read_lines(filename: &str) -> Vec<String> {
 }
 
 const DEFAULT_USER_DIRS_FILE_POSTFIX: &str = "directories.txt";
+const SETTINGS_FILE_POSTFIX: &str = "settings.txt";
+const LOGS_DIR_POSTFIX: &str = "logs";
+const BACKUPS_DIR_POSTFIX: &str = "backups";
+const LIBRARY_CACHE_FILE_POSTFIX: &str = "library.cache";
 
 pub struct State {
   pub running: bool,
@@ -49,6 +61,13 @@ pub struct State {
   files: Vec<Mp3File>,
   pub directories: Vec<(std::path::PathBuf, Source)>,
   pub shown_indexes: Vec<usize>,
+  /// Files edited together on the home screen, in the order of `files`.
+  pub selection: Vec<usize>,
+  pub settings: Settings,
+  pub log: Log,
+  pub backups: Backups,
+  /// The library scan running in the background since startup.
+  pub scan: Scan,
 }
 
 impl State {
@@ -65,13 +84,23 @@ impl State {
       files: vec![],
       directories: vec![],
       shown_indexes: vec![],
+      selection: vec![],
+      settings: Settings::default(),
+      log: Log::new(PathBuf::new()),
+      backups: Backups::new(PathBuf::new()),
+      scan: Scan::default(),
     };
 
+    new.settings = Settings::load(&new.settings_path());
+    let home = PathBuf::from(&new.TAGCHR_HOME_FOLDER);
+    new.log = Log::new(home.join(LOGS_DIR_POSTFIX));
+    new.backups = Backups::new(home.join(BACKUPS_DIR_POSTFIX));
+
     let DEFAULT_USER_DIRS_FILE = new.TAGCHR_HOME_FOLDER.clone() + DEFAULT_USER_DIRS_FILE_POSTFIX;
 
     // println!("{}", DEFAULT_USER_DIRS_FILE);
 
-    let user_dirs: Vec<std::path::PathBuf> = read_lines(&DEFAULT_USER_DIRS_FILE).iter().map(
+    let user_dirs: Vec<std::path::PathBuf> = read_lines(&DEFAULT_USER_DIRS_FILE, &mut new.log).iter().map(
       |s| {
         let mut p = std::path::PathBuf::new();
         p.push(s);
@@ -93,22 +122,53 @@ impl State {
         None => {}
     }
     
-    let dirs = new.directories.clone();
-    for (dir,src) in dirs {
-      let canon_dir = dir.canonicalize();
-      match canon_dir {
-        Ok(value) => {  
-          new.scan_mp3_files(value, src)
-        }
-        Err(e) => {
-          println!("Cannot canonicalize path {:?}", dir.to_str());
-        }
+    return new;
+  }
+  pub fn settings_path(&self) -> String {
+    self.TAGCHR_HOME_FOLDER.clone() + SETTINGS_FILE_POSTFIX
+  }
+  pub fn library_cache_path(&self) -> PathBuf {
+    PathBuf::from(&self.TAGCHR_HOME_FOLDER).join(LIBRARY_CACHE_FILE_POSTFIX)
+  }
+  /// Stops trusting the cached tags of the file `i` before writing it.
+  pub fn forget_cached(&mut self, i: usize) {
+    let song_path = self.files[i].tags.path().to_string();
+    if let Err(e) = library::forget(&self.library_cache_path(), &song_path) {
+      self.log.error(format!("Cannot update the library cache for {}: {}", song_path, e));
+    }
+    // the scan may have read the file before
+    self.scan.cache.remove(&song_path);
+  }
+  /// Adds files read by the scan, showing the ones matching the search.
+  pub fn add_files(&mut self, scanned: Vec<Scanned>) {
+    let search = self.search.to_lowercase();
+    for (file, cached) in scanned {
+      if let Some(e) = &file.tags.read_error {
+        self.log.error(e);
+        self.scan.errors += 1;
       }
+      if let Some(tags) = cached {
+        self.scan.cache.insert(tags);
+      }
+      if matches(&file, &search) {
+        // the workers finish the files out of order
+        let at = self.shown_indexes.partition_point(|&j| self.files[j].position < file.position);
+        self.shown_indexes.insert(at, self.files.len());
+      }
+      self.files.push(file);
+      self.scan.read += 1;
     }
-    
-    new.search_mp3_files(new.search.clone());
-    
-    return new;
+  }
+  /// Saves the tags read by the scan for the next startup, files deleted since the last scan are
+  /// left out.
+  pub fn finish_scan(&mut self) {
+    self.scan.done = true;
+    let cache_path = self.library_cache_path();
+    if let Err(e) = self.scan.cache.save(&cache_path) {
+      self.log.error(format!("Cannot save the library cache {}: {}", cache_path.display(), e));
+    }
+    // the tags stay with the files
+    self.scan.cache = LibraryCache::default();
   }
   pub fn get_file(&self, i: usize) -> &Mp3File {
     &self.files[i]
@@ -116,62 +176,102 @@ impl State {
   pub fn get_file_mut(&mut self, i: usize) -> &mut Mp3File {
     &mut self.files[i]
   }
-  fn scan_mp3_files(&mut self, path_input: PathBuf, source: Source) {
-    if let Ok(entries) = fs::read_dir(path_input) {
-      let mut entries = entries.filter_map(Result::ok).collect::<Vec<_>>();
-      entries.sort_by(|a, b|
-        b.metadata().unwrap().modified().unwrap().cmp(&a.metadata().unwrap().modified().unwrap())
-      );
-      for entry in entries {
-        let path = entry.path();
-        if path.is_dir() {
-          self.scan_mp3_files(path.into(), source);
-        } else if path.extension().map_or(false, |ext| ext == "mp3") {
-          self.files.push(Mp3File {
-            tags: SongTags::new(path.to_str().unwrap().into()),
-            name: entry.file_name().to_str().unwrap().into(),
-            path: path.to_str().unwrap().to_string().replace("\\", "/"),
-            source,
-            modified_date: {
-              let modified_date = entry.metadata().unwrap().modified().unwrap();
-              let modified_date: DateTime<Local> = DateTime::from(modified_date);
-              modified_date.naive_local().format_pretty()
-            },
-          });
+  /// Files an edit of the file `i` applies to, the selection when `i` is part of it.
+  pub fn targets(&self, i: usize) -> Vec<usize> {
+    if self.is_selected(i) { self.selection.clone() } else { vec![i] }
+  }
+  pub fn is_selected(&self, i: usize) -> bool {
+    self.selection.binary_search(&i).is_ok()
+  }
+  /// Files with edits that are not saved yet.
+  pub fn pending(&self) -> Vec<usize> {
+    (0..self.files.len()).filter(|&i| self.files[i].tags.edited()).collect()
+  }
+  /// Adds the file `i` to the selection or takes it out.
+  pub fn toggle_selected(&mut self, i: usize) {
+    match self.selection.binary_search(&i) {
+      Ok(j) => {
+        self.selection.remove(j);
+      }
+      Err(j) => self.selection.insert(j, i),
+    }
+  }
+  pub fn select(&mut self, i: usize) {
+    if let Err(j) = self.selection.binary_search(&i) {
+      self.selection.insert(j, i);
+    }
+  }
+  /// Genres read from the files with the number of files using each, most used first.
+  pub fn library_genres(&self) -> Vec<(String, usize)> {
+    let mut genres: Vec<(String, usize)> = Vec::new();
+    for file in &self.files {
+      for g in file.tags.genre.0.original.iter().flat_map(|g| genre::split(g)) {
+        match genres.iter_mut().find(|(other, _)| other.eq_ignore_ascii_case(&g)) {
+          Some((_, count)) => {
+            *count += 1;
+          }
+          None => genres.push((g, 1)),
         }
       }
     }
+    genres.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+    genres
   }
   pub fn search_mp3_files(&mut self, search: String) {
     let search = search.to_lowercase();
-    if search.trim().is_empty() {
-      self.shown_indexes = (0..self.files.len()).collect();
-      return;
-    }
     self.shown_indexes = self.files
       .iter()
       .enumerate()
-      .filter(
-        |(i, f)|
-          f.name.to_lowercase().contains(&search) ||
-          f.tags.title.0.original
-            .as_ref()
-            .map(|t| t.to_lowercase().contains(&search))
-            .unwrap_or_default() ||
-          f.tags.artist.0.original
-            .as_ref()
-            .map(|a| a.to_lowercase().contains(&search))
-            .unwrap_or_default() ||
-          f.tags.genre.0.original
-            .as_ref()
-            .map(|g| g.to_lowercase().contains(&search))
-            .unwrap_or_default() ||
-          f.tags.lyrics.text.original
-            .as_ref()
-            .map(|t| t.to_lowercase().contains(&search))
-            .unwrap_or_default()
-      )
-      .map(|(i, f)| i)
+      .filter(|(_, f)| matches(f, &search))
+      .map(|(i, _)| i)
       .collect();
+    self.shown_indexes.sort_by_key(|&i| self.files[i].position);
+  }
+  /// Row of the file `i` in the table, `None` when it is not shown.
+  pub fn shown_row(&self, i: usize) -> Option<usize> {
+    self.shown_indexes.iter().position(|&j| j == i)
+  }
+  /// File shown after the file `i`, before it when `forward` is false, wrapping around the table.
+  pub fn next_shown(&self, i: usize, forward: bool) -> Option<usize> {
+    let n = self.shown_indexes.len();
+    let next = match self.shown_row(i) {
+      Some(row) if forward => (row + 1) % n,
+      Some(row) => (row + n - 1) % n,
+      None => 0,
+    };
+    self.shown_indexes.get(next).copied()
+  }
+}
+
+/// Whether the file is shown for `search`, lowercased.
+fn matches(f: &Mp3File, search: &str) -> bool {
+  let contains = |value: &Option<String>| {
+    value
+      .as_ref()
+      .map(|v| v.to_lowercase().contains(search))
+      .unwrap_or_default()
+  };
+  search.trim().is_empty() ||
+    f.name.to_lowercase().contains(search) ||
+    contains(&f.tags.title.0.original) ||
+    contains(&f.tags.artist.0.original) ||
+    contains(&f.tags.genre.0.original) ||
+    contains(&f.tags.album.0.original) ||
+    f.tags.lyrics.iter().any(|l| contains(&l.text.original))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::State;
+
+  #[test]
+  fn edits_the_selection_only_from_a_selected_file() {
+    let mut state = State::new();
+    assert_eq!(state.targets(3), [3]);
+    state.select(1);
+    state.select(5);
+    assert_eq!(state.targets(5), [1, 5]);
+    // a file outside the selection is edited alone
+    assert_eq!(state.targets(3), [3]);
   }
 }
@@ -1,46 +1,87 @@
 use core::fmt;
-use id3::{ frame::Lyrics, Tag, TagLike };
+use std::{ fs, hash::{ Hash, Hasher }, io::{ self, Cursor }, sync::Arc };
+use chrono::{ NaiveDate, NaiveTime };
+use id3::{
+  frame::{
+    Comment,
+    Lyrics,
+    Picture,
+    PictureType,
+    SynchronisedLyrics,
+    SynchronisedLyricsType,
+    ExtendedText,
+    TimestampFormat,
+  },
+  Content,
+  Encoding,
+  Error,
+  ErrorKind,
+  Frame,
+  Tag,
+  TagLike,
+  Timestamp,
+  Version,
+  v1,
+};
+use image::ImageReader;
+use super::{
+  atomic,
+  backup::Backups,
+  disk::{ tag_sizes, Fingerprint, Fnv },
+  error::TagError,
+  genre,
+  id3v1,
+  library::Encode,
+  lrc,
+  settings::Settings,
+  validation::{ self, Validator },
+};
 
-#[derive(Clone, Default, Debug)]
-pub enum EditableState {
+#[derive(Clone, Default, PartialEq, Debug)]
+pub enum EditableState<T = String> {
   #[default]
   Unchanged,
-  Changed(String),
+  Changed(T),
   Removed,
 }
 
-impl EditableState {
-  fn compare(original: Option<&String>, new: String) -> Self {
-    if let Some(original) = original {
-      if *original == new {
-        EditableState::Unchanged
-      } else if new.chars().count() == 0 {
-        EditableState::Removed
-      } else {
-        EditableState::Changed(new)
-      }
-    } else {
-      if new.chars().count() == 0 { EditableState::Unchanged } else { EditableState::Changed(new) }
+impl<T: PartialEq> EditableState<T> {
+  fn compare(original: Option<&T>, new: Option<T>) -> Self {
+    match (original, new) {
+      (Some(original), Some(new)) if *original == new => EditableState::Unchanged,
+      (_, Some(new)) => EditableState::Changed(new),
+      (Some(_), None) => EditableState::Removed,
+      (None, None) => EditableState::Unchanged,
     }
   }
 }
 
 #[derive(Clone, Default, Debug)]
-pub struct Editable {
-  pub original: Option<String>,
-  pub state: EditableState,
+pub struct Editable<T = String> {
+  pub original: Option<T>,
+  pub state: EditableState<T>,
+  validator: Option<&'static dyn Validator>,
 }
 
-impl Editable {
-  pub fn new(original: Option<String>) -> Self {
+impl<T: PartialEq> Editable<T> {
+  pub fn new(original: Option<T>) -> Self {
     Self {
       original,
-      state: EditableState::default(),
+      state: EditableState::Unchanged,
+      validator: None,
     }
   }
-  pub fn edit(&mut self, new: String) {
+  /// Sets the current value, `None` removes it.
+  pub fn set(&mut self, new: Option<T>) {
     self.state = EditableState::compare(self.original.as_ref(), new);
   }
+  pub fn current(&self) -> Option<&T> {
+    match &self.state {
+      EditableState::Unchanged => self.original.as_ref(),
+      EditableState::Changed(v) => Some(v),
+      EditableState::Removed => None,
+    }
+  }
   pub fn reset(&mut self) {
     self.state = EditableState::Unchanged;
   }
@@ -52,82 +93,1664 @@ impl Editable {
   }
 }
 
+impl<T: PartialEq + Clone> Editable<T> {
+  /// Takes over the edit of `other`, the same field read at another time.
+  fn apply(&mut self, other: &Editable<T>) {
+    if other.edited() {
+      self.set(other.current().cloned());
+    }
+  }
+  /// The field as if it was added in the editor, `None` when it has no value left.
+  fn detached(&self) -> Option<Self> {
+    let current = self.current().cloned()?;
+    let mut detached = self.clone();
+    detached.original = None;
+    detached.set(Some(current));
+    Some(detached)
+  }
+}
+
+impl<T: PartialEq> PartialEq for Editable<T> {
+  /// Values are compared, the validator is part of the field rather than its value.
+  fn eq(&self, other: &Self) -> bool {
+    self.original == other.original && self.state == other.state
+  }
+}
+
+impl Editable {
+  /// Checks edited values with `validator`.
+  pub fn validated(mut self, validator: &'static dyn Validator) -> Self {
+    self.validator = Some(validator);
+    self
+  }
+  /// Edits text from an input, an empty string removes the value.
+  pub fn edit(&mut self, new: String) {
+    self.set(if new.chars().count() == 0 { None } else { Some(new) });
+  }
+  /// Why the edited value cannot be saved, values read from the file are never rejected.
+  pub fn error(&self) -> Option<String> {
+    match (&self.state, self.validator) {
+      (EditableState::Changed(v), Some(validator)) => validator.validate(v).err(),
+      _ => None,
+    }
+  }
+  pub fn valid(&self) -> bool {
+    self.error().is_none()
+  }
+}
+
 impl fmt::Display for Editable {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    let current = match &self.state {
-      EditableState::Unchanged => self.original.clone(),
-      EditableState::Changed(v) => Some(v.clone()),
-      EditableState::Removed => None,
-    };
-    write!(f, "{}", current.unwrap_or_default())
+    write!(f, "{}", self.current().map(|v| v.as_str()).unwrap_or_default())
   }
 }
 
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Default, PartialEq, Debug)]
 pub struct EditableTag(pub Editable);
 
-#[derive(Clone, Default, Debug)]
+/// Parses "n" or "n/total" into its numeric parts.
+pub fn parse_number_pair(s: &str) -> Option<(u32, Option<u32>)> {
+  let mut split = s.trim().splitn(2, '/');
+  let n = split.next()?.trim().parse().ok()?;
+  let total = match split.next() {
+    Some(total) => Some(total.trim().parse().ok()?),
+    None => None,
+  };
+  Some((n, total))
+}
+
+/// Parses an ID3v2.4 timestamp, `yyyy[-MM[-dd[THH[:mm[:ss]]]]]`, checking that the date exists.
+pub fn parse_timestamp(s: &str) -> Option<Timestamp> {
+  /// `Ok(None)` for a missing part, `Err` for a part that is not `len` digits.
+  fn part(s: Option<&str>, len: usize) -> Result<Option<u32>, ()> {
+    match s {
+      None => Ok(None),
+      Some(s) if s.len() == len && s.chars().all(|c| c.is_ascii_digit()) => Ok(s.parse().ok()),
+      Some(_) => Err(()),
+    }
+  }
+  let (date, time) = match s.trim().split_once('T') {
+    Some((date, time)) => (date, Some(time)),
+    None => (s.trim(), None),
+  };
+  let mut date = date.split('-');
+  let year = part(date.next(), 4).ok()??;
+  let month = part(date.next(), 2).ok()?;
+  let day = part(date.next(), 2).ok()?;
+  if date.next().is_some() {
+    return None;
+  }
+  let (mut hour, mut minute, mut second) = (None, None, None);
+  if let Some(time) = time {
+    let mut time = time.split(':');
+    hour = Some(part(time.next(), 2).ok()??);
+    minute = part(time.next(), 2).ok()?;
+    second = part(time.next(), 2).ok()?;
+    if day.is_none() || time.next().is_some() {
+      return None;
+    }
+  }
+  NaiveDate::from_ymd_opt(year as i32, month.unwrap_or(1), day.unwrap_or(1))?;
+  NaiveTime::from_hms_opt(hour.unwrap_or(0), minute.unwrap_or(0), second.unwrap_or(0))?;
+  Some(Timestamp {
+    year: year as i32,
+    month: month.map(|m| m as u8),
+    day: day.map(|d| d as u8),
+    hour: hour.map(|h| h as u8),
+    minute: minute.map(|m| m as u8),
+    second: second.map(|s| s as u8),
+  })
+}
+
+/// Dates kept in different frames by ID3v2.3 and ID3v2.4.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum DateFrame {
+  /// TDRC, or TYER with TDAT and TIME.
+  Recorded,
+  /// TDOR, or TORY with only the year.
+  Original,
+  /// TDRL, which ID3v2.3 lacks and is written as is.
+  Released,
+}
+
+impl DateFrame {
+  /// Frames holding the date in any version.
+  fn ids(self) -> &'static [&'static str] {
+    match self {
+      DateFrame::Recorded => &["TDRC", "TYER", "TDAT", "TIME"],
+      DateFrame::Original => &["TDOR", "TORY"],
+      DateFrame::Released => &["TDRL"],
+    }
+  }
+  fn read(self, tag: &Tag) -> Option<String> {
+    let text = |id: &str| {
+      tag
+        .get(id)
+        .and_then(|f| f.content().text())
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+    };
+    let digits = |t: &String| t.len() == 4 && t.chars().all(|c| c.is_ascii_digit());
+    match self {
+      DateFrame::Recorded => {
+        if let Some(date) = text("TDRC") {
+          return Some(date);
+        }
+        let mut date = text("TYER")?;
+        // TDAT is "DDMM" and TIME "HHMM"
+        if let Some(dm) = text("TDAT").filter(digits) {
+          date += &format!("-{}-{}", &dm[2..], &dm[..2]);
+          if let Some(hm) = text("TIME").filter(digits) {
+            date += &format!("T{}:{}", &hm[..2], &hm[2..]);
+          }
+        }
+        Some(date)
+      }
+      DateFrame::Original => text("TDOR").or_else(|| text("TORY")),
+      DateFrame::Released => text("TDRL"),
+    }
+  }
+  /// Replaces the frames of the date with the ones `version` uses.
+  fn write(self, tag: &mut Tag, date: Option<&Timestamp>, version: Version) {
+    for id in self.ids() {
+      tag.remove(*id);
+    }
+    let Some(date) = date else {
+      return;
+    };
+    match (self, version) {
+      (DateFrame::Recorded, Version::Id3v23) => {
+        tag.set_text("TYER", format!("{:04}", date.year));
+        if let (Some(month), Some(day)) = (date.month, date.day) {
+          tag.set_text("TDAT", format!("{:02}{:02}", day, month));
+        }
+        if let Some(hour) = date.hour {
+          tag.set_text("TIME", format!("{:02}{:02}", hour, date.minute.unwrap_or(0)));
+        }
+      }
+      (DateFrame::Original, Version::Id3v23) => {
+        tag.set_text("TORY", format!("{:04}", date.year));
+      }
+      (DateFrame::Recorded, _) => tag.set_text("TDRC", date.to_string()),
+      (DateFrame::Original, _) => tag.set_text("TDOR", date.to_string()),
+      (DateFrame::Released, _) => tag.set_text("TDRL", date.to_string()),
+    }
+  }
+}
+
+/// Converts CRLF and lone CR line endings to LF.
+pub fn normalize_line_endings(s: &str) -> String {
+  s.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+/// Keeps every synced line on a single line of text.
+fn normalize_synced(content: &[(u32, String)]) -> Vec<(u32, String)> {
+  content
+    .iter()
+    .map(|(ms, text)| (*ms, normalize_line_endings(text).trim_matches('\n').replace('\n', " ")))
+    .collect()
+}
+
+/// Unsynced (USLT) and synced (SYLT) lyrics sharing a language and description.
+#[derive(Clone, Default, PartialEq, Debug)]
 pub struct LyricsEditableTag {
   pub lang: Editable,
   pub desc: Editable,
   pub text: Editable,
+  /// Lines of the SYLT frame timed in milliseconds.
+  pub synced: Editable<Vec<(u32, String)>>,
+  synced_type: Option<SynchronisedLyricsType>,
 }
 
 impl LyricsEditableTag {
   pub fn new(lyrics: Option<Lyrics>) -> Self {
     Self {
-      lang: Editable::new(lyrics.as_ref().map(|l| l.lang.clone())),
+      lang: Editable::new(lyrics.as_ref().map(|l| l.lang.clone())).validated(
+        &validation::Language
+      ),
       desc: Editable::new(lyrics.as_ref().map(|l| l.description.clone())),
-      text: Editable::new(lyrics.map(|l| l.text.clone())),
+      text: Editable::new(lyrics.map(|l| normalize_line_endings(&l.text))),
+      ..Default::default()
+    }
+  }
+  /// Lyrics with only a SYLT frame.
+  fn synced(synced: &SynchronisedLyrics) -> Self {
+    let mut lyrics = Self {
+      lang: Editable::new(Some(synced.lang.clone())).validated(&validation::Language),
+      desc: Editable::new(Some(synced.description.clone())),
+      ..Default::default()
+    };
+    lyrics.read_synced(synced);
+    lyrics
+  }
+  /// Attaches a SYLT frame read from the tag.
+  fn read_synced(&mut self, synced: &SynchronisedLyrics) {
+    self.synced = Editable::new(Some(normalize_synced(&synced.content)));
+    self.synced_type = Some(synced.content_type);
+  }
+  pub fn edited(&self) -> bool {
+    self.lang.edited() || self.desc.edited() || self.text.edited() || self.synced.edited()
+  }
+  /// Language and type of the SYLT frame written for these lyrics, id3 keeps one frame per pair.
+  fn synced_key(&self) -> Option<(String, SynchronisedLyricsType)> {
+    self.synced.current()?;
+    Some((self.lang.to_string(), self.synced_type.unwrap_or(SynchronisedLyricsType::Lyrics)))
+  }
+  /// Whether any of the frames was read from the tag rather than added in the editor.
+  pub fn read_from_tag(&self) -> bool {
+    self.text.original.is_some() || self.synced.original.is_some()
+  }
+  /// Language and description, no two USLT frames in a tag may share them.
+  pub fn key(&self) -> (String, String) {
+    (self.lang.to_string(), self.desc.to_string())
+  }
+  pub fn removed(&self) -> bool {
+    self.read_from_tag() && self.text.current().is_none() && self.synced.current().is_none()
+  }
+  /// Text split into the lines shown in the text area.
+  pub fn text_lines(&self) -> Vec<String> {
+    self.text.to_string().split('\n').map(String::from).collect()
+  }
+  /// Edits the text from all lines of a text area.
+  pub fn edit_text_lines(&mut self, lines: &[String]) {
+    self.text.edit(lines.join("\n"));
+  }
+  /// Synced lines formatted as LRC for the text area.
+  pub fn synced_lines(&self) -> Vec<String> {
+    let lines = self.synced.current().map(|c| lrc::format(c)).unwrap_or_default();
+    lines.split('\n').map(String::from).collect()
+  }
+  /// Edits the synced lines from LRC lines of a text area, keeps the previous value and fails
+  /// with the line number when a line has no timestamp.
+  pub fn edit_synced_lines(&mut self, lines: &[String]) -> Result<(), usize> {
+    let content = lrc::parse(&lines.join("\n"))?;
+    self.synced.set(if content.is_empty() { None } else { Some(content) });
+    Ok(())
+  }
+  fn reset(&mut self) {
+    self.lang.reset();
+    self.desc.reset();
+    self.text.reset();
+    self.synced.reset();
+  }
+  /// Whether `lyrics` is the frame this tag was read from.
+  fn is_original(&self, lyrics: &Lyrics) -> bool {
+    self.lang.original.as_ref() == Some(&lyrics.lang) &&
+      self.desc.original.as_ref() == Some(&lyrics.description) &&
+      self.text.original.as_ref() == Some(&normalize_line_endings(&lyrics.text))
+  }
+  /// Whether `synced` is the SYLT frame this tag was read from.
+  fn is_original_synced(&self, synced: &SynchronisedLyrics) -> bool {
+    self.lang.original.as_ref() == Some(&synced.lang) &&
+      self.desc.original.as_ref() == Some(&synced.description) &&
+      synced.timestamp_format == TimestampFormat::Ms &&
+      self.synced.original.as_ref() == Some(&normalize_synced(&synced.content))
+  }
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct CommentEditableTag {
+  pub lang: Editable,
+  pub desc: Editable,
+  pub text: Editable,
+}
+
+impl CommentEditableTag {
+  pub fn new(comment: Option<Comment>) -> Self {
+    Self {
+      lang: Editable::new(comment.as_ref().map(|c| c.lang.clone())).validated(
+        &validation::Language
+      ),
+      desc: Editable::new(comment.as_ref().map(|c| c.description.clone())),
+      text: Editable::new(comment.map(|c| normalize_line_endings(&c.text))),
+    }
+  }
+  pub fn edited(&self) -> bool {
+    self.lang.edited() || self.desc.edited() || self.text.edited()
+  }
+  /// Language and description, no two COMM frames in a tag may share them.
+  pub fn key(&self) -> (String, String) {
+    (self.lang.to_string(), self.desc.to_string())
+  }
+  pub fn removed(&self) -> bool {
+    self.text.original.is_some() && self.text.current().is_none()
+  }
+  /// Text split into the lines shown in the text area.
+  pub fn text_lines(&self) -> Vec<String> {
+    self.text.to_string().split('\n').map(String::from).collect()
+  }
+  /// Edits the text from all lines of a text area.
+  pub fn edit_text_lines(&mut self, lines: &[String]) {
+    self.text.edit(lines.join("\n"));
+  }
+  fn reset(&mut self) {
+    self.lang.reset();
+    self.desc.reset();
+    self.text.reset();
+  }
+  /// Whether `comment` is the frame this tag was read from.
+  fn is_original(&self, comment: &Comment) -> bool {
+    self.lang.original.as_ref() == Some(&comment.lang) &&
+      self.desc.original.as_ref() == Some(&comment.description) &&
+      self.text.original.as_ref() == Some(&normalize_line_endings(&comment.text))
+  }
+}
+
+/// Reason shown for lyrics and comments conflicting with another frame.
+pub const DUPLICATE_KEY: &str = "Another frame has the same language and description";
+/// Reason shown for synced lyrics conflicting with another SYLT frame.
+pub const DUPLICATE_SYNCED: &str = "Another synced frame has the same language and type";
+/// Reason shown for raw frames conflicting with another frame.
+pub const DUPLICATE_FRAME: &str = "Another frame has the same ID and description";
+
+/// Frames edited through the fixed fields and sub-screens, they are read-only among all frames.
+pub const MANAGED_FRAME_IDS: [&str; 18] = [
+  "TIT2",
+  "TPE1",
+  "TYER",
+  "TDAT",
+  "TIME",
+  "TDRC",
+  "TORY",
+  "TDOR",
+  "TDRL",
+  "TCON",
+  "TALB",
+  "TPE2",
+  "TRCK",
+  "TPOS",
+  "USLT",
+  "SYLT",
+  "COMM",
+  "APIC",
+];
+
+/// Any frame of the tag. Text and TXXX frames can be edited, everything else only removed.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RawFrameEditableTag {
+  /// Only editable for frames added in the editor.
+  pub id: Editable,
+  /// Description of TXXX frames.
+  pub desc: Editable,
+  /// Text of text frames with null separators shown as `\0`, decoded content of other frames.
+  pub value: Editable,
+  original: Option<ReadFrame>,
+}
+
+/// What is kept of a frame read from the tag to find it again on save, its content may be a large
+/// binary payload.
+#[derive(Clone, PartialEq, Debug)]
+struct ReadFrame {
+  id: String,
+  text: bool,
+  hash: u64,
+}
+
+impl ReadFrame {
+  fn new(frame: &Frame) -> Self {
+    Self {
+      id: frame.id().to_string(),
+      text: matches!(frame.content(), Content::Text(_) | Content::ExtendedText(_)),
+      hash: content_hash(frame),
+    }
+  }
+}
+
+fn content_hash(frame: &Frame) -> u64 {
+  let mut hasher = Fnv::default();
+  frame.content().hash(&mut hasher);
+  hasher.finish()
+}
+
+impl RawFrameEditableTag {
+  pub fn new(frame: &Frame) -> Self {
+    let (desc, value) = match frame.content() {
+      Content::Text(text) => (None, text.replace('\0', "\\0")),
+      Content::ExtendedText(ext) => (Some(ext.description.clone()), ext.value.replace('\0', "\\0")),
+      content => (None, content.to_string()),
+    };
+    let mut value = Editable::new(Some(value));
+    value.validator = validation::for_frame(frame.id());
+    Self {
+      id: Editable::new(Some(frame.id().to_string())),
+      desc: Editable::new(desc),
+      value,
+      original: Some(ReadFrame::new(frame)),
+    }
+  }
+  /// Adds a text frame, a TXXX frame when `id` is "TXXX".
+  fn added(id: &str) -> Self {
+    let mut frame = Self {
+      id: Editable::new(None).validated(&validation::FrameId),
+      desc: Editable::new(None),
+      value: Editable::new(None),
+      original: None,
+    };
+    frame.edit_id(id.into());
+    frame
+  }
+  /// Changes the ID of an added frame and checks the value in the format of the new ID.
+  pub fn edit_id(&mut self, id: String) {
+    self.value.validator = validation::for_frame(&id);
+    self.id.edit(id);
+  }
+  pub fn is_new(&self) -> bool {
+    self.original.is_none()
+  }
+  pub fn is_txxx(&self) -> bool {
+    self.id.to_string() == "TXXX"
+  }
+  /// Whether the value can be edited here.
+  pub fn is_text(&self) -> bool {
+    match &self.original {
+      Some(frame) => frame.text && !self.managed(),
+      None => true,
+    }
+  }
+  /// Whether the frame belongs to the fixed editor.
+  pub fn managed(&self) -> bool {
+    MANAGED_FRAME_IDS.contains(&self.id.to_string().as_str())
+  }
+  pub fn edited(&self) -> bool {
+    self.id.edited() || self.desc.edited() || self.value.edited()
+  }
+  pub fn removed(&self) -> bool {
+    self.original.is_some() && self.value.current().is_none()
+  }
+  /// Frame names are shown for known IDs.
+  pub fn name(&self) -> String {
+    // the name only depends on the ID
+    Frame::text(self.id.to_string(), "").name().to_string()
+  }
+  /// Why the ID cannot be saved, new frame IDs must name a text frame the fixed editor does not
+  /// manage.
+  pub fn id_error(&self) -> Option<String> {
+    match self.id.current() {
+      None if self.is_new() => Some("Enter a frame ID".into()),
+      _ => self.id.error(),
+    }
+  }
+  pub fn valid_id(&self) -> bool {
+    self.id_error().is_none()
+  }
+  /// The frame to write, `None` when removed.
+  fn to_frame(&self) -> Option<Frame> {
+    let value = self.value.current()?.replace("\\0", "\0");
+    if self.is_txxx() {
+      Some(Frame::from(ExtendedText { description: self.desc.to_string(), value }))
+    } else {
+      Some(Frame::text(self.id.to_string(), value))
+    }
+  }
+}
+
+/// All standard APIC picture types in the order of their ID3 codes.
+pub const PICTURE_TYPES: [PictureType; 21] = [
+  PictureType::Other,
+  PictureType::Icon,
+  PictureType::OtherIcon,
+  PictureType::CoverFront,
+  PictureType::CoverBack,
+  PictureType::Leaflet,
+  PictureType::Media,
+  PictureType::LeadArtist,
+  PictureType::Artist,
+  PictureType::Conductor,
+  PictureType::Band,
+  PictureType::Composer,
+  PictureType::Lyricist,
+  PictureType::RecordingLocation,
+  PictureType::DuringRecording,
+  PictureType::DuringPerformance,
+  PictureType::ScreenCapture,
+  PictureType::BrightFish,
+  PictureType::Illustration,
+  PictureType::BandLogo,
+  PictureType::PublisherLogo,
+];
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PictureEditableTag(pub Editable<Arc<Picture>>);
+
+impl PictureEditableTag {
+  /// Reads a PNG or JPEG image from disk.
+  pub fn read_image(path: &str, picture_type: PictureType) -> Result<Picture, String> {
+    let data = fs::read(path).map_err(|e| e.to_string())?;
+    let mime_type = match image::guess_format(&data) {
+      Ok(format @ (image::ImageFormat::Png | image::ImageFormat::Jpeg)) => format.to_mime_type(),
+      _ => {
+        return Err("Unsupported image format, expected PNG or JPEG".into());
+      }
+    };
+    Ok(Picture {
+      mime_type: mime_type.into(),
+      picture_type,
+      description: String::new(),
+      data,
+    })
+  }
+  /// Width and height read from the image header.
+  pub fn dimensions(picture: &Picture) -> Option<(u32, u32)> {
+    ImageReader::new(Cursor::new(&picture.data))
+      .with_guessed_format()
+      .ok()?
+      .into_dimensions()
+      .ok()
+  }
+}
+
+/// ID3v2 version tags are written with.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum VersionSetting {
+  /// The version the tag was read with, v2.4 for files without a tag.
+  #[default]
+  Keep,
+  Id3v23,
+  Id3v24,
+}
+
+impl VersionSetting {
+  pub const ALL: [VersionSetting; 3] = [
+    VersionSetting::Keep,
+    VersionSetting::Id3v23,
+    VersionSetting::Id3v24,
+  ];
+  pub fn cycle(self, forward: bool) -> Self {
+    cycle(&Self::ALL, self, forward)
+  }
+  pub fn key(self) -> &'static str {
+    match self {
+      VersionSetting::Keep => "keep",
+      VersionSetting::Id3v23 => "2.3",
+      VersionSetting::Id3v24 => "2.4",
+    }
+  }
+  pub fn from_key(key: &str) -> Option<Self> {
+    Self::ALL.into_iter().find(|v| v.key() == key)
+  }
+}
+
+impl fmt::Display for VersionSetting {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      VersionSetting::Keep => write!(f, "Keep"),
+      VersionSetting::Id3v23 => write!(f, "ID3v2.3"),
+      VersionSetting::Id3v24 => write!(f, "ID3v2.4"),
+    }
+  }
+}
+
+/// Encoding of text frames, ID3v2.3 has no UTF-8 so UTF-16 is written there instead.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum EncodingSetting {
+  /// The encoding frames were read with where known, the version's default otherwise.
+  #[default]
+  Keep,
+  Utf16,
+  Utf8,
+}
+
+impl EncodingSetting {
+  pub const ALL: [EncodingSetting; 3] = [
+    EncodingSetting::Keep,
+    EncodingSetting::Utf16,
+    EncodingSetting::Utf8,
+  ];
+  pub fn cycle(self, forward: bool) -> Self {
+    cycle(&Self::ALL, self, forward)
+  }
+  pub fn key(self) -> &'static str {
+    match self {
+      EncodingSetting::Keep => "keep",
+      EncodingSetting::Utf16 => "utf-16",
+      EncodingSetting::Utf8 => "utf-8",
+    }
+  }
+  pub fn from_key(key: &str) -> Option<Self> {
+    Self::ALL.into_iter().find(|e| e.key() == key)
+  }
+  /// Encoding to write a frame read with `encoding` in a tag of `version`.
+  fn resolve(self, version: Version, encoding: Option<Encoding>) -> Option<Encoding> {
+    let encoding = match self {
+      EncodingSetting::Keep => encoding,
+      EncodingSetting::Utf16 => Some(Encoding::UTF16),
+      EncodingSetting::Utf8 => Some(Encoding::UTF8),
+    };
+    match (version, encoding) {
+      (Version::Id3v24, encoding) => encoding,
+      (_, Some(Encoding::UTF8 | Encoding::UTF16BE)) => Some(Encoding::UTF16),
+      (_, encoding) => encoding,
+    }
+  }
+}
+
+impl fmt::Display for EncodingSetting {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      EncodingSetting::Keep => write!(f, "Keep"),
+      EncodingSetting::Utf16 => write!(f, "UTF-16"),
+      EncodingSetting::Utf8 => write!(f, "UTF-8"),
+    }
+  }
+}
+
+fn cycle<T: Copy + PartialEq>(all: &[T], current: T, forward: bool) -> T {
+  let i = all.iter().position(|v| *v == current).unwrap_or_default();
+  all[(if forward { i + 1 } else { i + all.len() - 1 }) % all.len()]
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct WriteOptions {
+  pub version: VersionSetting,
+  pub encoding: EncodingSetting,
+}
+
+/// What saving does to the ID3v1 tag at the end of the file.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum Id3v1Action {
+  /// Rewrites an existing or edited ID3v1 tag, files without one stay without.
+  #[default]
+  Update,
+  /// Writes the ID3v1 tag even when the file had none.
+  Write,
+  Strip,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Id3v1Field {
+  Title,
+  Artist,
+  Album,
+  Year,
+  Comment,
+  Track,
+  Genre,
+}
+
+impl Id3v1Field {
+  pub const ALL: [Id3v1Field; 7] = [
+    Id3v1Field::Title,
+    Id3v1Field::Artist,
+    Id3v1Field::Album,
+    Id3v1Field::Year,
+    Id3v1Field::Comment,
+    Id3v1Field::Track,
+    Id3v1Field::Genre,
+  ];
+  /// Characters the field holds in an ID3v1.1 tag.
+  pub fn max_len(self) -> usize {
+    match self {
+      Id3v1Field::Title | Id3v1Field::Artist | Id3v1Field::Album => 30,
+      Id3v1Field::Year => 4,
+      Id3v1Field::Comment => 28,
+      Id3v1Field::Track => 3,
+      Id3v1Field::Genre => usize::MAX,
+    }
+  }
+  /// The value an ID3v1 tag can hold for `value`, `None` for unknown genres and tracks over 255.
+  pub fn fit(self, value: &str) -> Option<String> {
+    match self {
+      Id3v1Field::Genre => id3v1::genre_id(value).and_then(id3v1::genre_name),
+      Id3v1Field::Track => value.trim().parse::<u8>().ok().map(|t| t.to_string()),
+      f => Some(value.chars().take(f.max_len()).collect::<String>().trim_end().to_string()),
+    }
+  }
+}
+
+/// Fields of the ID3v1 tag at the end of the file.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Id3v1EditableTag {
+  /// Whether the file had an ID3v1 tag when it was read.
+  pub present: bool,
+  fields: [Editable; 7],
+}
+
+impl Id3v1EditableTag {
+  fn new(tag: Option<v1::Tag>) -> Self {
+    let Some(tag) = tag else {
+      return Self::default();
+    };
+    let value = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+    Self {
+      present: true,
+      fields: [
+        value(&tag.title),
+        value(&tag.artist),
+        value(&tag.album),
+        value(&tag.year),
+        value(&tag.comment),
+        tag.track.filter(|t| *t > 0).map(|t| t.to_string()),
+        tag.genre().map(String::from),
+      ].map(Editable::new),
+    }
+  }
+  pub fn field(&self, f: Id3v1Field) -> &Editable {
+    &self.fields[f as usize]
+  }
+  pub fn field_mut(&mut self, f: Id3v1Field) -> &mut Editable {
+    &mut self.fields[f as usize]
+  }
+  pub fn edited(&self) -> bool {
+    self.fields.iter().any(|f| f.edited())
+  }
+  pub fn reset(&mut self) {
+    for field in self.fields.iter_mut() {
+      field.reset();
+    }
+  }
+  fn to_tag(&self) -> v1::Tag {
+    let text = |f: Id3v1Field| self.field(f).to_string();
+    let mut tag = v1::Tag::new();
+    tag.title = text(Id3v1Field::Title);
+    tag.artist = text(Id3v1Field::Artist);
+    tag.album = text(Id3v1Field::Album);
+    tag.year = text(Id3v1Field::Year);
+    tag.comment = text(Id3v1Field::Comment);
+    tag.track = text(Id3v1Field::Track).parse().ok();
+    tag.genre_id = id3v1::genre_id(&text(Id3v1Field::Genre)).unwrap_or(u8::MAX);
+    tag
+  }
+}
+
+/// Field of the ID of a frame in the list of frames, only frames added in the editor change it.
+const FRAME_ID: &str = "Frame ID";
+
+/// An entry of a list of frames, told apart from the others by the values read from the file.
+trait Entry: Sized {
+  fn changed(&self) -> bool;
+  /// Whether both entries were read from the same frame.
+  fn same_frame(&self, other: &Self) -> bool;
+  fn take_edits(&mut self, other: &Self);
+  /// The entry as if it was added in the editor, `None` when nothing of it would be written.
+  fn detached(&self) -> Option<Self>;
+}
+
+impl Entry for LyricsEditableTag {
+  fn changed(&self) -> bool {
+    self.edited()
+  }
+  fn same_frame(&self, other: &Self) -> bool {
+    self.read_from_tag() &&
+      self.lang.original == other.lang.original &&
+      self.desc.original == other.desc.original &&
+      self.text.original == other.text.original &&
+      self.synced.original == other.synced.original
+  }
+  fn take_edits(&mut self, other: &Self) {
+    self.lang.apply(&other.lang);
+    self.desc.apply(&other.desc);
+    self.text.apply(&other.text);
+    self.synced.apply(&other.synced);
+    self.synced_type = self.synced_type.or(other.synced_type);
+  }
+  fn detached(&self) -> Option<Self> {
+    let text = self.text.detached();
+    let synced = self.synced.detached();
+    if text.is_none() && synced.is_none() {
+      return None;
+    }
+    Some(Self {
+      lang: self.lang.detached().unwrap_or_else(|| self.lang.clone()),
+      desc: self.desc.detached().unwrap_or_else(|| self.desc.clone()),
+      text: text.unwrap_or_else(|| Editable::new(None)),
+      synced: synced.unwrap_or_else(|| Editable::new(None)),
+      synced_type: self.synced_type,
+    })
+  }
+}
+
+impl Entry for CommentEditableTag {
+  fn changed(&self) -> bool {
+    self.edited()
+  }
+  fn same_frame(&self, other: &Self) -> bool {
+    self.text.original.is_some() &&
+      self.lang.original == other.lang.original &&
+      self.desc.original == other.desc.original &&
+      self.text.original == other.text.original
+  }
+  fn take_edits(&mut self, other: &Self) {
+    self.lang.apply(&other.lang);
+    self.desc.apply(&other.desc);
+    self.text.apply(&other.text);
+  }
+  fn detached(&self) -> Option<Self> {
+    Some(Self {
+      lang: self.lang.detached().unwrap_or_else(|| self.lang.clone()),
+      desc: self.desc.detached().unwrap_or_else(|| self.desc.clone()),
+      text: self.text.detached()?,
+    })
+  }
+}
+
+impl Entry for RawFrameEditableTag {
+  fn changed(&self) -> bool {
+    self.edited()
+  }
+  fn same_frame(&self, other: &Self) -> bool {
+    self.original.is_some() && self.original == other.original
+  }
+  fn take_edits(&mut self, other: &Self) {
+    self.id.apply(&other.id);
+    self.desc.apply(&other.desc);
+    self.value.apply(&other.value);
+  }
+  fn detached(&self) -> Option<Self> {
+    Some(Self {
+      id: self.id.detached()?,
+      desc: self.desc.detached().unwrap_or_else(|| Editable::new(None)),
+      value: self.value.detached()?,
+      original: None,
+    })
+  }
+}
+
+impl Entry for PictureEditableTag {
+  fn changed(&self) -> bool {
+    self.0.edited()
+  }
+  fn same_frame(&self, other: &Self) -> bool {
+    self.0.original.is_some() && self.0.original == other.0.original
+  }
+  fn take_edits(&mut self, other: &Self) {
+    self.0.apply(&other.0);
+  }
+  fn detached(&self) -> Option<Self> {
+    self.0.detached().map(PictureEditableTag)
+  }
+}
+
+/// Applies the edited entries of `mine` to the entries of `theirs` read from the same frame,
+/// entries whose frame is gone from `theirs` are added to it.
+fn rebase_entries<E: Entry>(mine: &[E], theirs: &mut Vec<E>) {
+  for entry in mine.iter().filter(|e| e.changed()) {
+    match theirs.iter_mut().find(|t| t.same_frame(entry)) {
+      Some(t) => t.take_edits(entry),
+      None => theirs.extend(entry.detached()),
+    }
+  }
+}
+
+/// A field edited here that something else changed on disk too.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Conflict {
+  pub field: String,
+  /// Value read from the file before either change.
+  pub original: Option<String>,
+  pub mine: Option<String>,
+  pub theirs: Option<String>,
+}
+
+/// An edited field with its value read from the file and the value to be written, `None` when
+/// the field is absent.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Change {
+  pub field: String,
+  pub original: Option<String>,
+  pub current: Option<String>,
+}
+
+impl Change {
+  fn of(field: impl Into<String>, editable: &Editable) -> Self {
+    Self {
+      field: field.into(),
+      original: editable.original.clone(),
+      current: editable.current().cloned(),
     }
   }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct SongTags {
   song_path: String,
+  /// Version of the tag read from the file, `None` when the file has no tag.
+  pub version: Option<Version>,
+  /// Why the tag could not be read in full, the fields hold whatever was decoded.
+  pub read_error: Option<TagError>,
+  /// The file as it was read, `None` when it could not be.
+  pub disk: Option<Fingerprint>,
+  /// Write options of this file, `None` uses the global ones.
+  pub write_options: Editable<WriteOptions>,
   pub title: EditableTag,
   pub artist: EditableTag,
-  pub year: EditableTag,
+  /// Recording date.
+  pub date: EditableTag,
+  pub original_date: EditableTag,
+  pub release_date: EditableTag,
   pub genre: EditableTag,
-  pub lyrics: LyricsEditableTag,
+  pub album: EditableTag,
+  pub album_artist: EditableTag,
+  pub track: EditableTag,
+  pub disc: EditableTag,
+  pub lyrics: Vec<LyricsEditableTag>,
+  pub comments: Vec<CommentEditableTag>,
+  pub frames: Vec<RawFrameEditableTag>,
+  pub pictures: Vec<PictureEditableTag>,
+  /// Whether `pictures` were read, tags from the library cache leave them out until the file is
+  /// shown.
+  pictures_loaded: bool,
+  /// Language and type of the SYLT frames not timed in milliseconds, which are kept as they are.
+  other_synced: Vec<(String, SynchronisedLyricsType)>,
+  pub id3v1: Id3v1EditableTag,
 }
 
 impl SongTags {
   pub fn new(song_path: String) -> Self {
-    let tag = match Tag::read_from_path(song_path.clone()) {
-      Ok(x) => {x},
-      Err(e) => {print!("{}", e); Tag::new()}
+    let read = Tag::read_from_path(song_path.clone());
+    let id3v1 = v1::Tag::read_from_path(&song_path).ok();
+    let disk = Fingerprint::of(&song_path).ok();
+    Self::read(song_path, read, id3v1, disk)
+  }
+  /// Tags of the file at `song_path` from `data`, its ID3v2 tag followed by its ID3v1 tag as
+  /// stored in the file, without reading the file. `disk` is the file `data` was read from.
+  pub fn from_tags(song_path: String, data: &[u8], disk: Option<Fingerprint>) -> Self {
+    let (v2, v1) = tag_sizes(data);
+    let read = match v2 {
+      0 => Err(Error::new(ErrorKind::NoTag, "")),
+      _ => Tag::read_from2(Cursor::new(&data[..v2])),
+    };
+    let id3v1 = v1::Tag::read_from(Cursor::new(&data[data.len() - v1..])).ok();
+    Self::read(song_path, read, id3v1, disk)
+  }
+  fn read(
+    song_path: String,
+    read: id3::Result<Tag>,
+    id3v1: Option<v1::Tag>,
+    disk: Option<Fingerprint>
+  ) -> Self {
+    let version = read.as_ref().ok().map(|t| t.version());
+    let id3v1 = Id3v1EditableTag::new(id3v1);
+    let (tag, read_error) = match read {
+      Ok(tag) => (tag, None),
+      Err(Error { kind: ErrorKind::NoTag, .. }) => (Tag::new(), None),
+      Err(mut e) => {
+        let partial = e.partial_tag.take().unwrap_or_default();
+        (partial, Some(TagError::from_id3(e, &song_path)))
+      }
     };
     Self {
+      disk,
       song_path,
+      version,
+      read_error,
+      write_options: Editable::new(None),
       title: EditableTag(Editable::new(tag.title().map(|n| n.into()))),
       artist: EditableTag(Editable::new(tag.artist().map(|a| a.into()))),
-      year: EditableTag(Editable::new(tag.year().map(|y| y.to_string()))),
-      genre: EditableTag(Editable::new(tag.genre().map(|g| g.to_string()))),
-      lyrics: LyricsEditableTag::new({
-        let l = tag.lyrics().next().cloned();
-        l
-      }),
+      date: EditableTag(
+        Editable::new(DateFrame::Recorded.read(&tag)).validated(&validation::Date)
+      ),
+      original_date: EditableTag(
+        Editable::new(DateFrame::Original.read(&tag)).validated(&validation::Date)
+      ),
+      release_date: EditableTag(
+        Editable::new(DateFrame::Released.read(&tag)).validated(&validation::Date)
+      ),
+      genre: EditableTag(
+        Editable::new(
+          tag
+            .genre()
+            .map(genre::decode)
+            .filter(|g| !g.is_empty())
+            .map(|g| g.join(genre::SEPARATOR))
+        )
+      ),
+      album: EditableTag(Editable::new(tag.album().map(|a| a.into()))),
+      album_artist: EditableTag(Editable::new(tag.album_artist().map(|a| a.into()))),
+      track: EditableTag(
+        Editable::new(tag.get("TRCK").and_then(|f| f.content().text()).map(|t| t.into()))
+          .validated(&validation::NumberPair)
+      ),
+      disc: EditableTag(
+        Editable::new(tag.get("TPOS").and_then(|f| f.content().text()).map(|d| d.into()))
+          .validated(&validation::NumberPair)
+      ),
+      lyrics: {
+        let mut lyrics = tag
+          .lyrics()
+          .map(|l| LyricsEditableTag::new(Some(l.clone())))
+          .collect::<Vec<_>>();
+        // frames timed in MPEG frames are left untouched
+        let synced_ms = tag
+          .synchronised_lyrics()
+          .filter(|s| s.timestamp_format == TimestampFormat::Ms);
+        for synced in synced_ms {
+          let key = (synced.lang.clone(), synced.description.clone());
+          match lyrics.iter_mut().find(|l| l.synced.original.is_none() && l.key() == key) {
+            Some(l) => l.read_synced(synced),
+            None => lyrics.push(LyricsEditableTag::synced(synced)),
+          }
+        }
+        lyrics
+      },
+      comments: tag
+        .comments()
+        .map(|c| CommentEditableTag::new(Some(c.clone())))
+        .collect(),
+      frames: tag.frames().map(RawFrameEditableTag::new).collect(),
+      pictures: read_pictures(&tag),
+      pictures_loaded: true,
+      other_synced: tag
+        .synchronised_lyrics()
+        .filter(|s| s.timestamp_format != TimestampFormat::Ms)
+        .map(|s| (s.lang.clone(), s.content_type))
+        .collect(),
+      id3v1,
     }
   }
-  pub fn edited(&self) -> bool {
+  /// Reads the pictures of tags taken from the library cache from the file.
+  pub fn load_pictures(&mut self) {
+    if self.pictures_loaded {
+      return;
+    }
+    self.pictures_loaded = true;
+    // whatever was decoded of a damaged tag is shown
+    let tag = Tag::read_from_path(&self.song_path).unwrap_or_else(|e| {
+      e.partial_tag.unwrap_or_default()
+    });
+    self.pictures = read_pictures(&tag);
+  }
+  /// The tags to keep in the library cache, without pictures.
+  pub fn without_pictures(&self) -> Self {
+    Self { pictures: Vec::new(), pictures_loaded: false, ..self.clone() }
+  }
+  pub fn edited(&self) -> bool {
     self.title.0.edited() ||
       self.artist.0.edited() ||
-      self.year.0.edited() ||
+      self.date.0.edited() ||
+      self.original_date.0.edited() ||
+      self.release_date.0.edited() ||
       self.genre.0.edited() ||
-      self.lyrics.lang.edited() ||
-      self.lyrics.desc.edited() ||
-      self.lyrics.text.edited()
+      self.album.0.edited() ||
+      self.album_artist.0.edited() ||
+      self.track.0.edited() ||
+      self.disc.0.edited() ||
+      self.lyrics_edited() ||
+      self.comments_edited() ||
+      self.frames_edited() ||
+      self.pictures_edited() ||
+      self.id3v1.edited() ||
+      self.write_options.edited()
   }
-  pub fn save(&mut self) -> Result<(), String> {
-    let mut tags = Tag::new();
-    match &self.title.0.state {
-      EditableState::Unchanged => {
-        if let Some(t) = &self.title.0.original {
-          tags.set_title(t);
+  /// Fields of the fixed editor with their names.
+  fn fields(&self) -> [(&'static str, &EditableTag); 10] {
+    [
+      ("Title", &self.title),
+      ("Artist", &self.artist),
+      ("Date", &self.date),
+      ("Original date", &self.original_date),
+      ("Release date", &self.release_date),
+      ("Genre", &self.genre),
+      ("Album", &self.album),
+      ("Album artist", &self.album_artist),
+      ("Track", &self.track),
+      ("Disc", &self.disc),
+    ]
+  }
+  fn fields_mut(&mut self) -> [&mut EditableTag; 10] {
+    [
+      &mut self.title,
+      &mut self.artist,
+      &mut self.date,
+      &mut self.original_date,
+      &mut self.release_date,
+      &mut self.genre,
+      &mut self.album,
+      &mut self.album_artist,
+      &mut self.track,
+      &mut self.disc,
+    ]
+  }
+  /// Every edited field in the order of the editor, frames are named by their position.
+  pub fn changes(&self) -> Vec<Change> {
+    self.compared()
+      .into_iter()
+      .filter(|c| c.original != c.current)
+      .collect()
+  }
+  /// Every field with its value read from the file and its current value.
+  fn compared(&self) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let mut push = |change: Change| changes.push(change);
+    for (name, tag) in self.fields() {
+      push(Change::of(name, &tag.0));
+    }
+    for (j, lyrics) in self.lyrics.iter().enumerate() {
+      let name = format!("Lyrics #{}", j + 1);
+      push(Change::of(format!("{} language", name), &lyrics.lang));
+      push(Change::of(format!("{} description", name), &lyrics.desc));
+      push(Change::of(name.clone(), &lyrics.text));
+      push(Change {
+        field: format!("{} synced", name),
+        original: lyrics.synced.original.as_ref().map(|c| lrc::format(c)),
+        current: lyrics.synced.current().map(|c| lrc::format(c)),
+      });
+    }
+    for (j, comment) in self.comments.iter().enumerate() {
+      let name = format!("Comment #{}", j + 1);
+      push(Change::of(format!("{} language", name), &comment.lang));
+      push(Change::of(format!("{} description", name), &comment.desc));
+      push(Change::of(name, &comment.text));
+    }
+    for frame in &self.frames {
+      push(Change::of(FRAME_ID, &frame.id));
+      push(Change::of(format!("{} description", frame.id), &frame.desc));
+      push(Change::of(format!("{} frame", frame.id), &frame.value));
+    }
+    let describe = |p: &Arc<Picture>| {
+      let size = PictureEditableTag::dimensions(p)
+        .map(|(w, h)| format!(", {}x{}", w, h))
+        .unwrap_or_default();
+      format!("{} ({}{})", p.picture_type, p.mime_type, size)
+    };
+    for picture in &self.pictures {
+      let original = picture.0.original.as_ref().map(describe);
+      let mut current = picture.0.current().map(describe);
+      // a replaced image may look the same
+      if picture.0.edited() && current == original {
+        current = current.map(|c| format!("{}, new image", c));
+      }
+      push(Change { field: "Picture".into(), original, current });
+    }
+    for f in Id3v1Field::ALL {
+      push(Change::of(format!("ID3v1 {:?}", f), self.id3v1.field(f)));
+    }
+    let options = |o: Option<&WriteOptions>| {
+      Some(o.map_or("Global".into(), |o| format!("{}, {}", o.version, o.encoding)))
+    };
+    push(Change {
+      field: "Write options".into(),
+      original: options(self.write_options.original.as_ref()),
+      current: options(self.write_options.current()),
+    });
+    changes
+  }
+  /// Fields something else changed on disk since the tags were read, `disk` being the tags read
+  /// again, with the ones edited here to another value.
+  pub fn merge(&self, disk: &SongTags) -> (Vec<Change>, Vec<Conflict>) {
+    // fields of the same name, like pictures, are told apart by their order
+    let keyed = |changes: Vec<Change>| {
+      let mut keyed: Vec<((String, usize), Change)> = Vec::new();
+      for change in changes {
+        let n = keyed.iter().filter(|((f, _), _)| *f == change.field).count();
+        keyed.push(((change.field.clone(), n), change));
+      }
+      keyed
+    };
+    let mine = keyed(self.compared());
+    let mut theirs = disk.compared();
+    // pictures not read yet are taken as the ones on disk
+    if !self.pictures_loaded {
+      theirs.retain(|c| c.field != "Picture");
+    }
+    let theirs = keyed(theirs);
+    let mut external = Vec::new();
+    let mut conflicts = Vec::new();
+    for (key, change) in &mine {
+      let on_disk = theirs
+        .iter()
+        .find(|(k, _)| k == key)
+        .and_then(|(_, c)| c.original.clone());
+      // frames are matched by their ID, the fields also show in the list of frames
+      let shown = external
+        .iter()
+        .any(|c: &Change| c.original == change.original && c.current == on_disk);
+      if change.field == FRAME_ID || on_disk == change.original || shown {
+        continue;
+      }
+      if change.current != change.original && change.current != on_disk {
+        conflicts.push(Conflict {
+          field: change.field.clone(),
+          original: change.original.clone(),
+          mine: change.current.clone(),
+          theirs: on_disk.clone(),
+        });
+      }
+      external.push(Change {
+        field: change.field.clone(),
+        original: change.original.clone(),
+        current: on_disk,
+      });
+    }
+    for (key, change) in theirs {
+      if change.original.is_some() && !mine.iter().any(|(k, _)| *k == key) {
+        external.push(Change { field: change.field, original: None, current: change.original });
+      }
+    }
+    (external, conflicts)
+  }
+  /// The edits made here applied to `disk`, the tags read again after something else changed the
+  /// file. Fields changed on both sides take the value edited here.
+  pub fn rebase(&self, mut disk: SongTags) -> SongTags {
+    for ((_, mine), theirs) in self.fields().into_iter().zip(disk.fields_mut()) {
+      theirs.0.apply(&mine.0);
+    }
+    rebase_entries(&self.lyrics, &mut disk.lyrics);
+    rebase_entries(&self.comments, &mut disk.comments);
+    rebase_entries(&self.frames, &mut disk.frames);
+    rebase_entries(&self.pictures, &mut disk.pictures);
+    for f in Id3v1Field::ALL {
+      disk.id3v1.field_mut(f).apply(self.id3v1.field(f));
+    }
+    disk.write_options.apply(&self.write_options);
+    disk
+  }
+  /// Whether something else changed the file since its tags were read.
+  pub fn changed_on_disk(&self) -> bool {
+    Fingerprint::of(&self.song_path).ok() != self.disk
+  }
+  /// Takes the file as it is now as the one the edits were made to, the next save overwrites
+  /// whatever changed on disk.
+  pub fn accept_disk(&mut self) {
+    self.disk = Fingerprint::of(&self.song_path).ok();
+  }
+  /// Drops every edit, frames added in the editor are removed.
+  pub fn reset(&mut self) {
+    for tag in self.fields_mut() {
+      tag.0.reset();
+    }
+    self.reset_lyrics();
+    self.reset_comments();
+    self.reset_frames();
+    self.reset_pictures();
+    self.id3v1.reset();
+    self.write_options.reset();
+  }
+  /// Current ID3v2 value of a field an ID3v1 tag has, the comment without a description.
+  pub fn id3v2_value(&self, f: Id3v1Field) -> Option<String> {
+    match f {
+      Id3v1Field::Title => self.title.0.current().cloned(),
+      Id3v1Field::Artist => self.artist.0.current().cloned(),
+      Id3v1Field::Album => self.album.0.current().cloned(),
+      Id3v1Field::Year => self.date.0.current().map(|d| d.chars().take(4).collect()),
+      Id3v1Field::Comment =>
+        self.comments
+          .iter()
+          .find(|c| c.desc.to_string().is_empty())
+          .and_then(|c| c.text.current().cloned()),
+      Id3v1Field::Track =>
+        self.track.0
+          .current()
+          .and_then(|t| parse_number_pair(t))
+          .map(|(n, _)| n.to_string()),
+      // ID3v1 has room for one genre
+      Id3v1Field::Genre => self.genre.0.current().and_then(|g| genre::split(g).into_iter().next()),
+    }
+  }
+  /// Whether the ID3v2 value does not fit into the ID3v1 field and is cut or dropped on copy.
+  pub fn id3v1_truncated(&self, f: Id3v1Field) -> bool {
+    self.id3v2_value(f).is_some_and(|v| !f.fit(&v).is_some_and(|fit| fit.eq_ignore_ascii_case(&v)))
+  }
+  /// Whether both tags have a value for the field and the ID3v1 one differs from what the ID3v2
+  /// value would be in an ID3v1 tag.
+  pub fn id3v1_conflict(&self, f: Id3v1Field) -> bool {
+    match (self.id3v1.field(f).current(), self.id3v2_value(f)) {
+      (Some(v1), Some(v2)) => !f.fit(&v2).is_some_and(|v2| v2.eq_ignore_ascii_case(v1)),
+      _ => false,
+    }
+  }
+  /// Sets every ID3v1 field from ID3v2, cutting values to the field length.
+  pub fn copy_to_id3v1(&mut self) {
+    for f in Id3v1Field::ALL {
+      let value = self.id3v2_value(f).and_then(|v| f.fit(&v));
+      self.id3v1.field_mut(f).set(value.filter(|v| !v.is_empty()));
+    }
+  }
+  /// Sets the ID3v2 fields the ID3v1 tag has a value for.
+  pub fn copy_from_id3v1(&mut self) {
+    for f in Id3v1Field::ALL {
+      let Some(value) = self.id3v1.field(f).current().cloned() else {
+        continue;
+      };
+      match f {
+        Id3v1Field::Title => self.title.0.edit(value),
+        Id3v1Field::Artist => self.artist.0.edit(value),
+        Id3v1Field::Album => self.album.0.edit(value),
+        Id3v1Field::Year => self.date.0.edit(value),
+        Id3v1Field::Genre => self.genre.0.edit(value),
+        Id3v1Field::Track => {
+          // the ID3v1 tag has no total
+          let total = self.track.0.current().and_then(|t| parse_number_pair(t)?.1);
+          self.track.0.edit(match total {
+            Some(total) => format!("{}/{}", value, total),
+            None => value,
+          });
+        }
+        Id3v1Field::Comment => {
+          // a new comment gets no description as no other one lacks it
+          let j = self.comments
+            .iter()
+            .position(|c| c.desc.to_string().is_empty())
+            .unwrap_or_else(|| self.add_comment());
+          self.comments[j].text.edit(value);
+        }
+      }
+    }
+  }
+  /// Version the tag will be written with.
+  pub fn write_version(&self, defaults: &WriteOptions) -> Version {
+    match self.write_options.current().unwrap_or(defaults).version {
+      VersionSetting::Keep =>
+        match self.version {
+          Some(Version::Id3v23) => Version::Id3v23,
+          _ => Version::Id3v24,
         }
+      VersionSetting::Id3v23 => Version::Id3v23,
+      VersionSetting::Id3v24 => Version::Id3v24,
+    }
+  }
+  pub fn lyrics_edited(&self) -> bool {
+    self.lyrics.iter().any(|l| l.edited())
+  }
+  /// Whether another lyrics frame that will be written uses the same language and description,
+  /// or the same language for synced lyrics of the same type.
+  pub fn lyrics_conflict(&self, j: usize) -> bool {
+    self.lyrics_error(j).is_some()
+  }
+  /// Why the lyrics `j` cannot be written next to the other frames: USLT frames are told apart by
+  /// their language and description, SYLT frames by their language and type.
+  pub fn lyrics_error(&self, j: usize) -> Option<&'static str> {
+    let synced = |l: &LyricsEditableTag| l.synced.current().is_some();
+    let written = |l: &LyricsEditableTag| l.text.current().is_some() || synced(l);
+    let lyrics = &self.lyrics[j];
+    let others = || self.lyrics.iter().enumerate().filter(move |(k, _)| *k != j).map(|(_, l)| l);
+    if written(lyrics) && others().any(|l| written(l) && l.key() == lyrics.key()) {
+      return Some(DUPLICATE_KEY);
+    }
+    // the SYLT frames read from the tag only clash once they are written again
+    let key = lyrics.synced_key()?;
+    let clash = others().any(|l| l.synced_key().as_ref() == Some(&key)) ||
+      self.other_synced.contains(&key);
+    (clash && self.synced_rewritten()).then_some(DUPLICATE_SYNCED)
+  }
+  /// Whether saving writes every SYLT frame again, see `save`.
+  fn synced_rewritten(&self) -> bool {
+    self.lyrics
+      .iter()
+      .any(|l| l.edited() && (l.synced.original.is_some() || l.synced.current().is_some()))
+  }
+  /// Adds an empty English lyrics frame with a description no other frame uses.
+  pub fn add_lyrics(&mut self) -> usize {
+    let mut lyrics = LyricsEditableTag::new(None);
+    lyrics.lang.edit("eng".into());
+    let mut n = 1;
+    while self.lyrics.iter().any(|l| l.key() == lyrics.key()) {
+      n += 1;
+      lyrics.desc.edit(format!("#{}", n));
+    }
+    self.lyrics.push(lyrics);
+    self.lyrics.len() - 1
+  }
+  /// Marks the frame as removed, restores it when already removed and drops it entirely when it
+  /// was only added in the editor.
+  pub fn remove_lyrics(&mut self, j: usize) {
+    let lyrics = &mut self.lyrics[j];
+    if !lyrics.read_from_tag() {
+      self.lyrics.remove(j);
+    } else if lyrics.removed() {
+      lyrics.reset();
+    } else {
+      lyrics.text.set(None);
+      lyrics.synced.set(None);
+    }
+  }
+  pub fn reset_lyrics(&mut self) {
+    self.lyrics.retain(|l| l.read_from_tag());
+    for lyrics in self.lyrics.iter_mut() {
+      lyrics.reset();
+    }
+  }
+  pub fn comments_edited(&self) -> bool {
+    self.comments.iter().any(|c| c.edited())
+  }
+  /// Whether another comment that will be written uses the same language and description.
+  pub fn comment_conflict(&self, j: usize) -> bool {
+    let written = |c: &CommentEditableTag| c.text.current().is_some();
+    written(&self.comments[j]) &&
+      self.comments
+        .iter()
+        .enumerate()
+        .any(|(k, c)| k != j && written(c) && c.key() == self.comments[j].key())
+  }
+  /// Adds an empty English comment with a description no other comment uses.
+  pub fn add_comment(&mut self) -> usize {
+    let mut comment = CommentEditableTag::new(None);
+    comment.lang.edit("eng".into());
+    let mut n = 1;
+    while self.comments.iter().any(|c| c.key() == comment.key()) {
+      n += 1;
+      comment.desc.edit(format!("#{}", n));
+    }
+    self.comments.push(comment);
+    self.comments.len() - 1
+  }
+  /// Marks the comment as removed, restores it when already removed and drops it entirely when it
+  /// was only added in the editor.
+  pub fn remove_comment(&mut self, j: usize) {
+    let comment = &mut self.comments[j];
+    if comment.text.original.is_none() {
+      self.comments.remove(j);
+    } else if comment.removed() {
+      comment.reset();
+    } else {
+      comment.text.set(None);
+    }
+  }
+  pub fn reset_comments(&mut self) {
+    self.comments.retain(|c| c.text.original.is_some());
+    for comment in self.comments.iter_mut() {
+      comment.reset();
+    }
+  }
+  pub fn frames_edited(&self) -> bool {
+    self.frames.iter().any(|f| f.edited())
+  }
+  /// Whether the frame has an invalid ID or another written frame has the same ID, or the same
+  /// description for TXXX frames.
+  pub fn frame_conflict(&self, j: usize) -> bool {
+    let frame = &self.frames[j];
+    let key = |f: &RawFrameEditableTag| (f.id.to_string(), f.is_txxx().then(|| f.desc.to_string()));
+    !frame.valid_id() ||
+      (frame.value.current().is_some() &&
+        frame.edited() &&
+        self.frames
+          .iter()
+          .enumerate()
+          .any(|(k, f)| k != j && f.value.current().is_some() && key(f) == key(frame)))
+  }
+  /// Adds a frame with the given ID, "TXXX" adds a user-defined text frame.
+  pub fn add_frame(&mut self, id: &str) -> usize {
+    self.frames.push(RawFrameEditableTag::added(id));
+    self.frames.len() - 1
+  }
+  /// Marks the frame as removed, restores it when already removed and drops it entirely when it
+  /// was only added in the editor. Frames of the fixed editor are left alone.
+  pub fn remove_frame(&mut self, j: usize) {
+    let frame = &mut self.frames[j];
+    if frame.is_new() {
+      self.frames.remove(j);
+    } else if frame.managed() {
+    } else if frame.removed() {
+      frame.value.reset();
+    } else {
+      frame.value.set(None);
+    }
+  }
+  pub fn reset_frames(&mut self) {
+    self.frames.retain(|f| !f.is_new());
+    for frame in self.frames.iter_mut() {
+      frame.id.reset();
+      frame.desc.reset();
+      frame.value.reset();
+    }
+  }
+  /// Reads synced lyrics from an LRC file into the frame, a new frame is added when `j` is `None`.
+  pub fn import_lrc(&mut self, j: Option<usize>, path: &str) -> Result<usize, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let content = lrc::parse(text.trim_start_matches('\u{feff}')).map_err(|n| {
+      format!("Line {} of {} has no timestamp", n, path)
+    })?;
+    if content.is_empty() {
+      return Err(format!("{} contains no timed lines", path));
+    }
+    let j = j.unwrap_or_else(|| self.add_lyrics());
+    self.lyrics[j].synced.set(Some(content));
+    Ok(j)
+  }
+  /// Writes the synced lyrics of the frame to an LRC file along with title, artist and album.
+  pub fn export_lrc(&self, j: usize, path: &str) -> Result<(), String> {
+    let content = self.lyrics[j].synced.current().ok_or("The frame has no synced lyrics")?;
+    let mut lrc = String::new();
+    for (key, editable) in [
+      ("ti", &self.title),
+      ("ar", &self.artist),
+      ("al", &self.album),
+    ] {
+      if let Some(value) = editable.0.current() {
+        lrc += &format!("[{}:{}]\n", key, value);
+      }
+    }
+    lrc += &lrc::format(content);
+    lrc += "\n";
+    fs::write(path, lrc).map_err(|e| e.to_string())
+  }
+  pub fn pictures_edited(&self) -> bool {
+    self.pictures.iter().any(|p| p.0.edited())
+  }
+  /// First picture type from `preferred` and then the standard list not used by other pictures.
+  fn free_picture_type(&self, preferred: &[PictureType], except: Option<usize>) -> PictureType {
+    let used = |t: &PictureType| {
+      self.pictures
+        .iter()
+        .enumerate()
+        .any(|(j, p)| Some(j) != except && p.0.current().is_some_and(|p| p.picture_type == *t))
+    };
+    preferred
+      .iter()
+      .chain(PICTURE_TYPES.iter())
+      .find(|t| !used(t))
+      .copied()
+      .unwrap_or(PictureType::Other)
+  }
+  /// Adds an image from `path`, or replaces the picture at `replace` keeping its type.
+  pub fn import_picture(&mut self, path: &str, replace: Option<usize>) -> Result<(), String> {
+    match replace {
+      Some(j) => {
+        let picture_type = self.pictures[j].0
+          .current()
+          .or(self.pictures[j].0.original.as_ref())
+          .map(|p| p.picture_type)
+          .unwrap_or(PictureType::CoverFront);
+        let picture = PictureEditableTag::read_image(path, picture_type)?;
+        self.pictures[j].0.set(Some(Arc::new(picture)));
+      }
+      None => {
+        let picture_type = self.free_picture_type(
+          &[PictureType::CoverFront, PictureType::CoverBack],
+          None
+        );
+        let mut picture = PictureEditableTag(Editable::new(None));
+        picture.0.set(Some(Arc::new(PictureEditableTag::read_image(path, picture_type)?)));
+        self.pictures.push(picture);
       }
+    }
+    Ok(())
+  }
+  pub fn export_picture(&self, j: usize, path: &str) -> Result<(), String> {
+    let picture = self.pictures[j].0.current().ok_or("Picture is removed")?;
+    fs::write(path, &picture.data).map_err(|e| e.to_string())
+  }
+  /// Marks the picture as removed, restores it when already removed and drops it entirely when
+  /// it was only added in the editor.
+  pub fn remove_picture(&mut self, j: usize) {
+    let picture = &mut self.pictures[j].0;
+    if picture.original.is_none() {
+      self.pictures.remove(j);
+    } else if let EditableState::Removed = picture.state {
+      picture.reset();
+    } else {
+      picture.set(None);
+    }
+  }
+  /// Switches the picture to the next type no other picture uses.
+  pub fn cycle_picture_type(&mut self, j: usize) {
+    let Some(current) = self.pictures[j].0.current().cloned() else {
+      return;
+    };
+    let i = PICTURE_TYPES.iter().position(|t| *t == current.picture_type).unwrap_or(0);
+    let preferred = PICTURE_TYPES[i + 1..].iter().copied().collect::<Vec<_>>();
+    let picture_type = self.free_picture_type(&preferred, Some(j));
+    self.pictures[j].0.set(Some(Arc::new(Picture { picture_type, ..(*current).clone() })));
+  }
+  pub fn reset_pictures(&mut self) {
+    self.pictures.retain(|p| p.0.original.is_some());
+    for picture in self.pictures.iter_mut() {
+      picture.0.reset();
+    }
+  }
+  /// Reasons the edits cannot be saved, one line per invalid field.
+  pub fn errors(&self) -> Vec<String> {
+    let mut errors = Vec::new();
+    let mut check = |name: &str, error: Option<String>| {
+      if let Some(error) = error {
+        errors.push(format!("{}: {}", name, error));
+      }
+    };
+    for (name, tag) in self.fields() {
+      check(name, tag.0.error());
+    }
+    for (j, lyrics) in self.lyrics.iter().enumerate() {
+      let name = format!("Lyrics #{}", j + 1);
+      check(&name, lyrics.lang.error());
+      check(&name, self.lyrics_error(j).map(String::from));
+    }
+    for (j, comment) in self.comments.iter().enumerate() {
+      let name = format!("Comment #{}", j + 1);
+      check(&name, comment.lang.error());
+      check(&name, self.comment_conflict(j).then(|| DUPLICATE_KEY.into()));
+    }
+    for (j, frame) in self.frames.iter().enumerate() {
+      let name = format!("{} frame", frame.id);
+      match frame.id_error() {
+        Some(error) => check(&name, Some(error)),
+        None => check(&name, self.frame_conflict(j).then(|| DUPLICATE_FRAME.into())),
+      }
+      check(&name, frame.value.error());
+    }
+    errors
+  }
+  pub fn valid(&self) -> bool {
+    self.errors().is_empty()
+  }
+  pub fn path(&self) -> &str {
+    &self.song_path
+  }
+  /// Applies only the edited fields on top of the tag already on disk so that every other frame
+  /// (artwork, track numbers, comments, TXXX, ...) is written back untouched. The tag is written
+  /// with the file's write options, falling back to the global ones, then the ID3v1 tag is
+  /// updated, written or stripped. The file is replaced at once, see [`atomic::replace`].
+  pub fn save(
+    &mut self,
+    settings: &Settings,
+    id3v1_action: Id3v1Action,
+    backups: Option<&Backups>
+  ) -> Result<(), TagError> {
+    let defaults = &settings.write_options;
+    let errors = self.errors();
+    if !errors.is_empty() {
+      return Err(TagError::Invalid(errors));
+    }
+    if self.changed_on_disk() {
+      return Err(TagError::ChangedOnDisk(self.song_path.clone()));
+    }
+    let mut tags = match Tag::read_from_path(&self.song_path) {
+      Ok(tags) => tags,
+      Err(Error { kind: ErrorKind::NoTag, .. }) => Tag::new(),
+      Err(e) => {
+        return Err(TagError::from_id3(e, &self.song_path));
+      }
+    };
+    match &self.title.0.state {
+      EditableState::Unchanged => {}
       EditableState::Changed(name) => {
         tags.set_title(name);
       }
@@ -136,11 +1759,7 @@ impl SongTags {
       }
     }
     match &self.artist.0.state {
-      EditableState::Unchanged => {
-        if let Some(a) = &self.artist.0.original {
-          tags.set_artist(a);
-        }
-      }
+      EditableState::Unchanged => {}
       EditableState::Changed(artist) => {
         tags.set_artist(artist);
       }
@@ -148,60 +1767,997 @@ impl SongTags {
         tags.remove_artist();
       }
     }
-    match &self.year.0.state {
-      EditableState::Unchanged => {
-        if let Some(year) = &self.year.0.original {
-          tags.set_year(year.parse().unwrap_or(0));
+    let version = self.write_version(defaults);
+    // dates move to the frames of the version they are written with
+    let converted = self.version.is_some_and(|v| v != version);
+    for (frame, editable) in [
+      (DateFrame::Recorded, &self.date),
+      (DateFrame::Original, &self.original_date),
+      (DateFrame::Released, &self.release_date),
+    ] {
+      let id = frame.ids()[0];
+      match (&editable.0.state, editable.0.current().map(|d| parse_timestamp(d))) {
+        (EditableState::Unchanged, _) if !converted => {}
+        // dates read from the file that are not timestamps are left alone
+        (EditableState::Unchanged, Some(None)) => {}
+        (EditableState::Changed(v), Some(None)) => {
+          return Err(
+            TagError::Invalid(
+              vec![format!("Invalid {} value \"{}\", expected \"yyyy-MM-ddTHH:mm:ss\"", id, v)]
+            )
+          );
         }
+        (_, date) => frame.write(&mut tags, date.flatten().as_ref(), version),
       }
-      EditableState::Changed(year) => {
-        tags.set_year(year.parse().unwrap_or(0));
+    }
+    match &self.genre.0.state {
+      EditableState::Unchanged => {}
+      EditableState::Changed(genres) => {
+        let genres = genre::split(genres);
+        tags.remove_genre();
+        if !genres.is_empty() {
+          tags.set_text_values("TCON", genres);
+        }
       }
       EditableState::Removed => {
-        tags.remove_year();
+        tags.remove_genre();
       }
     }
-    match &self.genre.0.state {
-      EditableState::Unchanged => {
-        tags.set_genre(self.genre.0.original.clone().unwrap_or_default());
+    match &self.album.0.state {
+      EditableState::Unchanged => {}
+      EditableState::Changed(album) => {
+        tags.set_album(album);
       }
-      EditableState::Changed(genre) => {
-        tags.set_genre(genre);
+      EditableState::Removed => {
+        tags.remove_album();
+      }
+    }
+    match &self.album_artist.0.state {
+      EditableState::Unchanged => {}
+      EditableState::Changed(album_artist) => {
+        tags.set_album_artist(album_artist);
       }
       EditableState::Removed => {
-        tags.remove_genre();
+        tags.remove_album_artist();
       }
     }
-    tags.add_lyrics(Lyrics {
-      lang: {
-        match &self.lyrics.lang.state {
-          EditableState::Changed(lang) => lang.clone(),
-          EditableState::Unchanged => self.lyrics.lang.original.clone().unwrap_or_default(),
-          EditableState::Removed => String::default(),
+    for (id, editable) in [
+      ("TRCK", &self.track),
+      ("TPOS", &self.disc),
+    ] {
+      match &editable.0.state {
+        EditableState::Unchanged => {}
+        EditableState::Changed(v) => {
+          match parse_number_pair(v) {
+            Some((n, Some(total))) => tags.set_text(id, format!("{}/{}", n, total)),
+            Some((n, None)) => tags.set_text(id, n.to_string()),
+            None => {
+              return Err(
+                TagError::Invalid(
+                  vec![format!("Invalid {} value \"{}\", expected \"n\" or \"n/total\"", id, v)]
+                )
+              );
+            }
+          }
         }
-      },
-      description: {
-        match &self.lyrics.desc.state {
-          EditableState::Changed(desc) => desc.clone(),
-          EditableState::Unchanged => self.lyrics.desc.original.clone().unwrap_or_default(),
-          EditableState::Removed => String::default(),
+        EditableState::Removed => {
+          tags.remove(id);
         }
-      },
-      text: {
-        match &self.lyrics.text.state {
-          EditableState::Changed(text) => text.clone(),
-          EditableState::Unchanged => self.lyrics.text.original.clone().unwrap_or_default(),
-          EditableState::Removed => String::default(),
+      }
+    }
+    if self.lyrics_edited() {
+      let edited = self.lyrics
+        .iter()
+        .filter(|l| l.edited())
+        .collect::<Vec<_>>();
+      let other_lyrics = tags
+        .remove("USLT")
+        .into_iter()
+        .filter(|f| !f.content().lyrics().is_some_and(|l| edited.iter().any(|e| e.is_original(l))))
+        .collect::<Vec<_>>();
+      for frame in other_lyrics {
+        tags.add_frame(frame);
+      }
+      for lyrics in edited.iter() {
+        if let Some(text) = lyrics.text.current() {
+          tags.add_frame(Lyrics {
+            lang: lyrics.lang.to_string(),
+            description: lyrics.desc.to_string(),
+            text: text.clone(),
+          });
+        }
+      }
+      // SYLT frames are only rewritten when needed, id3 considers frames with the same language
+      // and type duplicates even when their descriptions differ
+      if edited.iter().any(|l| l.synced.original.is_some() || l.synced.current().is_some()) {
+        let other_synced = tags
+          .remove("SYLT")
+          .into_iter()
+          .filter(|f| {
+            !f
+              .content()
+              .synchronised_lyrics()
+              .is_some_and(|s| edited.iter().any(|e| e.is_original_synced(s)))
+          })
+          .collect::<Vec<_>>();
+        for frame in other_synced {
+          tags.add_frame(frame);
+        }
+        for lyrics in edited {
+          if let Some(content) = lyrics.synced.current() {
+            tags.add_frame(SynchronisedLyrics {
+              lang: lyrics.lang.to_string(),
+              timestamp_format: TimestampFormat::Ms,
+              content_type: lyrics.synced_key().map_or(SynchronisedLyricsType::Lyrics, |k| k.1),
+              description: lyrics.desc.to_string(),
+              content: content.clone(),
+            });
+          }
+        }
+      }
+    }
+    if self.comments_edited() {
+      let edited = self.comments
+        .iter()
+        .filter(|c| c.edited())
+        .collect::<Vec<_>>();
+      let other_comments = tags
+        .remove("COMM")
+        .into_iter()
+        .filter(|f| !f.content().comment().is_some_and(|c| edited.iter().any(|e| e.is_original(c))))
+        .collect::<Vec<_>>();
+      for frame in other_comments {
+        tags.add_frame(frame);
+      }
+      for comment in edited {
+        if let Some(text) = comment.text.current() {
+          tags.add_frame(Comment {
+            lang: comment.lang.to_string(),
+            description: comment.desc.to_string(),
+            text: text.clone(),
+          });
+        }
+      }
+    }
+    for frame in self.frames.iter().filter(|f| f.edited()) {
+      if let Some(original) = &frame.original {
+        let others = tags
+          .remove(&original.id)
+          .into_iter()
+          .filter(|f| content_hash(f) != original.hash)
+          .collect::<Vec<_>>();
+        for other in others {
+          tags.add_frame(other);
         }
-      },
-    });
-    let write_res = tags.write_to_path(self.song_path.clone(), id3::Version::Id3v24);
-    match write_res {
-      Ok(_) => {
-        *self = Self::new(self.song_path.clone());
-        Ok(())
       }
-      Err(e) => { Err(e.description) }
+      if let Some(new) = frame.to_frame() {
+        tags.add_frame(new);
+      }
+    }
+    if self.pictures_edited() {
+      let replaced = self.pictures
+        .iter()
+        .filter(|p| p.0.edited())
+        .filter_map(|p| p.0.original.as_deref())
+        .collect::<Vec<_>>();
+      let other_pictures = tags
+        .remove("APIC")
+        .into_iter()
+        .filter(|f| !f.content().picture().is_some_and(|p| replaced.contains(&p)))
+        .collect::<Vec<_>>();
+      for frame in other_pictures {
+        tags.add_frame(frame);
+      }
+      for picture in self.pictures.iter().filter(|p| p.0.edited()) {
+        if let Some(picture) = picture.0.current() {
+          tags.add_frame((**picture).clone());
+        }
+      }
+    }
+    let encoding = self.write_options.current().unwrap_or(defaults).encoding;
+    let mut written = Tag::with_version(version);
+    for frame in tags.frames() {
+      let frame_encoding = encoding.resolve(version, frame.encoding());
+      written.add_frame(frame.clone().set_encoding(frame_encoding));
+    }
+    // the tags on disk are kept before they are overwritten
+    if let Some(backups) = backups {
+      backups.store(&self.song_path).map_err(|e| TagError::from_io(e, &self.song_path))?;
+    }
+    atomic::replace(&self.song_path, settings.keep_times, |temp| {
+      written
+        .write_to_path(temp, version)
+        .and_then(|_| {
+          match id3v1_action {
+            Id3v1Action::Strip => v1::Tag::remove_from_path(temp).map(|_| ()),
+            Id3v1Action::Update if !self.id3v1.present && !self.id3v1.edited() => Ok(()),
+            _ => id3v1::write_to_path(temp, &self.id3v1.to_tag()),
+          }
+        })
+        .map_err(|e| TagError::from_id3(e, &self.song_path))
+    })?;
+    let write_options = self.write_options.current().copied();
+    *self = Self::new(self.song_path.clone());
+    self.write_options = Editable::new(write_options);
+    Ok(())
+  }
+}
+
+fn read_pictures(tag: &Tag) -> Vec<PictureEditableTag> {
+  tag
+    .pictures()
+    .map(|p| PictureEditableTag(Editable::new(Some(Arc::new(p.clone())))))
+    .collect()
+}
+
+/// Tag versions and synced lyrics types as stored in the library cache, by their position.
+const VERSIONS: [Version; 3] = [Version::Id3v22, Version::Id3v23, Version::Id3v24];
+const SYNCED_TYPES: [SynchronisedLyricsType; 7] = [
+  SynchronisedLyricsType::Other,
+  SynchronisedLyricsType::Lyrics,
+  SynchronisedLyricsType::Transcription,
+  SynchronisedLyricsType::PartName,
+  SynchronisedLyricsType::Event,
+  SynchronisedLyricsType::Chord,
+  SynchronisedLyricsType::Trivia,
+];
+
+fn position<T: PartialEq>(all: &[T], value: T) -> u8 {
+  all.iter().position(|v| *v == value).unwrap_or_default() as u8
+}
+
+fn at<T: Copy>(all: &[T], position: u8) -> io::Result<T> {
+  all.get(position as usize).copied().ok_or(io::ErrorKind::InvalidData.into())
+}
+
+/// Only the values read from the file are cached, the tags are cached right after being read.
+impl Encode for SongTags {
+  fn encode(&self, data: &mut Vec<u8>) {
+    self.song_path.encode(data);
+    self.version.map(|v| position(&VERSIONS, v)).encode(data);
+    self.disk.encode(data);
+    for (_, tag) in self.fields() {
+      tag.0.original.encode(data);
+    }
+    self.lyrics.encode(data);
+    self.comments.encode(data);
+    self.frames.encode(data);
+    self.id3v1.encode(data);
+    self.other_synced
+      .iter()
+      .map(|(lang, t)| (lang.clone(), position(&SYNCED_TYPES, *t)))
+      .collect::<Vec<_>>()
+      .encode(data);
+  }
+  fn decode(reader: &mut &[u8]) -> io::Result<Self> {
+    let song_path = String::decode(reader)?;
+    let version = Option::<u8>::decode(reader)?;
+    let disk = Option::decode(reader)?;
+    let mut tags = Self::read(song_path, Err(Error::new(ErrorKind::NoTag, "")), None, disk);
+    tags.version = version.map(|v| at(&VERSIONS, v)).transpose()?;
+    for tag in tags.fields_mut() {
+      tag.0.original = Option::decode(reader)?;
+    }
+    tags.lyrics = Vec::decode(reader)?;
+    tags.comments = Vec::decode(reader)?;
+    tags.frames = Vec::decode(reader)?;
+    tags.id3v1 = Id3v1EditableTag::decode(reader)?;
+    tags.other_synced = Vec::<(String, u8)>::decode(reader)?
+      .into_iter()
+      .map(|(lang, t)| Ok((lang, at(&SYNCED_TYPES, t)?)))
+      .collect::<io::Result<_>>()?;
+    tags.pictures_loaded = false;
+    Ok(tags)
+  }
+}
+
+impl Encode for LyricsEditableTag {
+  fn encode(&self, data: &mut Vec<u8>) {
+    self.lang.original.encode(data);
+    self.desc.original.encode(data);
+    self.text.original.encode(data);
+    self.synced.original.encode(data);
+    self.synced_type.map(|t| position(&SYNCED_TYPES, t)).encode(data);
+  }
+  fn decode(reader: &mut &[u8]) -> io::Result<Self> {
+    let mut lyrics = Self::new(None);
+    lyrics.lang.original = Option::decode(reader)?;
+    lyrics.desc.original = Option::decode(reader)?;
+    lyrics.text.original = Option::decode(reader)?;
+    lyrics.synced.original = Option::decode(reader)?;
+    lyrics.synced_type = Option::<u8>::decode(reader)?
+      .map(|t| at(&SYNCED_TYPES, t))
+      .transpose()?;
+    Ok(lyrics)
+  }
+}
+
+impl Encode for CommentEditableTag {
+  fn encode(&self, data: &mut Vec<u8>) {
+    self.lang.original.encode(data);
+    self.desc.original.encode(data);
+    self.text.original.encode(data);
+  }
+  fn decode(reader: &mut &[u8]) -> io::Result<Self> {
+    let mut comment = Self::new(None);
+    comment.lang.original = Option::decode(reader)?;
+    comment.desc.original = Option::decode(reader)?;
+    comment.text.original = Option::decode(reader)?;
+    Ok(comment)
+  }
+}
+
+impl Encode for RawFrameEditableTag {
+  fn encode(&self, data: &mut Vec<u8>) {
+    self.id.original.encode(data);
+    self.desc.original.encode(data);
+    self.value.original.encode(data);
+    self.original.as_ref().map(|f| (f.id.clone(), (f.text, f.hash))).encode(data);
+  }
+  fn decode(reader: &mut &[u8]) -> io::Result<Self> {
+    let id = Option::<String>::decode(reader)?;
+    let desc = Option::decode(reader)?;
+    let mut value = Editable::new(Option::decode(reader)?);
+    value.validator = validation::for_frame(id.as_deref().unwrap_or_default());
+    let original = Option::<(String, (bool, u64))>::decode(reader)?;
+    Ok(Self {
+      id: Editable::new(id),
+      desc: Editable::new(desc),
+      value,
+      original: original.map(|(id, (text, hash))| ReadFrame { id, text, hash }),
+    })
+  }
+}
+
+impl Encode for Id3v1EditableTag {
+  fn encode(&self, data: &mut Vec<u8>) {
+    self.present.encode(data);
+    for field in &self.fields {
+      field.original.encode(data);
+    }
+  }
+  fn decode(reader: &mut &[u8]) -> io::Result<Self> {
+    let mut tag = Self { present: bool::decode(reader)?, ..Default::default() };
+    for field in tag.fields.iter_mut() {
+      field.original = Option::decode(reader)?;
+    }
+    Ok(tag)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{ fs, path::PathBuf, sync::Arc };
+  use id3::{
+    frame::{
+      Comment,
+      ExtendedText,
+      Lyrics,
+      Picture,
+      PictureType,
+      Popularimeter,
+      Private,
+      SynchronisedLyrics,
+      SynchronisedLyricsType,
+      TimestampFormat,
+    },
+    Encoding,
+    Frame,
+    Tag,
+    TagLike,
+    Version,
+    v1,
+  };
+  use super::{
+    super::{
+      disk::{ read_tags, Fingerprint },
+      error::TagError,
+      id3v1,
+      testing::{ TempDir, AUDIO },
+    },
+    parse_number_pair,
+    parse_timestamp,
+    EncodingSetting,
+    Id3v1Action,
+    Id3v1Field,
+    PictureEditableTag,
+    SongTags,
+    Settings,
+    VersionSetting,
+    WriteOptions,
+  };
+
+  /// Writes an MP3 stub carrying a tag with many frames `SongTags` knows nothing about, in a
+  /// directory removed with the returned guard.
+  fn fixture() -> (TempDir, PathBuf, Tag) {
+    let dir = TempDir::new("tag");
+    let path = dir.mp3("song.mp3");
+    let mut tag = Tag::new();
+    tag.set_title("Title");
+    tag.set_artist("Artist");
+    tag.set_genre("Rock");
+    tag.set_year(1999);
+    tag.set_album("Album");
+    tag.set_album_artist("Album Artist");
+    tag.set_track(3);
+    tag.set_total_tracks(12);
+    tag.set_disc(1);
+    tag.set_total_discs(2);
+    tag.add_frame(Picture {
+      mime_type: "image/png".into(),
+      picture_type: PictureType::CoverFront,
+      description: "cover".into(),
+      data: (0..=255).collect(),
+    });
+    tag.add_frame(Comment {
+      lang: "eng".into(),
+      description: "note".into(),
+      text: "a comment".into(),
+    });
+    tag.add_frame(ExtendedText {
+      description: "CATALOG".into(),
+      value: "XYZ-001".into(),
+    });
+    tag.add_frame(Popularimeter {
+      user: "me@example.com".into(),
+      rating: 196,
+      counter: 7,
+    });
+    tag.add_frame(Private {
+      owner_identifier: "tagchr".into(),
+      private_data: vec![1, 2, 3],
+    });
+    tag.add_frame(Lyrics {
+      lang: "eng".into(),
+      description: "".into(),
+      text: "original lyrics".into(),
+    });
+    tag.add_frame(Lyrics {
+      lang: "deu".into(),
+      description: "translation".into(),
+      text: "übersetzung".into(),
+    });
+    tag.write_to_path(&path, Version::Id3v24).unwrap();
+    let tag = Tag::read_from_path(&path).unwrap();
+    (dir, path, tag)
+  }
+
+  fn assert_kept(before: &Tag, after: &Tag, except: impl Fn(&Frame) -> bool) {
+    for frame in before.frames().filter(|f| !except(f)) {
+      assert!(after.frames().any(|f| f == frame), "frame lost on save: {:?}", frame);
+    }
+  }
+
+  #[test]
+  fn tags_read_from_memory_match_the_file() {
+    let (dir, path, _) = fixture();
+    let song_path = path.to_str().unwrap();
+    let untagged = dir.mp3("untagged.mp3");
+    id3v1::write_to_path(song_path, &v1::Tag { title: "Title".into(), ..Default::default() })
+      .unwrap();
+    for song_path in [song_path, untagged.to_str().unwrap()] {
+      let data = read_tags(song_path).unwrap();
+      let disk = Fingerprint::of(song_path).ok();
+      let read = SongTags::from_tags(song_path.into(), &data, disk);
+      assert_eq!(read, SongTags::new(song_path.into()));
+    }
+  }
+
+  #[test]
+  fn save_keeps_unrelated_frames() {
+    let (_dir, path, before) = fixture();
+    let mut tags = SongTags::new(path.to_str().unwrap().into());
+    tags.title.0.edit("New Title".into());
+    tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+
+    let after = Tag::read_from_path(&path).unwrap();
+    assert_eq!(after.title(), Some("New Title"));
+    assert_eq!(after.frames().count(), before.frames().count());
+    assert_kept(&before, &after, |f| f.id() == "TIT2");
+    assert!(fs::read(&path).unwrap().ends_with(&AUDIO.repeat(64)));
+  }
+
+  #[test]
+  fn save_removes_only_cleared_fields() {
+    let (_dir, path, before) = fixture();
+    let mut tags = SongTags::new(path.to_str().unwrap().into());
+    tags.artist.0.edit("".into());
+    tags.genre.0.edit("".into());
+    tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+
+    let after = Tag::read_from_path(&path).unwrap();
+    assert_eq!(after.artist(), None);
+    assert_eq!(after.genre(), None);
+    assert_eq!(after.frames().count(), before.frames().count() - 2);
+    assert_kept(&before, &after, |f| f.id() == "TPE1" || f.id() == "TCON");
+  }
+
+  #[test]
+  fn changes_are_listed_and_reverted() {
+    let (_dir, path, _) = fixture();
+    let mut tags = SongTags::new(path.to_str().unwrap().into());
+    let read = tags.clone();
+    assert!(tags.changes().is_empty());
+    tags.title.0.edit("New".into());
+    tags.artist.0.edit("".into());
+    tags.lyrics[0].text.edit("new\nlyrics".into());
+    let j = tags.add_frame("TMOO");
+    tags.frames[j].value.edit("calm".into());
+    let changes = tags
+      .changes()
+      .into_iter()
+      .map(|c| (c.field, c.original, c.current))
+      .collect::<Vec<_>>();
+    assert_eq!(changes, vec![
+      ("Title".into(), Some("Title".into()), Some("New".into())),
+      ("Artist".into(), Some("Artist".into()), None),
+      ("Lyrics #1".into(), Some("original lyrics".into()), Some("new\nlyrics".into())),
+      ("Frame ID".into(), None, Some("TMOO".into())),
+      ("TMOO frame".into(), None, Some("calm".into())),
+    ]);
+    tags.reset();
+    assert!(!tags.edited());
+    assert_eq!(tags, read);
+  }
+
+  #[test]
+  fn save_replaces_only_the_edited_lyrics_frame() {
+    let (_dir, path, before) = fixture();
+    let mut tags = SongTags::new(path.to_str().unwrap().into());
+    tags.lyrics[0].text.edit("new lyrics".into());
+    tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+
+    let after = Tag::read_from_path(&path).unwrap();
+    let lyrics = after.lyrics().collect::<Vec<_>>();
+    assert_eq!(lyrics.len(), 2);
+    assert!(lyrics.iter().any(|l| l.lang == "eng" && l.text == "new lyrics"));
+    assert_kept(&before, &after, |f| {
+      f.content().lyrics().is_some_and(|l| l.text == "original lyrics")
+    });
+  }
+
+  #[test]
+  fn save_applies_lyrics_frame_edits() {
+    let (_dir, path, _) = fixture();
+    let mut tags = SongTags::new(path.to_str().unwrap().into());
+    assert_eq!(tags.lyrics.len(), 2);
+    let j = tags.lyrics.iter().position(|l| l.lang.to_string() == "deu").unwrap();
+    tags.remove_lyrics(j);
+    let j = tags.add_lyrics();
+    tags.lyrics[j].text.edit("traduction".into());
+    assert!(!tags.lyrics_conflict(j));
+    tags.lyrics[j].desc.edit("".into());
+    assert!(tags.lyrics_conflict(j));
+    tags.lyrics[j].lang.edit("fra".into());
+    assert!(!tags.lyrics_conflict(j));
+    tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+
+    let after = Tag::read_from_path(&path).unwrap();
+    let lyrics = after.lyrics().collect::<Vec<_>>();
+    assert_eq!(lyrics.len(), 2);
+    assert!(lyrics.iter().any(|l| l.lang == "eng" && l.text == "original lyrics"));
+    assert!(lyrics.iter().any(|l| l.lang == "fra" && l.text == "traduction"));
+  }
+
+  #[test]
+  fn refuses_to_drop_synced_lyrics_of_the_same_language_and_type() {
+    // id3 keeps one SYLT frame per language and type, whatever their descriptions
+    for (timestamp_format, shown) in [(TimestampFormat::Ms, 1), (TimestampFormat::Mpeg, 0)] {
+      let dir = TempDir::new("tag");
+      let path = dir.mp3("song.mp3");
+      let mut tag = Tag::new();
+      tag.add_frame(SynchronisedLyrics {
+        lang: "eng".into(),
+        timestamp_format,
+        content_type: SynchronisedLyricsType::Lyrics,
+        description: "verse".into(),
+        content: vec![(1000, "Verse".into())],
+      });
+      tag.write_to_path(&path, Version::Id3v24).unwrap();
+
+      let mut tags = SongTags::new(path.to_str().unwrap().into());
+      assert_eq!(tags.lyrics.len(), shown);
+      assert!(tags.valid());
+      let j = tags.add_lyrics();
+      tags.lyrics[j].desc.edit("chorus".into());
+      tags.lyrics[j].synced.set(Some(vec![(500, "Chorus".into())]));
+      assert!(tags.lyrics_conflict(j));
+      let res = tags.save(&Settings::default(), Id3v1Action::Update, None);
+      assert!(matches!(res, Err(TagError::Invalid(_))));
+      let descriptions = |path: &PathBuf| {
+        let tag = Tag::read_from_path(path).unwrap();
+        tag.synchronised_lyrics().map(|s| s.description.clone()).collect::<Vec<_>>()
+      };
+      assert_eq!(descriptions(&path), ["verse"]);
+
+      tags.lyrics[j].lang.edit("fra".into());
+      assert!(!tags.lyrics_conflict(j));
+      tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+      let mut saved = descriptions(&path);
+      saved.sort();
+      assert_eq!(saved, ["chorus", "verse"]);
     }
   }
+
+
+  #[test]
+  fn multiline_lyrics_round_trip() {
+    let (_dir, path, mut tag) = fixture();
+    tag.add_frame(Lyrics {
+      lang: "eng".into(),
+      description: "".into(),
+      text: "first verse\r\nsecond line\r\n\r\nchorus\rlast line".into(),
+    });
+    tag.write_to_path(&path, Version::Id3v24).unwrap();
+
+    let mut tags = SongTags::new(path.to_str().unwrap().into());
+    let j = tags.lyrics.iter().position(|l| l.desc.to_string() == "").unwrap();
+    let mut lines = tags.lyrics[j].text_lines();
+    assert_eq!(lines, ["first verse", "second line", "", "chorus", "last line"]);
+    tags.lyrics[j].edit_text_lines(&lines);
+    assert!(!tags.lyrics[j].edited());
+    lines.push("".into());
+    lines.push("outro".into());
+    tags.lyrics[j].edit_text_lines(&lines);
+    tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+
+    let after = Tag::read_from_path(&path).unwrap();
+    let lyrics = after.lyrics().collect::<Vec<_>>();
+    assert_eq!(lyrics.len(), 2);
+    let lyrics = lyrics.iter().find(|l| l.description.is_empty()).unwrap();
+    assert_eq!(lyrics.text, "first verse\nsecond line\n\nchorus\nlast line\n\noutro");
+    let tags = SongTags::new(path.to_str().unwrap().into());
+    assert_eq!(tags.lyrics[j].text_lines(), lines);
+  }
+
+  #[test]
+  fn synced_lyrics_round_trip_through_lrc() {
+    let (_dir, path, before) = fixture();
+    let lrc_path = path.with_extension("lrc");
+    let lrc = "\u{feff}[ar:Someone]\r\n[00:01.50]first\r\n[00:03.00]second\r\n";
+    fs::write(&lrc_path, lrc).unwrap();
+    let mut tags = SongTags::new(path.to_str().unwrap().into());
+    let j = tags.lyrics.iter().position(|l| l.desc.to_string() == "").unwrap();
+    assert_eq!(tags.import_lrc(Some(j), lrc_path.to_str().unwrap()), Ok(j));
+    assert_eq!(tags.lyrics[j].synced_lines(), ["[00:01.50]first", "[00:03.00]second"]);
+    tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+
+    let after = Tag::read_from_path(&path).unwrap();
+    let synced = after.synchronised_lyrics().collect::<Vec<_>>();
+    assert_eq!(synced.len(), 1);
+    assert_eq!(synced[0].lang, "eng");
+    assert_eq!(synced[0].content, [(1_500, "first".to_string()), (3_000, "second".to_string())]);
+    assert_kept(&before, &after, |_| false);
+
+    let mut tags = SongTags::new(path.to_str().unwrap().into());
+    assert_eq!(tags.lyrics.len(), 2);
+    let j = tags.lyrics.iter().position(|l| l.desc.to_string() == "").unwrap();
+    assert!(tags.lyrics[j].text.current().is_some());
+    let mut lines = tags.lyrics[j].synced_lines();
+    lines.push("[00:05.00]third".into());
+    tags.lyrics[j].edit_synced_lines(&lines).unwrap();
+    assert_eq!(tags.lyrics[j].edit_synced_lines(&["untimed".into()]), Err(1));
+    tags.export_lrc(j, lrc_path.to_str().unwrap()).unwrap();
+    assert_eq!(
+      fs::read_to_string(&lrc_path).unwrap(),
+      "[ti:Title]\n[ar:Artist]\n[al:Album]\n[00:01.50]first\n[00:03.00]second\n[00:05.00]third\n"
+    );
+  }
+
+  #[test]
+  fn save_applies_comment_edits() {
+    let (_dir, path, before) = fixture();
+    let mut tags = SongTags::new(path.to_str().unwrap().into());
+    assert_eq!(tags.comments.len(), 1);
+    tags.remove_comment(0);
+    let j = tags.add_comment();
+    tags.comments[j].desc.edit("note".into());
+    tags.comments[j].text.edit("line one\nline two".into());
+    assert!(!tags.comment_conflict(j));
+    tags.remove_comment(0);
+    assert!(tags.comment_conflict(j));
+    assert!(!tags.valid());
+    tags.remove_comment(0);
+    tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+
+    let after = Tag::read_from_path(&path).unwrap();
+    let comments = after.comments().collect::<Vec<_>>();
+    assert_eq!(comments.len(), 1);
+    assert_eq!(comments[0].description, "note");
+    assert_eq!(comments[0].text, "line one\nline two");
+    assert_kept(&before, &after, |f| f.id() == "COMM");
+  }
+
+  #[test]
+  fn save_applies_raw_frame_edits() {
+    let (_dir, path, before) = fixture();
+    let mut tags = SongTags::new(path.to_str().unwrap().into());
+    let position = |tags: &SongTags, id: &str| {
+      tags.frames.iter().position(|f| f.id.to_string() == id)
+    };
+    let catalog = position(&tags, "TXXX").unwrap();
+    assert_eq!(tags.frames[catalog].desc.to_string(), "CATALOG");
+    tags.frames[catalog].value.edit("XYZ-002".into());
+    let title = position(&tags, "TIT2").unwrap();
+    tags.remove_frame(title);
+    assert!(!tags.frames[title].removed());
+    tags.remove_frame(position(&tags, "PRIV").unwrap());
+    let j = tags.add_frame("");
+    tags.frames[j].id.edit("TALB".into());
+    assert!(tags.frame_conflict(j));
+    tags.frames[j].id.edit("TCOM".into());
+    tags.frames[j].value.edit("Composer\\0Other".into());
+    assert!(!tags.frame_conflict(j));
+    let j = tags.add_frame("TXXX");
+    tags.frames[j].desc.edit("CATALOG".into());
+    tags.frames[j].value.edit("dup".into());
+    assert!(tags.frame_conflict(j));
+    tags.remove_frame(j);
+    tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+
+    let after = Tag::read_from_path(&path).unwrap();
+    let extended = after.extended_texts().collect::<Vec<_>>();
+    assert_eq!(extended.len(), 1);
+    assert_eq!(extended[0].value, "XYZ-002");
+    assert_eq!(after.get("TCOM").and_then(|f| f.content().text()), Some("Composer\0Other"));
+    assert!(after.get("PRIV").is_none());
+    assert_eq!(after.title(), Some("Title"));
+    assert_kept(&before, &after, |f| ["TXXX", "TCOM", "PRIV"].contains(&f.id()));
+  }
+
+  #[test]
+  fn save_keeps_or_converts_the_version() {
+    let (_dir, path, before) = fixture();
+    let mut v23 = Tag::new();
+    for frame in before.frames() {
+      v23.add_frame(frame.clone().set_encoding(None));
+    }
+    v23.write_to_path(&path, Version::Id3v23).unwrap();
+    let mut tags = SongTags::new(path.to_str().unwrap().into());
+    assert_eq!(tags.version, Some(Version::Id3v23));
+    tags.title.0.edit("New".into());
+    let utf8 = WriteOptions { version: VersionSetting::Keep, encoding: EncodingSetting::Utf8 };
+    let settings = Settings { write_options: utf8, ..Default::default() };
+    tags.save(&settings, Id3v1Action::Update, None).unwrap();
+    let after = Tag::read_from_path(&path).unwrap();
+    assert_eq!(after.version(), Version::Id3v23);
+    assert_eq!(after.title(), Some("New"));
+    assert!(after.frames().all(|f| f.encoding() != Some(Encoding::UTF8)));
+
+    tags.write_options.set(
+      Some(WriteOptions { version: VersionSetting::Id3v24, ..utf8 })
+    );
+    assert!(tags.edited());
+    tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+    assert!(!tags.edited());
+    assert_eq!(tags.version, Some(Version::Id3v24));
+    let after = Tag::read_from_path(&path).unwrap();
+    assert_eq!(after.get("TXXX").and_then(|f| f.encoding()), Some(Encoding::UTF8));
+    assert_eq!(after.title(), Some("New"));
+  }
+
+  #[test]
+  fn id3v1_sync_and_save() {
+    let (_dir, path, _) = fixture();
+    let mut v1_tag = v1::Tag::new();
+    v1_tag.title = "Other".into();
+    v1_tag.artist = "Artist".into();
+    v1_tag.track = Some(3);
+    v1_tag.genre_id = 17;
+    id3v1::write_to_path(path.to_str().unwrap(), &v1_tag).unwrap();
+
+    let mut tags = SongTags::new(path.to_str().unwrap().into());
+    assert!(tags.id3v1.present);
+    assert_eq!(tags.id3v1.field(Id3v1Field::Genre).to_string(), "Rock");
+    assert!(tags.id3v1_conflict(Id3v1Field::Title));
+    assert!(!tags.id3v1_conflict(Id3v1Field::Artist));
+    assert!(!tags.id3v1_conflict(Id3v1Field::Track));
+    assert!(!tags.id3v1_conflict(Id3v1Field::Genre));
+
+    tags.title.0.edit("A title much longer than thirty characters".into());
+    assert!(tags.id3v1_truncated(Id3v1Field::Title));
+    tags.copy_to_id3v1();
+    assert_eq!(tags.id3v1.field(Id3v1Field::Title).to_string(), "A title much longer than thirt");
+    assert!(!tags.id3v1_conflict(Id3v1Field::Title));
+    tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+    assert_eq!(tags.id3v1.field(Id3v1Field::Title).to_string(), "A title much longer than thirt");
+    assert_eq!(tags.id3v1.field(Id3v1Field::Album).to_string(), "Album");
+    assert_eq!(v1::Tag::read_from_path(&path).unwrap().track, Some(3));
+
+    tags.id3v1.field_mut(Id3v1Field::Comment).edit("from v1".into());
+    tags.copy_from_id3v1();
+    assert_eq!(tags.track.0.to_string(), "3/12");
+    let j = tags.comments.iter().position(|c| c.desc.to_string().is_empty()).unwrap();
+    assert_eq!(tags.comments[j].text.to_string(), "from v1");
+    tags.save(&Settings::default(), Id3v1Action::Strip, None).unwrap();
+    assert!(!tags.id3v1.present);
+    assert_eq!(tags.comments.len(), 2);
+
+    tags.title.0.edit("Title".into());
+    tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+    assert!(!tags.id3v1.present);
+    tags.copy_to_id3v1();
+    tags.save(&Settings::default(), Id3v1Action::Write, None).unwrap();
+    assert_eq!(v1::Tag::read_from_path(&path).unwrap().title, "Title");
+  }
+
+  #[test]
+  fn number_pairs() {
+    assert_eq!(parse_number_pair("3"), Some((3, None)));
+    assert_eq!(parse_number_pair(" 3 / 12 "), Some((3, Some(12))));
+    assert_eq!(parse_number_pair("3/"), None);
+    assert_eq!(parse_number_pair("A1"), None);
+    assert_eq!(parse_number_pair(""), None);
+  }
+
+  #[test]
+  fn save_writes_track_and_disc() {
+    let (_dir, path, _) = fixture();
+    let mut tags = SongTags::new(path.to_str().unwrap().into());
+    assert_eq!(tags.track.0.to_string(), "3/12");
+    tags.track.0.edit("4/12".into());
+    tags.disc.0.edit("x".into());
+    assert!(!tags.valid());
+    assert!(tags.save(&Settings::default(), Id3v1Action::Update, None).is_err());
+    tags.disc.0.edit("2".into());
+    tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+
+    let after = Tag::read_from_path(&path).unwrap();
+    assert_eq!((after.track(), after.total_tracks()), (Some(4), Some(12)));
+    assert_eq!((after.disc(), after.total_discs()), (Some(2), None));
+  }
+
+  #[test]
+  fn invalid_fields_block_save() {
+    let (_dir, path, _) = fixture();
+    let mut tags = SongTags::new(path.to_str().unwrap().into());
+    assert!(tags.errors().is_empty());
+    tags.comments[0].lang.edit("english".into());
+    let j = tags.add_frame("TBPM");
+    tags.frames[j].value.edit("fast".into());
+    assert_eq!(
+      tags.errors(),
+      vec![
+        "Comment #1: At most 3 characters".to_string(),
+        "TBPM frame: Expected a whole number".to_string()
+      ]
+    );
+    assert!(tags.save(&Settings::default(), Id3v1Action::Update, None).is_err());
+    tags.comments[0].lang.edit("deu".into());
+    tags.frames[j].edit_id("TMOO".into());
+    assert!(tags.valid());
+    tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+    let after = Tag::read_from_path(&path).unwrap();
+    assert_eq!(after.get("TMOO").and_then(|f| f.content().text()), Some("fast"));
+  }
+
+  #[test]
+  fn genres_are_decoded_and_saved_as_values() {
+    let (_dir, path, mut before) = fixture();
+    before.set_genre("(17)(4)Eurodisco");
+    before.write_to_path(&path, Version::Id3v24).unwrap();
+    let mut tags = SongTags::new(path.to_str().unwrap().into());
+    assert_eq!(tags.genre.0.to_string(), "Rock; Eurodisco");
+    assert_eq!(tags.id3v2_value(Id3v1Field::Genre).as_deref(), Some("Rock"));
+    tags.title.0.edit("New".into());
+    tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+    assert_eq!(Tag::read_from_path(&path).unwrap().genre(), Some("(17)(4)Eurodisco"));
+
+    tags.genre.0.edit("Rock; Synthwave".into());
+    tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+    let after = Tag::read_from_path(&path).unwrap();
+    assert_eq!(after.genres(), Some(vec!["Rock", "Synthwave"]));
+    assert_eq!(tags.genre.0.to_string(), "Rock; Synthwave");
+  }
+
+  #[test]
+  fn timestamps() {
+    let date = parse_timestamp(" 2023-02-28T13:05 ").unwrap();
+    assert_eq!((date.year, date.month, date.day), (2023, Some(2), Some(28)));
+    assert_eq!((date.hour, date.minute, date.second), (Some(13), Some(5), None));
+    assert_eq!(parse_timestamp("2023").unwrap().to_string(), "2023");
+    assert_eq!(parse_timestamp("2023-02").unwrap().to_string(), "2023-02");
+    assert!(parse_timestamp("2023-02-30").is_none());
+    assert!(parse_timestamp("2023-2-3").is_none());
+    assert!(parse_timestamp("2023-02T10").is_none());
+    assert!(parse_timestamp("2023-02-28T25").is_none());
+    assert!(parse_timestamp("23").is_none());
+    assert!(parse_timestamp("").is_none());
+  }
+
+  #[test]
+  fn save_writes_dates_for_the_version() {
+    let (_dir, path, _) = fixture();
+    let mut tags = SongTags::new(path.to_str().unwrap().into());
+    assert_eq!(tags.date.0.to_string(), "1999");
+    tags.date.0.edit("1999-02-30".into());
+    assert!(!tags.valid());
+    assert!(tags.save(&Settings::default(), Id3v1Action::Update, None).is_err());
+    tags.date.0.edit("1999-03-04T05:06".into());
+    tags.original_date.0.edit("1970".into());
+    tags.release_date.0.edit("2000-01-01".into());
+    tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+    let after = Tag::read_from_path(&path).unwrap();
+    assert_eq!(after.get("TYER"), None);
+    assert_eq!(after.get("TDRC").and_then(|f| f.content().text()), Some("1999-03-04T05:06"));
+    assert_eq!(after.get("TDOR").and_then(|f| f.content().text()), Some("1970"));
+    assert_eq!(after.get("TDRL").and_then(|f| f.content().text()), Some("2000-01-01"));
+
+    let v23 = WriteOptions { version: VersionSetting::Id3v23, ..WriteOptions::default() };
+    tags.write_options.set(Some(v23));
+    tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+    let after = Tag::read_from_path(&path).unwrap();
+    let text = |id: &str| after.get(id).and_then(|f| f.content().text()).map(String::from);
+    assert_eq!(text("TDRC"), None);
+    assert_eq!(text("TYER").as_deref(), Some("1999"));
+    assert_eq!(text("TDAT").as_deref(), Some("0403"));
+    assert_eq!(text("TIME").as_deref(), Some("0506"));
+    assert_eq!(text("TORY").as_deref(), Some("1970"));
+    assert_eq!(tags.date.0.to_string(), "1999-03-04T05:06");
+    assert_eq!(tags.original_date.0.to_string(), "1970");
+  }
+
+  #[test]
+  fn save_applies_picture_edits() {
+    let (_dir, path, before) = fixture();
+    let image_path = path.with_extension("png");
+    let mut png = std::io::Cursor::new(Vec::new());
+    image::RgbImage::new(2, 3).write_to(&mut png, image::ImageFormat::Png).unwrap();
+    fs::write(&image_path, png.get_ref()).unwrap();
+
+    let mut tags = SongTags::new(path.to_str().unwrap().into());
+    tags.import_picture(image_path.to_str().unwrap(), None).unwrap();
+    let added = tags.pictures[1].0.current().unwrap();
+    assert_eq!(added.picture_type, PictureType::CoverBack);
+    assert_eq!(added.mime_type, "image/png");
+    assert_eq!(PictureEditableTag::dimensions(added), Some((2, 3)));
+    // undo steps share the image data rather than copying it
+    let step = tags.clone();
+    assert!(Arc::ptr_eq(step.pictures[1].0.current().unwrap(), added));
+    tags.remove_picture(0);
+    assert!(tags.edited());
+    tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+
+    let after = Tag::read_from_path(&path).unwrap();
+    let pictures = after.pictures().collect::<Vec<_>>();
+    assert_eq!(pictures.len(), 1);
+    assert_eq!(pictures[0].data, *png.get_ref());
+    assert_kept(&before, &after, |f| f.id() == "APIC");
+  }
+
+  #[test]
+  fn edits_are_rebased_onto_changes_on_disk() {
+    let (_dir, path, mut external) = fixture();
+    let mut tags = SongTags::new(path.to_str().unwrap().into());
+    tags.title.0.edit("My Title".into());
+    tags.artist.0.edit("My Artist".into());
+    tags.comments[0].text.edit("my comment".into());
+    external.set_artist("Their Artist");
+    external.set_album("Their Album");
+    external.write_to_path(&path, Version::Id3v24).unwrap();
+
+    assert!(tags.changed_on_disk());
+    assert!(matches!(
+      tags.save(&Settings::default(), Id3v1Action::Update, None),
+      Err(TagError::ChangedOnDisk(_))
+    ));
+    let disk = SongTags::new(path.to_str().unwrap().into());
+    let (changed, conflicts) = tags.merge(&disk);
+    let fields = changed.iter().map(|c| c.field.as_str()).collect::<Vec<_>>();
+    assert_eq!(fields, ["Artist", "Album"]);
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].mine.as_deref(), Some("My Artist"));
+    assert_eq!(conflicts[0].theirs.as_deref(), Some("Their Artist"));
+
+    let mut tags = tags.rebase(disk);
+    assert!(!tags.changed_on_disk());
+    assert_eq!(tags.album.0.to_string(), "Their Album");
+    assert!(!tags.album.0.edited());
+    tags.save(&Settings::default(), Id3v1Action::Update, None).unwrap();
+    let after = Tag::read_from_path(&path).unwrap();
+    assert_eq!(after.title(), Some("My Title"));
+    assert_eq!(after.artist(), Some("My Artist"));
+    assert_eq!(after.album(), Some("Their Album"));
+    assert_eq!(after.comments().map(|c| c.text.as_str()).collect::<Vec<_>>(), ["my comment"]);
+  }
 }
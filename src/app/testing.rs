@@ -0,0 +1,35 @@
+use std::{ fs, path::{ Path, PathBuf } };
+use uuid::Uuid;
+
+/// An MPEG frame header and padding, repeated to stand for the audio of a file.
+pub const AUDIO: [u8; 8] = [0xff, 0xfb, 0x90, 0x44, 0x00, 0x00, 0x00, 0x00];
+
+/// A new directory under the temporary directory of the system, removed with everything in it
+/// when dropped, so that a failed test cleans up too.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+  pub fn new(name: &str) -> Self {
+    let dir = std::env::temp_dir().join(format!("tagchr-{}-{}", name, Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    Self(dir)
+  }
+  pub fn path(&self) -> &Path {
+    &self.0
+  }
+  pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+    self.0.join(path)
+  }
+  /// Writes an MP3 file without tags at `path` in the directory.
+  pub fn mp3(&self, path: impl AsRef<Path>) -> PathBuf {
+    let path = self.join(path);
+    fs::write(&path, AUDIO.repeat(64)).unwrap();
+    path
+  }
+}
+
+impl Drop for TempDir {
+  fn drop(&mut self) {
+    let _ = fs::remove_dir_all(&self.0);
+  }
+}
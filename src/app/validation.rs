@@ -0,0 +1,131 @@
+use core::fmt;
+use super::tag::{ parse_number_pair, parse_timestamp, MANAGED_FRAME_IDS };
+
+/// A check of an edited text value, the error is the reason shown under the input.
+pub trait Validator: fmt::Debug + Sync {
+  fn validate(&self, value: &str) -> Result<(), String>;
+}
+
+/// Limits the number of characters.
+#[derive(Debug)]
+pub struct MaxLength(pub usize);
+
+impl Validator for MaxLength {
+  fn validate(&self, value: &str) -> Result<(), String> {
+    if value.chars().count() > self.0 {
+      return Err(format!("At most {} characters", self.0));
+    }
+    Ok(())
+  }
+}
+
+/// A whole number such as TBPM or TLEN.
+#[derive(Debug)]
+pub struct Numeric;
+
+impl Validator for Numeric {
+  fn validate(&self, value: &str) -> Result<(), String> {
+    match value.trim().parse::<u64>() {
+      Ok(_) => Ok(()),
+      Err(_) => Err("Expected a whole number".into()),
+    }
+  }
+}
+
+/// An ISO-639-2 language code, three lowercase letters or "XXX" when unknown.
+#[derive(Debug)]
+pub struct Language;
+
+impl Validator for Language {
+  fn validate(&self, value: &str) -> Result<(), String> {
+    MaxLength(3).validate(value)?;
+    if value != "XXX" && (value.len() != 3 || !value.chars().all(|c| c.is_ascii_lowercase())) {
+      return Err("Expected an ISO-639-2 code like \"eng\"".into());
+    }
+    Ok(())
+  }
+}
+
+/// An ID3v2.4 timestamp.
+#[derive(Debug)]
+pub struct Date;
+
+impl Validator for Date {
+  fn validate(&self, value: &str) -> Result<(), String> {
+    match parse_timestamp(value) {
+      Some(_) => Ok(()),
+      None => Err("Expected yyyy[-MM[-dd[THH[:mm[:ss]]]]]".into()),
+    }
+  }
+}
+
+/// "n" or "n/total" as used by TRCK and TPOS.
+#[derive(Debug)]
+pub struct NumberPair;
+
+impl Validator for NumberPair {
+  fn validate(&self, value: &str) -> Result<(), String> {
+    match parse_number_pair(value) {
+      Some(_) => Ok(()),
+      None => Err("Expected \"n\" or \"n/total\"".into()),
+    }
+  }
+}
+
+/// ID of a text frame added among all frames.
+#[derive(Debug)]
+pub struct FrameId;
+
+impl Validator for FrameId {
+  fn validate(&self, value: &str) -> Result<(), String> {
+    if value.len() != 4 || !value.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()) {
+      return Err("Expected 4 uppercase letters or digits".into());
+    }
+    if !value.starts_with('T') {
+      return Err("Only text frames starting with T can be added".into());
+    }
+    if MANAGED_FRAME_IDS.contains(&value) {
+      return Err("Edited on the home screen".into());
+    }
+    Ok(())
+  }
+}
+
+/// Check of the value of a text frame with a known format.
+pub fn for_frame(id: &str) -> Option<&'static dyn Validator> {
+  match id {
+    "TBPM" | "TLEN" | "TDLY" | "TSIZ" => Some(&Numeric),
+    "TDRC" | "TDOR" | "TDRL" | "TDEN" | "TDTG" => Some(&Date),
+    "TRCK" | "TPOS" => Some(&NumberPair),
+    "TLAN" => Some(&Language),
+    // the musical key, e.g. "Cbm"
+    "TKEY" => Some(&MaxLength(3)),
+    // ISRC codes are 12 characters
+    "TSRC" => Some(&MaxLength(12)),
+    _ => None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{ for_frame, FrameId, Language, MaxLength, Validator };
+
+  #[test]
+  fn validators() {
+    assert!(MaxLength(3).validate("née").is_ok());
+    assert!(MaxLength(3).validate("four").is_err());
+    assert!(Language.validate("eng").is_ok());
+    assert!(Language.validate("XXX").is_ok());
+    assert!(Language.validate("en").is_err());
+    assert!(Language.validate("Eng").is_err());
+    assert_eq!(Language.validate("engl"), Err("At most 3 characters".into()));
+    assert!(FrameId.validate("TMOO").is_ok());
+    assert!(FrameId.validate("TIT2").is_err());
+    assert!(FrameId.validate("WXXX").is_err());
+    assert!(FrameId.validate("tmoo").is_err());
+    assert!(for_frame("TBPM").unwrap().validate("120").is_ok());
+    assert!(for_frame("TBPM").unwrap().validate("fast").is_err());
+    assert!(for_frame("TDRC").unwrap().validate("2024-13").is_err());
+    assert!(for_frame("TMOO").is_none());
+  }
+}
@@ -0,0 +1,329 @@
+use std::sync::mpsc::Sender;
+use crate::{
+  app::{ app::Command, state::State, tag::{ CommentEditableTag, DUPLICATE_KEY } },
+  ui::{
+    home::{ self, screen::{ EditorFocusable, HomeScreen } },
+    shortcut::Shortcut,
+    text_area::TextAreaTrait,
+    ui_enums::Screen,
+    InputHandler,
+    StateDependentWidget,
+    UiState,
+    StyleFlags,
+  },
+};
+use crossterm::event::{ Event, KeyCode, KeyEventKind, KeyModifiers };
+use ratatui::{
+  buffer::Buffer,
+  layout::{ Constraint, Layout, Margin, Rect },
+  style::{ Color, Style, Stylize },
+  text::{ Line, Span },
+  widgets::{ Block, BorderType, List, ListItem, ListState, Paragraph, StatefulWidget, Widget },
+};
+use tui_textarea::TextArea;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Focusable {
+  List,
+  Lang,
+  Desc,
+  Text,
+}
+
+impl Focusable {
+  pub fn next(self) -> Self {
+    match self {
+      Focusable::List => Focusable::Lang,
+      Focusable::Lang => Focusable::Desc,
+      Focusable::Desc => Focusable::Text,
+      Focusable::Text => Focusable::List,
+    }
+  }
+  pub fn prev(self) -> Self {
+    match self {
+      Focusable::List => Focusable::Text,
+      Focusable::Lang => Focusable::List,
+      Focusable::Desc => Focusable::Lang,
+      Focusable::Text => Focusable::Desc,
+    }
+  }
+}
+
+pub struct CommentsScreen {
+  pub index: usize,
+  pub focused_el: Focusable,
+  /// Comment whose fields are shown, `None` when the file has no comments.
+  pub selected: Option<usize>,
+  pub lang_input: TextArea<'static>,
+  pub desc_input: TextArea<'static>,
+  pub text_textarea: TextArea<'static>,
+}
+
+fn comment_input(title: &'static str) -> TextArea<'static> {
+  let mut input = TextArea::new(Vec::new());
+  input.set_block(Block::bordered().border_type(BorderType::Rounded).title_top(title));
+  input.set_cursor_line_style(Style::new());
+  input
+}
+
+impl CommentsScreen {
+  pub fn new(index: usize, comments: &[CommentEditableTag]) -> Self {
+    let mut new = Self {
+      index,
+      focused_el: Focusable::List,
+      selected: None,
+      lang_input: comment_input("Lang"),
+      desc_input: comment_input("Desc"),
+      text_textarea: comment_input("Text"),
+    };
+    new.select(if comments.is_empty() { None } else { Some(0) }, comments);
+    new
+  }
+  /// Shows another comment and fills the inputs with its values.
+  pub fn select(&mut self, j: Option<usize>, comments: &[CommentEditableTag]) {
+    let j = j.filter(|j| *j < comments.len());
+    self.selected = j;
+    let comment = j.map(|j| &comments[j]);
+    self.lang_input.set_text(comment.map(|c| c.lang.to_string()).unwrap_or_default());
+    self.desc_input.set_text(comment.map(|c| c.desc.to_string()).unwrap_or_default());
+    let mut text_textarea = TextArea::new(comment.map(|c| c.text_lines()).unwrap_or_default());
+    text_textarea.set_block(self.text_textarea.block().cloned().unwrap_or_default());
+    text_textarea.set_cursor_line_style(Style::new());
+    self.text_textarea = text_textarea;
+  }
+  pub fn input_mut(&mut self, el: Focusable) -> Option<&mut TextArea<'static>> {
+    match el {
+      Focusable::List => None,
+      Focusable::Lang => Some(&mut self.lang_input),
+      Focusable::Desc => Some(&mut self.desc_input),
+      Focusable::Text => Some(&mut self.text_textarea),
+    }
+  }
+}
+
+impl InputHandler for CommentsScreen {
+  fn handle_input(
+    &self,
+    state: &State,
+    ui_state: &UiState,
+    event: Event,
+    sender: Sender<Command>
+  ) -> bool {
+    let comments = state.get_file(self.index).tags.comments.len();
+    match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+        match (key_event.code, key_event.modifiers, self.focused_el) {
+          (KeyCode::Esc, _, _) => {
+            sender.send(
+              Command::SetScreen(
+                Screen::Home(
+                  HomeScreen::new(
+                    home::screen::Focusable::Editor(self.index, EditorFocusable::CommentsButton),
+                    Some(&state.get_file(self.index).tags)
+                  )
+                )
+              )
+            );
+            true
+          }
+          (KeyCode::Down, KeyModifiers::CONTROL, f_el) | (KeyCode::PageDown, _, f_el) => {
+            sender.send(Command::FocusCommentsElement(f_el.next()));
+            true
+          }
+          (KeyCode::Up, KeyModifiers::CONTROL, f_el) | (KeyCode::PageUp, _, f_el) => {
+            sender.send(Command::FocusCommentsElement(f_el.prev()));
+            true
+          }
+          (KeyCode::Char('r' | 'к'), KeyModifiers::CONTROL, f_el) => {
+            sender.send(Command::ResetCommentsScreenTag(f_el));
+            true
+          }
+          (KeyCode::Char('z' | 'я'), KeyModifiers::CONTROL, _) => {
+            sender.send(Command::Undo);
+            true
+          }
+          (KeyCode::Char('y' | 'н'), KeyModifiers::CONTROL, _) => {
+            sender.send(Command::Redo);
+            true
+          }
+          (code, KeyModifiers::NONE, Focusable::List) => {
+            let j = self.selected.unwrap_or_default();
+            match code {
+              KeyCode::Up if comments > 0 => {
+                sender.send(Command::SelectComment(if j > 0 { j - 1 } else { comments - 1 }));
+              }
+              KeyCode::Down if comments > 0 => {
+                sender.send(Command::SelectComment(if j + 1 >= comments { 0 } else { j + 1 }));
+              }
+              KeyCode::Enter if comments > 0 => {
+                sender.send(Command::FocusCommentsElement(Focusable::Text));
+              }
+              KeyCode::Char('a') => {
+                sender.send(Command::AddComment);
+              }
+              KeyCode::Delete if comments > 0 => {
+                sender.send(Command::RemoveComment(j));
+              }
+              _ => {
+                return false;
+              }
+            }
+            true
+          }
+          _ => {
+            sender.send(Command::HandleCommentsScreenInput(key_event, self.focused_el));
+            true
+          }
+        }
+      }
+      _ => false,
+    }
+  }
+}
+
+impl StateDependentWidget for CommentsScreen {
+  fn render_from_state(&self, area: Rect, buf: &mut Buffer, state: &State, ui_state: &UiState)
+    where Self: Sized
+  {
+    let [header_area, main_area, footer_area] = Layout::vertical([
+      Constraint::Length(2),
+      Constraint::Fill(1),
+      Constraint::Length(1),
+    ]).areas(area);
+    let footer_area = footer_area.inner(Margin::new(1, 0));
+    let [list_area, fields_area] = Layout::horizontal([
+      Constraint::Length(32),
+      Constraint::Fill(1),
+    ]).areas(main_area);
+    let [lang_area, desc_area, text_area] = Layout::vertical([
+      Constraint::Length(3),
+      Constraint::Length(3),
+      Constraint::Fill(1),
+    ]).areas(fields_area);
+
+    Paragraph::new(vec![Line::from("Comments Editing"), Line::from("").gray()])
+      .centered()
+      .render(header_area, buf);
+
+    let tags = &state.get_file(self.index).tags;
+
+    {
+      let focused = self.focused_el == Focusable::List;
+      let items = tags.comments
+        .iter()
+        .enumerate()
+        .map(|(j, comment)| {
+          let (lang, desc) = comment.key();
+          let mut style = Style::from(StyleFlags {
+            enabled: true,
+            valid: !tags.comment_conflict(j) && comment.lang.valid(),
+            highlighted: comment.edited(),
+          });
+          if comment.removed() {
+            style = style.crossed_out();
+          }
+          ListItem::new(
+            Line::from(
+              Vec::from([Span::from(lang).dark_gray(), Span::from(" "), Span::from(desc)])
+            )
+          ).style(style)
+        })
+        .collect::<Vec<_>>();
+      let list = List::new(items)
+        .highlight_style(Style::new().on_dark_gray().bold())
+        .block(
+          Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_top("Frames")
+            .title_bottom(
+              if tags.comments.is_empty() {
+                Line::from(" No comments ").dark_gray().centered()
+              } else {
+                Line::default()
+              }
+            )
+            .border_style(
+              Style::from(StyleFlags {
+                enabled: true,
+                valid: true,
+                highlighted: focused,
+              })
+            )
+        );
+      let mut list_state = ListState::default().with_selected(self.selected);
+      <List as StatefulWidget>::render(list, list_area, buf, &mut list_state);
+    }
+
+    let comment = self.selected.and_then(|j| tags.comments.get(j).map(|c| (j, c)));
+    for (el, area) in [
+      (Focusable::Lang, lang_area),
+      (Focusable::Desc, desc_area),
+      (Focusable::Text, text_area),
+    ] {
+      let mut input = match el {
+        Focusable::Lang => self.lang_input.clone(),
+        Focusable::Desc => self.desc_input.clone(),
+        _ => self.text_textarea.clone(),
+      };
+      let edited = comment.is_some_and(|(_, c)| {
+        match el {
+          Focusable::Lang => c.lang.edited(),
+          Focusable::Desc => c.desc.edited(),
+          _ => c.text.edited(),
+        }
+      });
+      let error = match el {
+        Focusable::Text => None,
+        _ => {
+          comment.and_then(|(j, c)| {
+            let error = if el == Focusable::Lang { c.lang.error() } else { None };
+            error.or_else(|| tags.comment_conflict(j).then(|| DUPLICATE_KEY.into()))
+          })
+        }
+      };
+      if let Some(error) = &error {
+        input.set_block(input.block().cloned().unwrap_or_default().title_bottom(error.clone()));
+      }
+      let valid = error.is_none();
+      let border_flags = StyleFlags {
+        enabled: comment.is_some(),
+        valid,
+        highlighted: self.focused_el == el,
+      };
+      input.set_style(
+        Style::from(StyleFlags {
+          enabled: true,
+          valid,
+          highlighted: edited,
+        })
+      );
+      input.set_block(
+        input.block().cloned().unwrap_or_default().border_style(Style::from(border_flags))
+      );
+      input.toggle_cursor(border_flags.highlighted);
+      input.render(area, buf);
+    }
+
+    Paragraph::new(
+      Line::from(
+        [
+          if self.focused_el == Focusable::List {
+            [
+              Shortcut::new("A", "Add", Color::Gray).to_spans(),
+              Vec::from([Span::from(" :: ").dark_gray()]),
+              Shortcut::new("Del", "Remove", Color::Gray).to_spans(),
+              Vec::from([Span::from(" :: ").dark_gray()]),
+            ].concat()
+          } else {
+            Vec::new()
+          },
+          Shortcut::new("Esc", "Back", Color::Gray).to_spans(),
+          Vec::from([Span::from(" :: ").dark_gray()]),
+          Shortcut::new("Ctrl+R", "Reset field", Color::Gray).to_spans(),
+        ].concat()
+      )
+    )
+      .right_aligned()
+      .render(footer_area, buf);
+  }
+}
@@ -0,0 +1,353 @@
+use std::sync::mpsc::Sender;
+use crate::{
+  app::{ app::Command, state::State, tag::{ RawFrameEditableTag, DUPLICATE_FRAME } },
+  ui::{
+    home::{ self, screen::HomeScreen },
+    shortcut::Shortcut,
+    text_area::TextAreaTrait,
+    ui_enums::Screen,
+    InputHandler,
+    StateDependentWidget,
+    UiState,
+    StyleFlags,
+  },
+};
+use crossterm::event::{ Event, KeyCode, KeyEventKind, KeyModifiers };
+use ratatui::{
+  buffer::Buffer,
+  layout::{ Constraint, Layout, Margin, Rect },
+  style::{ Color, Style, Stylize },
+  text::{ Line, Span },
+  widgets::{
+    Block,
+    BorderType,
+    Cell,
+    Paragraph,
+    Row,
+    StatefulWidget,
+    Table,
+    TableState,
+    Widget,
+  },
+};
+use tui_textarea::TextArea;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Focusable {
+  List,
+  Id,
+  Desc,
+  Value,
+}
+
+impl Focusable {
+  pub fn next(self) -> Self {
+    match self {
+      Focusable::List => Focusable::Id,
+      Focusable::Id => Focusable::Desc,
+      Focusable::Desc => Focusable::Value,
+      Focusable::Value => Focusable::List,
+    }
+  }
+  pub fn prev(self) -> Self {
+    match self {
+      Focusable::List => Focusable::Value,
+      Focusable::Id => Focusable::List,
+      Focusable::Desc => Focusable::Id,
+      Focusable::Value => Focusable::Desc,
+    }
+  }
+  /// Whether the input can be edited for the frame.
+  pub fn editable(self, frame: &RawFrameEditableTag) -> bool {
+    match self {
+      Focusable::List => true,
+      Focusable::Id => frame.is_new(),
+      Focusable::Desc => frame.is_txxx() && frame.is_text(),
+      Focusable::Value => frame.is_text(),
+    }
+  }
+}
+
+pub struct FramesScreen {
+  pub index: usize,
+  pub focused_el: Focusable,
+  /// Frame whose fields are shown, `None` when the tag has no frames.
+  pub selected: Option<usize>,
+  pub id_input: TextArea<'static>,
+  pub desc_input: TextArea<'static>,
+  pub value_input: TextArea<'static>,
+}
+
+fn frame_input(title: &'static str) -> TextArea<'static> {
+  let mut input = TextArea::new(Vec::new());
+  input.set_block(Block::bordered().border_type(BorderType::Rounded).title_top(title));
+  input.set_cursor_line_style(Style::new());
+  input
+}
+
+impl FramesScreen {
+  pub fn new(index: usize, frames: &[RawFrameEditableTag]) -> Self {
+    let mut new = Self {
+      index,
+      focused_el: Focusable::List,
+      selected: None,
+      id_input: frame_input("ID"),
+      desc_input: frame_input("Desc"),
+      value_input: frame_input("Value"),
+    };
+    new.select(if frames.is_empty() { None } else { Some(0) }, frames);
+    new
+  }
+  /// Shows another frame and fills the inputs with its values.
+  pub fn select(&mut self, j: Option<usize>, frames: &[RawFrameEditableTag]) {
+    let j = j.filter(|j| *j < frames.len());
+    self.selected = j;
+    let frame = j.map(|j| &frames[j]);
+    self.id_input.set_text(frame.map(|f| f.id.to_string()).unwrap_or_default());
+    self.desc_input.set_text(frame.map(|f| f.desc.to_string()).unwrap_or_default());
+    self.value_input.set_text(frame.map(|f| f.value.to_string()).unwrap_or_default());
+  }
+  pub fn input_mut(&mut self, el: Focusable) -> Option<&mut TextArea<'static>> {
+    match el {
+      Focusable::List => None,
+      Focusable::Id => Some(&mut self.id_input),
+      Focusable::Desc => Some(&mut self.desc_input),
+      Focusable::Value => Some(&mut self.value_input),
+    }
+  }
+}
+
+impl InputHandler for FramesScreen {
+  fn handle_input(
+    &self,
+    state: &State,
+    ui_state: &UiState,
+    event: Event,
+    sender: Sender<Command>
+  ) -> bool {
+    let frames = state.get_file(self.index).tags.frames.len();
+    match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+        match (key_event.code, key_event.modifiers, self.focused_el) {
+          (KeyCode::Esc, _, _) => {
+            sender.send(
+              Command::SetScreen(
+                Screen::Home(
+                  HomeScreen::new(
+                    home::screen::Focusable::Table(self.index),
+                    Some(&state.get_file(self.index).tags)
+                  )
+                )
+              )
+            );
+            true
+          }
+          (KeyCode::Down, KeyModifiers::CONTROL, f_el) | (KeyCode::PageDown, _, f_el) => {
+            sender.send(Command::FocusFramesElement(f_el.next()));
+            true
+          }
+          (KeyCode::Up, KeyModifiers::CONTROL, f_el) | (KeyCode::PageUp, _, f_el) => {
+            sender.send(Command::FocusFramesElement(f_el.prev()));
+            true
+          }
+          (KeyCode::Char('r' | 'к'), KeyModifiers::CONTROL, f_el) => {
+            sender.send(Command::ResetFramesScreenTag(f_el));
+            true
+          }
+          (KeyCode::Char('z' | 'я'), KeyModifiers::CONTROL, _) => {
+            sender.send(Command::Undo);
+            true
+          }
+          (KeyCode::Char('y' | 'н'), KeyModifiers::CONTROL, _) => {
+            sender.send(Command::Redo);
+            true
+          }
+          (code, KeyModifiers::NONE, Focusable::List) => {
+            let j = self.selected.unwrap_or_default();
+            match code {
+              KeyCode::Up if frames > 0 => {
+                sender.send(Command::SelectFrame(if j > 0 { j - 1 } else { frames - 1 }));
+              }
+              KeyCode::Down if frames > 0 => {
+                sender.send(Command::SelectFrame(if j + 1 >= frames { 0 } else { j + 1 }));
+              }
+              KeyCode::Enter if frames > 0 => {
+                sender.send(Command::FocusFramesElement(Focusable::Value));
+              }
+              KeyCode::Char('a') => {
+                sender.send(Command::AddFrame("TXXX".into()));
+              }
+              KeyCode::Char('t') => {
+                sender.send(Command::AddFrame(String::new()));
+              }
+              KeyCode::Delete if frames > 0 => {
+                sender.send(Command::RemoveFrame(j));
+              }
+              _ => {
+                return false;
+              }
+            }
+            true
+          }
+          _ => {
+            sender.send(Command::HandleFramesScreenInput(key_event, self.focused_el));
+            true
+          }
+        }
+      }
+      _ => false,
+    }
+  }
+}
+
+impl StateDependentWidget for FramesScreen {
+  fn render_from_state(&self, area: Rect, buf: &mut Buffer, state: &State, ui_state: &UiState)
+    where Self: Sized
+  {
+    let [header_area, table_area, fields_area, footer_area] = Layout::vertical([
+      Constraint::Length(2),
+      Constraint::Fill(1),
+      Constraint::Length(3),
+      Constraint::Length(1),
+    ]).areas(area);
+    let footer_area = footer_area.inner(Margin::new(1, 0));
+    let [id_area, desc_area, value_area] = Layout::horizontal([
+      Constraint::Length(8),
+      Constraint::Length(28),
+      Constraint::Fill(1),
+    ]).areas(fields_area);
+
+    Paragraph::new(vec![Line::from("All Frames"), Line::from("").gray()])
+      .centered()
+      .render(header_area, buf);
+
+    let tags = &state.get_file(self.index).tags;
+    let frame = self.selected.and_then(|j| tags.frames.get(j).map(|f| (j, f)));
+
+    {
+      let focused = self.focused_el == Focusable::List;
+      let rows = tags.frames
+        .iter()
+        .enumerate()
+        .map(|(j, frame)| {
+          let mut style = Style::from(StyleFlags {
+            enabled: !frame.managed(),
+            valid: !tags.frame_conflict(j) && frame.value.valid(),
+            highlighted: frame.edited(),
+          });
+          if frame.removed() {
+            style = style.crossed_out();
+          }
+          let name = if frame.is_txxx() { frame.desc.to_string() } else { frame.name() };
+          // removed frames keep showing what is removed
+          let value = frame.value.current().or(frame.value.original.as_ref());
+          Row::new(
+            vec![
+              Cell::from(frame.id.to_string()),
+              Cell::from(name).dark_gray(),
+              Cell::from(value.and_then(|v| v.lines().next()).unwrap_or_default().to_string()),
+            ]
+          ).style(style)
+        })
+        .collect::<Vec<_>>();
+      let hint = match frame {
+        None => " No frames ",
+        Some((_, f)) if f.managed() => " Edited on the home screen ",
+        Some((_, f)) if !f.is_text() => " Can only be removed ",
+        _ => "",
+      };
+      let widths = [Constraint::Length(4), Constraint::Fill(1), Constraint::Fill(2)];
+      let table = Table::new(rows, widths)
+        .column_spacing(2)
+        .row_highlight_style(Style::new().on_dark_gray().bold())
+        .block(
+          Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_top("Frames")
+            .title_bottom(Line::from(hint).dark_gray().centered())
+            .border_style(
+              Style::from(StyleFlags {
+                enabled: true,
+                valid: true,
+                highlighted: focused,
+              })
+            )
+        );
+      let mut table_state = TableState::default().with_selected(self.selected);
+      <Table as StatefulWidget>::render(table, table_area, buf, &mut table_state);
+    }
+
+    for (el, area) in [
+      (Focusable::Id, id_area),
+      (Focusable::Desc, desc_area),
+      (Focusable::Value, value_area),
+    ] {
+      let mut input = match el {
+        Focusable::Id => self.id_input.clone(),
+        Focusable::Desc => self.desc_input.clone(),
+        _ => self.value_input.clone(),
+      };
+      let edited = frame.is_some_and(|(_, f)| {
+        match el {
+          Focusable::Id => f.id.edited() && !f.is_new(),
+          Focusable::Desc => f.desc.edited(),
+          _ => f.value.edited(),
+        }
+      });
+      let error = frame.and_then(|(j, f)| {
+        match el {
+          Focusable::Value => f.value.error(),
+          Focusable::Id => {
+            f.id_error().or_else(|| tags.frame_conflict(j).then(|| DUPLICATE_FRAME.into()))
+          }
+          _ => (f.valid_id() && tags.frame_conflict(j)).then(|| DUPLICATE_FRAME.into()),
+        }
+      });
+      if let Some(error) = &error {
+        input.set_block(input.block().cloned().unwrap_or_default().title_bottom(error.clone()));
+      }
+      let valid = error.is_none();
+      let border_flags = StyleFlags {
+        enabled: frame.is_some_and(|(_, f)| el.editable(f)),
+        valid,
+        highlighted: self.focused_el == el,
+      };
+      input.set_style(
+        Style::from(StyleFlags {
+          enabled: true,
+          valid,
+          highlighted: edited,
+        })
+      );
+      input.set_block(
+        input.block().cloned().unwrap_or_default().border_style(Style::from(border_flags))
+      );
+      input.toggle_cursor(border_flags.highlighted);
+      input.render(area, buf);
+    }
+
+    Paragraph::new(
+      Line::from(
+        [
+          if self.focused_el == Focusable::List {
+            [
+              Shortcut::new("A", "Add TXXX", Color::Gray).to_spans(),
+              Vec::from([Span::from(" :: ").dark_gray()]),
+              Shortcut::new("T", "Add text frame", Color::Gray).to_spans(),
+              Vec::from([Span::from(" :: ").dark_gray()]),
+              Shortcut::new("Del", "Remove", Color::Gray).to_spans(),
+              Vec::from([Span::from(" :: ").dark_gray()]),
+            ].concat()
+          } else {
+            Vec::new()
+          },
+          Shortcut::new("Esc", "Back", Color::Gray).to_spans(),
+          Vec::from([Span::from(" :: ").dark_gray()]),
+          Shortcut::new("Ctrl+R", "Reset field", Color::Gray).to_spans(),
+        ].concat()
+      )
+    )
+      .right_aligned()
+      .render(footer_area, buf);
+  }
+}
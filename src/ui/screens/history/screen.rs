@@ -0,0 +1,243 @@
+use std::{ fs, sync::mpsc::Sender };
+use crate::{
+  app::{ app::Command, backup::{ Backup, Backups }, state::State, tag::Change },
+  ui::{
+    home::screen::{ Focusable as HomeFocusable, HomeScreen },
+    modals::save_tags::change_lines,
+    shortcut::Shortcut,
+    ui_enums::Screen,
+    InputHandler,
+    StateDependentWidget,
+    StyleFlags,
+    UiState,
+  },
+};
+use crossterm::event::{ Event, KeyCode, KeyEventKind };
+use ratatui::{
+  buffer::Buffer,
+  layout::{ Constraint, Layout, Margin, Rect },
+  style::{ Color, Style, Stylize },
+  text::{ Line, Span },
+  widgets::{
+    Block,
+    BorderType,
+    List,
+    ListItem,
+    ListState,
+    Paragraph,
+    StatefulWidget,
+    Widget,
+    Wrap,
+  },
+};
+
+/// Lines skipped by PageUp and PageDown.
+const PAGE: usize = 10;
+
+/// Earlier tags of a file kept on save, each with what restoring it would change.
+pub struct HistoryScreen {
+  pub index: usize,
+  /// Backups of the file with their changes against the tags on disk, the latest first.
+  versions: Vec<(Backup, Vec<Change>)>,
+  pub cursor: usize,
+  /// Lines of the changes scrolled past.
+  pub scroll: usize,
+  /// Time of the backup restored last.
+  pub restored: Option<String>,
+}
+
+impl HistoryScreen {
+  /// Lists the backups of the file `index` at `path`, compared with the file as it is now.
+  pub fn new(index: usize, path: &str, backups: &Backups) -> Self {
+    let current = fs::read(path).unwrap_or_default();
+    Self {
+      index,
+      versions: backups
+        .list(path)
+        .into_iter()
+        .map(|backup| {
+          let changes = backup.changes(&current).unwrap_or_default();
+          (backup, changes)
+        })
+        .collect(),
+      cursor: 0,
+      scroll: 0,
+      restored: None,
+    }
+  }
+}
+
+impl InputHandler for HistoryScreen {
+  fn handle_input(
+    &self,
+    state: &State,
+    ui_state: &UiState,
+    event: Event,
+    sender: Sender<Command>
+  ) -> bool {
+    let versions = self.versions.len();
+    let lines = self.versions
+      .get(self.cursor)
+      .map_or(0, |(_, changes)| changes.iter().map(|c| change_lines(c).len()).sum());
+    match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+        match key_event.code {
+          KeyCode::Esc => {
+            let tags = &state.get_file(self.index).tags;
+            let home = HomeScreen::new(HomeFocusable::Table(self.index), Some(tags));
+            sender.send(Command::SetScreen(Screen::Home(home)));
+            true
+          }
+          KeyCode::Up if versions > 0 => {
+            let j = if self.cursor == 0 { versions - 1 } else { self.cursor - 1 };
+            sender.send(Command::SetHistoryCursor(j));
+            true
+          }
+          KeyCode::Down if versions > 0 => {
+            sender.send(Command::SetHistoryCursor((self.cursor + 1) % versions));
+            true
+          }
+          KeyCode::PageUp => {
+            sender.send(Command::ScrollHistory(self.scroll.saturating_sub(PAGE)));
+            true
+          }
+          KeyCode::PageDown => {
+            let last = lines.saturating_sub(1);
+            sender.send(Command::ScrollHistory((self.scroll + PAGE).min(last)));
+            true
+          }
+          // restoring reloads the file, edits would be lost
+          KeyCode::Enter | KeyCode::Char('r' | 'к') if
+            !state.get_file(self.index).tags.edited()
+          => {
+            let Some((backup, _)) = self.versions.get(self.cursor) else {
+              return false;
+            };
+            sender.send(Command::RestoreBackup(self.index, backup.clone()));
+            true
+          }
+          _ => false,
+        }
+      }
+      _ => false,
+    }
+  }
+}
+
+impl StateDependentWidget for HistoryScreen {
+  fn render_from_state(&self, area: Rect, buf: &mut Buffer, state: &State, ui_state: &UiState)
+    where Self: Sized
+  {
+    let [header_area, main_area, footer_area] = Layout::vertical([
+      Constraint::Length(2),
+      Constraint::Fill(1),
+      Constraint::Length(1),
+    ]).areas(area);
+    let footer_area = footer_area.inner(Margin::new(1, 0));
+    let [list_area, changes_area] = Layout::horizontal([
+      Constraint::Length(32),
+      Constraint::Fill(1),
+    ]).areas(main_area);
+    let [title_area, subtitle_area] = Layout::vertical([
+      Constraint::Length(1),
+      Constraint::Length(1),
+    ]).areas(header_area);
+
+    let file = state.get_file(self.index);
+    let edited = file.tags.edited();
+    Line::from(format!("History of {}", file.name)).centered().render(title_area, buf);
+    match (&self.restored, edited) {
+      (_, true) => Line::from("Save or revert the edits of the file to restore a backup").yellow(),
+      (Some(time), false) => Line::from(format!("Restored the tags of {}", time)).green(),
+      (None, false) => Line::from("Tags are backed up before every save").dark_gray(),
+    }
+      .centered()
+      .render(subtitle_area, buf);
+
+    {
+      let items = self.versions
+        .iter()
+        .map(|(backup, changes)| {
+          ListItem::new(
+            Line::from(
+              Vec::from([
+                Span::from(backup.time.format("%Y-%m-%d %H:%M:%S").to_string()),
+                match changes.len() {
+                  0 => Span::from(" same").dark_gray(),
+                  n => Span::from(format!(" {} changes", n)).dark_gray(),
+                },
+              ])
+            )
+          )
+        })
+        .collect::<Vec<_>>();
+      let list = List::new(items)
+        .highlight_style(Style::new().on_dark_gray().bold())
+        .block(
+          Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_top("Backups")
+            .title_bottom(
+              if self.versions.is_empty() {
+                Line::from(" No backups yet ").dark_gray().centered()
+              } else {
+                Line::default()
+              }
+            )
+            .border_style(
+              Style::from(StyleFlags {
+                enabled: true,
+                valid: true,
+                highlighted: true,
+              })
+            )
+        );
+      let selected = (!self.versions.is_empty()).then(|| self.cursor.min(self.versions.len() - 1));
+      let mut list_state = ListState::default().with_selected(selected);
+      <List as StatefulWidget>::render(list, list_area, buf, &mut list_state);
+    }
+
+    {
+      let version = self.versions.get(self.cursor);
+      let lines = version
+        .map(|(_, changes)| changes.iter().flat_map(change_lines).skip(self.scroll).collect())
+        .unwrap_or_default();
+      Paragraph::new::<Vec<Line>>(lines)
+        .wrap(Wrap::default())
+        .block(
+          Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_top("Restoring changes")
+            .title_bottom(
+              match version {
+                Some((_, changes)) if changes.is_empty() => {
+                  Line::from(" Same as the file ").dark_gray().centered()
+                }
+                _ => Line::default(),
+              }
+            )
+        )
+        .render(changes_area, buf);
+    }
+
+    Paragraph::new(
+      Line::from(
+        [
+          if edited || self.versions.is_empty() {
+            Vec::new()
+          } else {
+            [
+              Shortcut::new("Enter", "Restore", Color::Yellow).to_spans(),
+              Vec::from([Span::from(" :: ").dark_gray()]),
+            ].concat()
+          },
+          Shortcut::new("PgUp/PgDn", "Scroll", Color::Gray).to_spans(),
+          Vec::from([Span::from(" :: ").dark_gray()]),
+          Shortcut::new("Esc", "Back", Color::Gray).to_spans(),
+        ].concat()
+      )
+    )
+      .right_aligned()
+      .render(footer_area, buf);
+  }
+}
@@ -1,12 +1,25 @@
 use std::sync::mpsc::Sender;
 
 use crate::{
-  app::{ app::{ App, Command }, state::{ State, Source }, tag::SongTags },
+  app::{ app::{ App, Command }, state::{ State, Source }, tag::{ EditableState, EditableTag, Id3v1Field, PictureEditableTag, SongTags } },
   info::{ PROJECT_DESC, PROJECT_NAME },
   ui::{
     block::BlockTrait,
+    cover::{ CoverArt, GraphicsProtocol },
+    comments::screen::CommentsScreen,
+    frames::screen::FramesScreen,
+    history::screen::HistoryScreen,
+    log::screen::LogScreen,
     lyrics::screen::LyricsScreen,
-    modals::{ help::HelpModal, modal::{ self, enums::Modal }, save_tags::ConfirmSaveTagsModal },
+    pending::screen::PendingScreen,
+    settings::screen::{ version_name, SettingsScreen },
+    modals::{
+      genre::GenreModal,
+      help::HelpModal,
+      modal::{ self, enums::Modal },
+      path::{ PathModal, PathPurpose },
+      save_tags::ConfirmSaveTagsModal,
+    },
     shortcut::Shortcut,
     text_area::TextAreaTrait,
     ui::{ ui_enums, Ui },
@@ -20,7 +33,7 @@ use crate::{
   },
 };
 use crossterm::event::{ Event, KeyCode, KeyEvent, KeyModifiers };
-use id3::TagLike;
+use id3::{ frame::PictureType, TagLike };
 use ratatui::{
   buffer::Buffer,
   layout::{ Constraint, Flex, Layout, Margin, Rect },
@@ -31,6 +44,7 @@ use ratatui::{
     Block,
     BorderType,
     Cell,
+    Gauge,
     List,
     Paragraph,
     Row,
@@ -48,9 +62,93 @@ use uuid::Uuid;
 pub enum EditorFocusable {
   TitleInput,
   ArtistInput,
-  YearInput,
+  AlbumInput,
+  AlbumArtistInput,
+  DateInput,
+  OriginalDateInput,
+  ReleaseDateInput,
   GenreInput,
+  TrackInput,
+  DiscInput,
   LyricsButton,
+  CommentsButton,
+  ArtworkList,
+}
+
+impl EditorFocusable {
+  /// Editor elements in the order they are cycled through.
+  pub const ALL: [EditorFocusable; 13] = [
+    EditorFocusable::TitleInput,
+    EditorFocusable::ArtistInput,
+    EditorFocusable::AlbumInput,
+    EditorFocusable::AlbumArtistInput,
+    EditorFocusable::DateInput,
+    EditorFocusable::OriginalDateInput,
+    EditorFocusable::ReleaseDateInput,
+    EditorFocusable::GenreInput,
+    EditorFocusable::TrackInput,
+    EditorFocusable::DiscInput,
+    EditorFocusable::LyricsButton,
+    EditorFocusable::CommentsButton,
+    EditorFocusable::ArtworkList,
+  ];
+  pub fn next(self) -> Self {
+    let i = Self::ALL.iter().position(|el| *el == self).unwrap();
+    Self::ALL[(i + 1) % Self::ALL.len()]
+  }
+  pub fn prev(self) -> Self {
+    let i = Self::ALL.iter().position(|el| *el == self).unwrap();
+    Self::ALL[(i + Self::ALL.len() - 1) % Self::ALL.len()]
+  }
+  /// The text tag edited by this element, `None` for buttons.
+  pub fn tag(self, tags: &SongTags) -> Option<&EditableTag> {
+    match self {
+      EditorFocusable::TitleInput => Some(&tags.title),
+      EditorFocusable::ArtistInput => Some(&tags.artist),
+      EditorFocusable::AlbumInput => Some(&tags.album),
+      EditorFocusable::AlbumArtistInput => Some(&tags.album_artist),
+      EditorFocusable::DateInput => Some(&tags.date),
+      EditorFocusable::OriginalDateInput => Some(&tags.original_date),
+      EditorFocusable::ReleaseDateInput => Some(&tags.release_date),
+      EditorFocusable::GenreInput => Some(&tags.genre),
+      EditorFocusable::TrackInput => Some(&tags.track),
+      EditorFocusable::DiscInput => Some(&tags.disc),
+      EditorFocusable::LyricsButton | EditorFocusable::CommentsButton => None,
+      EditorFocusable::ArtworkList => None,
+    }
+  }
+  pub fn tag_mut(self, tags: &mut SongTags) -> Option<&mut EditableTag> {
+    match self {
+      EditorFocusable::TitleInput => Some(&mut tags.title),
+      EditorFocusable::ArtistInput => Some(&mut tags.artist),
+      EditorFocusable::AlbumInput => Some(&mut tags.album),
+      EditorFocusable::AlbumArtistInput => Some(&mut tags.album_artist),
+      EditorFocusable::DateInput => Some(&mut tags.date),
+      EditorFocusable::OriginalDateInput => Some(&mut tags.original_date),
+      EditorFocusable::ReleaseDateInput => Some(&mut tags.release_date),
+      EditorFocusable::GenreInput => Some(&mut tags.genre),
+      EditorFocusable::TrackInput => Some(&mut tags.track),
+      EditorFocusable::DiscInput => Some(&mut tags.disc),
+      EditorFocusable::LyricsButton | EditorFocusable::CommentsButton => None,
+      EditorFocusable::ArtworkList => None,
+    }
+  }
+  /// The ID3v1 field shown under the element's input.
+  pub fn id3v1_field(self) -> Option<Id3v1Field> {
+    match self {
+      EditorFocusable::TitleInput => Some(Id3v1Field::Title),
+      EditorFocusable::ArtistInput => Some(Id3v1Field::Artist),
+      EditorFocusable::AlbumInput => Some(Id3v1Field::Album),
+      EditorFocusable::DateInput => Some(Id3v1Field::Year),
+      EditorFocusable::GenreInput => Some(Id3v1Field::Genre),
+      EditorFocusable::TrackInput => Some(Id3v1Field::Track),
+      _ => None,
+    }
+  }
+  /// Why the current value of the element's tag cannot be saved.
+  pub fn error(self, tags: &SongTags) -> Option<String> {
+    self.tag(tags).and_then(|t| t.0.error())
+  }
 }
 
 #[derive(PartialEq, Clone, Copy, Debug)]
@@ -65,12 +163,30 @@ pub struct HomeScreen {
   pub search_input: TextArea<'static>,
   pub title_input: TextArea<'static>,
   pub artist_input: TextArea<'static>,
-  pub year_input: TextArea<'static>,
+  pub album_input: TextArea<'static>,
+  pub album_artist_input: TextArea<'static>,
+  pub date_input: TextArea<'static>,
+  pub original_date_input: TextArea<'static>,
+  pub release_date_input: TextArea<'static>,
   pub genre_input: TextArea<'static>,
+  pub track_input: TextArea<'static>,
+  pub disc_input: TextArea<'static>,
+  pub selected_picture: usize,
+}
+
+/// Placeholder of an input whose value differs between the selected files.
+const MIXED: &str = "<mixed>";
+
+fn editor_input(title: &'static str, text: Option<String>) -> TextArea<'static> {
+  let mut input = TextArea::new(Vec::from([text.unwrap_or_default()]));
+  input.set_block(Block::bordered().border_type(BorderType::Rounded).title_top(title));
+  input.set_cursor_line_style(Style::new());
+  input
 }
 
 impl HomeScreen {
   pub fn new(selection: Focusable, tags: Option<&SongTags>) -> Self {
+    let text = |el: EditorFocusable| tags.and_then(|t| el.tag(t)).map(|t| t.0.to_string());
     Self {
       focused_el: selection,
       search_input: {
@@ -79,38 +195,65 @@ impl HomeScreen {
         input.set_cursor_line_style(Style::new());
         input
       },
-      title_input: {
-        let mut input = TextArea::new(
-          Vec::from([tags.map(|t| t.title.0.to_string()).unwrap_or_default()])
-        );
-        input.set_block(Block::bordered().border_type(BorderType::Rounded).title_top("Title"));
-        input.set_cursor_line_style(Style::new());
-        input
-      },
-      artist_input: {
-        let mut input = TextArea::new(
-          Vec::from([tags.map(|t| t.artist.0.to_string()).unwrap_or_default()])
-        );
-        input.set_block(Block::bordered().border_type(BorderType::Rounded).title_top("Artist"));
-        input.set_cursor_line_style(Style::new());
-        input
-      },
-      genre_input: {
-        let mut input = TextArea::new(
-          Vec::from([tags.map(|t| t.genre.0.to_string()).unwrap_or_default()])
-        );
-        input.set_block(Block::bordered().border_type(BorderType::Rounded).title_top("Genre"));
-        input.set_cursor_line_style(Style::new());
-        input
-      },
-      year_input: {
-        let mut input = TextArea::new(
-          Vec::from([tags.map(|t| t.year.0.to_string()).unwrap_or_default()])
-        );
-        input.set_block(Block::bordered().border_type(BorderType::Rounded).title_top("Year"));
-        input.set_cursor_line_style(Style::new());
-        input
-      },
+      title_input: editor_input("Title", text(EditorFocusable::TitleInput)),
+      artist_input: editor_input("Artist", text(EditorFocusable::ArtistInput)),
+      album_input: editor_input("Album", text(EditorFocusable::AlbumInput)),
+      album_artist_input: editor_input("Album Artist", text(EditorFocusable::AlbumArtistInput)),
+      date_input: editor_input("Date", text(EditorFocusable::DateInput)),
+      original_date_input: editor_input("Original", text(EditorFocusable::OriginalDateInput)),
+      release_date_input: editor_input("Released", text(EditorFocusable::ReleaseDateInput)),
+      genre_input: editor_input("Genre", text(EditorFocusable::GenreInput)),
+      track_input: editor_input("Track", text(EditorFocusable::TrackInput)),
+      disc_input: editor_input("Disc", text(EditorFocusable::DiscInput)),
+      selected_picture: 0,
+    }
+  }
+  pub fn input(&self, el: EditorFocusable) -> Option<&TextArea<'static>> {
+    match el {
+      EditorFocusable::TitleInput => Some(&self.title_input),
+      EditorFocusable::ArtistInput => Some(&self.artist_input),
+      EditorFocusable::AlbumInput => Some(&self.album_input),
+      EditorFocusable::AlbumArtistInput => Some(&self.album_artist_input),
+      EditorFocusable::DateInput => Some(&self.date_input),
+      EditorFocusable::OriginalDateInput => Some(&self.original_date_input),
+      EditorFocusable::ReleaseDateInput => Some(&self.release_date_input),
+      EditorFocusable::GenreInput => Some(&self.genre_input),
+      EditorFocusable::TrackInput => Some(&self.track_input),
+      EditorFocusable::DiscInput => Some(&self.disc_input),
+      EditorFocusable::LyricsButton | EditorFocusable::CommentsButton => None,
+      EditorFocusable::ArtworkList => None,
+    }
+  }
+  pub fn input_mut(&mut self, el: EditorFocusable) -> Option<&mut TextArea<'static>> {
+    match el {
+      EditorFocusable::TitleInput => Some(&mut self.title_input),
+      EditorFocusable::ArtistInput => Some(&mut self.artist_input),
+      EditorFocusable::AlbumInput => Some(&mut self.album_input),
+      EditorFocusable::AlbumArtistInput => Some(&mut self.album_artist_input),
+      EditorFocusable::DateInput => Some(&mut self.date_input),
+      EditorFocusable::OriginalDateInput => Some(&mut self.original_date_input),
+      EditorFocusable::ReleaseDateInput => Some(&mut self.release_date_input),
+      EditorFocusable::GenreInput => Some(&mut self.genre_input),
+      EditorFocusable::TrackInput => Some(&mut self.track_input),
+      EditorFocusable::DiscInput => Some(&mut self.disc_input),
+      EditorFocusable::LyricsButton | EditorFocusable::CommentsButton => None,
+      EditorFocusable::ArtworkList => None,
+    }
+  }
+  /// Fills editor inputs with the values shared by `tags`, inputs of values that differ are left
+  /// empty with a placeholder. Inputs are cleared when there are no tags.
+  pub fn set_inputs(&mut self, tags: &[&SongTags]) {
+    for el in EditorFocusable::ALL {
+      let mut texts = tags.iter().filter_map(|t| el.tag(t)).map(|t| t.0.to_string());
+      let text = texts.next();
+      let mixed = texts.any(|t| Some(&t) != text.as_ref());
+      if let Some(input) = self.input_mut(el) {
+        input.set_placeholder_text(if mixed { MIXED } else { "" });
+        match text.filter(|_| !mixed) {
+          Some(text) => input.set_text(text),
+          None => input.clear(),
+        }
+      }
     }
   }
 }
@@ -139,28 +282,36 @@ impl InputHandler for HomeScreen {
               }
               Focusable::Editor(i, editor_selection) => {
                 sender.send(
-                  Command::FocusHomeElement(match editor_selection {
-                    EditorFocusable::TitleInput =>
-                      Focusable::Editor(i, EditorFocusable::LyricsButton),
-                    EditorFocusable::ArtistInput =>
-                      Focusable::Editor(i, EditorFocusable::TitleInput),
-                    EditorFocusable::YearInput =>
-                      Focusable::Editor(i, EditorFocusable::ArtistInput),
-                    EditorFocusable::GenreInput => Focusable::Editor(i, EditorFocusable::YearInput),
-                    EditorFocusable::LyricsButton =>
-                      Focusable::Editor(i, EditorFocusable::GenreInput),
-                  })
+                  Command::FocusHomeElement(Focusable::Editor(i, editor_selection.prev()))
                 );
                 true
               }
               Focusable::Search => false,
             }
+          (KeyCode::Up, KeyModifiers::SHIFT, Focusable::Table(i)) => {
+            if let Some(prev) = state.next_shown(i, false) {
+              sender.send(Command::ExtendSelection(i, prev));
+            }
+            true
+          }
+          (KeyCode::Down, KeyModifiers::SHIFT, Focusable::Table(i)) => {
+            if let Some(next) = state.next_shown(i, true) {
+              sender.send(Command::ExtendSelection(i, next));
+            }
+            true
+          }
+          (KeyCode::Char(' '), KeyModifiers::NONE, Focusable::Table(i)) => {
+            sender.send(Command::ToggleSelected(i));
+            true
+          }
+          (KeyCode::Char('a' | 'ф'), KeyModifiers::CONTROL, Focusable::Table(_)) => {
+            sender.send(Command::SelectShown);
+            true
+          }
           (KeyCode::Up, KeyModifiers::NONE, Focusable::Table(i)) => {
-            sender.send(
-              Command::FocusHomeElement(
-                Focusable::Table(if i > 0 { i - 1 } else { shown_indexes - 1 })
-              )
-            );
+            if let Some(prev) = state.next_shown(i, false) {
+              sender.send(Command::FocusHomeElement(Focusable::Table(prev)));
+            }
             true
           }
           (KeyCode::End, _, f_el) | (KeyCode::Right, KeyModifiers::CONTROL, f_el) =>
@@ -176,7 +327,15 @@ impl InputHandler for HomeScreen {
                   EditorFocusable::LyricsButton => {
                     sender.send(
                       Command::SetScreen(
-                        Screen::Lyrics(LyricsScreen::new(i, state.get_file(i).tags.lyrics.clone()))
+                        Screen::Lyrics(LyricsScreen::new(i, &state.get_file(i).tags.lyrics))
+                      )
+                    );
+                    true
+                  }
+                  EditorFocusable::CommentsButton => {
+                    sender.send(
+                      Command::SetScreen(
+                        Screen::Comments(CommentsScreen::new(i, &state.get_file(i).tags.comments))
                       )
                     );
                     true
@@ -188,34 +347,22 @@ impl InputHandler for HomeScreen {
           (KeyCode::Down, KeyModifiers::CONTROL, f_el) | (KeyCode::PageDown, _, f_el) =>
             match f_el {
               Focusable::Search if shown_indexes > 0 => {
-                sender.send(Command::FocusHomeElement(Focusable::Table(0)));
+                sender.send(Command::FocusHomeElement(Focusable::Table(state.shown_indexes[0])));
                 true
               }
               Focusable::Table(..) => false,
               Focusable::Editor(i, editor_selection) => {
                 sender.send(
-                  Command::FocusHomeElement(match editor_selection {
-                    EditorFocusable::TitleInput =>
-                      Focusable::Editor(i, EditorFocusable::ArtistInput),
-                    EditorFocusable::ArtistInput =>
-                      Focusable::Editor(i, EditorFocusable::YearInput),
-                    EditorFocusable::YearInput => Focusable::Editor(i, EditorFocusable::GenreInput),
-                    EditorFocusable::GenreInput =>
-                      Focusable::Editor(i, EditorFocusable::LyricsButton),
-                    EditorFocusable::LyricsButton =>
-                      Focusable::Editor(i, EditorFocusable::TitleInput),
-                  })
+                  Command::FocusHomeElement(Focusable::Editor(i, editor_selection.next()))
                 );
                 true
               }
               _ => false,
             }
           (KeyCode::Down, KeyModifiers::NONE, Focusable::Table(i)) => {
-            sender.send(
-              Command::FocusHomeElement(
-                Focusable::Table(if i == shown_indexes - 1 { 0 } else { i + 1 })
-              )
-            );
+            if let Some(next) = state.next_shown(i, true) {
+              sender.send(Command::FocusHomeElement(Focusable::Table(next)));
+            }
             true
           }
           (KeyCode::Home, _, f_el) | (KeyCode::Left, KeyModifiers::CONTROL, f_el) =>
@@ -226,10 +373,23 @@ impl InputHandler for HomeScreen {
               }
               _ => false,
             }
+          (KeyCode::Enter, _, Focusable::Editor(i, EditorFocusable::GenreInput)) => {
+            // empty when the selected files differ
+            let current = self.genre_input.lines()[0].clone();
+            sender.send(
+              Command::OpenModal(
+                Modal::Genre(GenreModal::new(i, current, state.library_genres()))
+              )
+            );
+            true
+          }
           (
             KeyCode::Enter,
             _,
-            f_el @ (Focusable::Table(..) | Focusable::Editor(_, EditorFocusable::LyricsButton)),
+            f_el @ (
+              Focusable::Table(..) |
+              Focusable::Editor(_, EditorFocusable::LyricsButton | EditorFocusable::CommentsButton)
+            ),
           ) => {
             match f_el {
               Focusable::Table(i) => {
@@ -243,7 +403,15 @@ impl InputHandler for HomeScreen {
                   EditorFocusable::LyricsButton => {
                     sender.send(
                       Command::SetScreen(
-                        Screen::Lyrics(LyricsScreen::new(i, state.get_file(i).tags.lyrics.clone()))
+                        Screen::Lyrics(LyricsScreen::new(i, &state.get_file(i).tags.lyrics))
+                      )
+                    );
+                    true
+                  }
+                  EditorFocusable::CommentsButton => {
+                    sender.send(
+                      Command::SetScreen(
+                        Screen::Comments(CommentsScreen::new(i, &state.get_file(i).tags.comments))
                       )
                     );
                     true
@@ -258,16 +426,99 @@ impl InputHandler for HomeScreen {
             KeyModifiers::CONTROL,
             Focusable::Table(i) | Focusable::Editor(i, _),
           ) => {
-            let tags = &state.get_file(i).tags;
-            if tags.edited() {
+            let edited = state
+              .targets(i)
+              .into_iter()
+              .filter(|&i| state.get_file(i).tags.edited())
+              .collect::<Vec<_>>();
+            let tags = edited.iter().map(|&i| &state.get_file(i).tags).collect::<Vec<_>>();
+            if !tags.is_empty() && tags.iter().all(|t| t.valid()) {
+              let title = match tags.as_slice() {
+                [t] => t.title.0.to_string(),
+                _ => format!("{} files", tags.len()),
+              };
+              let has_id3v1 = tags.iter().any(|t| t.id3v1.present || t.id3v1.edited());
+              let changes = edited
+                .iter()
+                .map(|&i| (state.get_file(i).name.clone(), state.get_file(i).tags.changes()))
+                .collect::<Vec<_>>();
               sender.send(
                 Command::OpenModal(
-                  Modal::ConfirmSaveTags(ConfirmSaveTagsModal::new(i, tags.title.0.to_string()))
+                  Modal::ConfirmSaveTags(
+                    ConfirmSaveTagsModal::new(edited, title, has_id3v1, &changes)
+                  )
                 )
               );
             }
             true
           }
+          (
+            KeyCode::Char('f' | 'а'),
+            KeyModifiers::CONTROL,
+            Focusable::Table(i) | Focusable::Editor(i, _),
+          ) => {
+            let frames = &state.get_file(i).tags.frames;
+            sender.send(Command::SetScreen(Screen::Frames(FramesScreen::new(i, frames))));
+            true
+          }
+          (
+            KeyCode::Char('b' | 'и'),
+            KeyModifiers::CONTROL,
+            Focusable::Table(i) | Focusable::Editor(i, _),
+          ) => {
+            let path = state.get_file(i).tags.path();
+            let screen = HistoryScreen::new(i, path, &state.backups);
+            sender.send(Command::SetScreen(Screen::History(screen)));
+            true
+          }
+          (KeyCode::Char('p' | 'з'), KeyModifiers::CONTROL, _) => {
+            sender.send(Command::SetScreen(Screen::Pending(PendingScreen::new())));
+            true
+          }
+          (KeyCode::Char('l' | 'д'), KeyModifiers::CONTROL, _) => {
+            sender.send(Command::SetScreen(Screen::Log(LogScreen::new())));
+            true
+          }
+          (KeyCode::Char('o' | 'щ'), KeyModifiers::CONTROL, f_el) => {
+            let index = match f_el {
+              Focusable::Table(i) | Focusable::Editor(i, _) => Some(i),
+              Focusable::Search => None,
+            };
+            sender.send(Command::SetScreen(Screen::Settings(SettingsScreen::new(index))));
+            true
+          }
+          (
+            KeyCode::Char('u' | 'г'),
+            KeyModifiers::CONTROL,
+            Focusable::Table(i) | Focusable::Editor(i, _),
+          ) => {
+            sender.send(Command::CopyToId3v1(i));
+            true
+          }
+          (
+            KeyCode::Char('d' | 'в'),
+            KeyModifiers::CONTROL,
+            Focusable::Table(i) | Focusable::Editor(i, _),
+          ) => {
+            sender.send(Command::CopyFromId3v1(i));
+            true
+          }
+          (
+            KeyCode::Char('z' | 'я'),
+            KeyModifiers::CONTROL,
+            Focusable::Table(_) | Focusable::Editor(..),
+          ) => {
+            sender.send(Command::Undo);
+            true
+          }
+          (
+            KeyCode::Char('y' | 'н'),
+            KeyModifiers::CONTROL,
+            Focusable::Table(_) | Focusable::Editor(..),
+          ) => {
+            sender.send(Command::Redo);
+            true
+          }
           (KeyCode::Char('h' | 'р'), KeyModifiers::CONTROL, _) => {
             sender.send(Command::OpenModal(Modal::Help(HelpModal)));
             true
@@ -276,6 +527,58 @@ impl InputHandler for HomeScreen {
             sender.send(Command::ResetHomeScreenTag(f_el));
             true
           }
+          (code, KeyModifiers::NONE, Focusable::Editor(i, EditorFocusable::ArtworkList)) => {
+            let pictures = state.get_file(i).tags.pictures.len();
+            let j = self.selected_picture;
+            match code {
+              KeyCode::Up if pictures > 0 => {
+                sender.send(Command::SelectPicture(if j > 0 { j - 1 } else { pictures - 1 }));
+              }
+              KeyCode::Down if pictures > 0 => {
+                sender.send(Command::SelectPicture(if j + 1 >= pictures { 0 } else { j + 1 }));
+              }
+              KeyCode::Char('a') => {
+                sender.send(
+                  Command::OpenModal(Modal::Path(PathModal::new(PathPurpose::ImportPicture(i), "")))
+                );
+              }
+              KeyCode::Char('r') if j < pictures => {
+                sender.send(
+                  Command::OpenModal(
+                    Modal::Path(PathModal::new(PathPurpose::ReplacePicture(i, j), ""))
+                  )
+                );
+              }
+              KeyCode::Char('e') if j < pictures => {
+                let file = state.get_file(i);
+                let extension = file.tags.pictures[j].0
+                  .current()
+                  .and_then(|p| p.mime_type.strip_prefix("image/"))
+                  .map(|ext| if ext == "jpeg" { "jpg" } else { ext })
+                  .unwrap_or("jpg");
+                let path = format!(
+                  "{}.{}",
+                  file.path.strip_suffix(".mp3").unwrap_or(&file.path),
+                  extension
+                );
+                sender.send(
+                  Command::OpenModal(
+                    Modal::Path(PathModal::new(PathPurpose::ExportPicture(i, j), path))
+                  )
+                );
+              }
+              KeyCode::Char('t') if j < pictures => {
+                sender.send(Command::CyclePictureType(i, j));
+              }
+              KeyCode::Delete if j < pictures => {
+                sender.send(Command::RemovePicture(i, j));
+              }
+              _ => {
+                return false;
+              }
+            }
+            true
+          }
           _ => {
             sender.send(Command::HandleHomeScreenInput(event.clone(), self.focused_el));
             true
@@ -295,6 +598,8 @@ impl StateDependentWidget for HomeScreen {
     let github_shortcut = Shortcut::new("Ctrl+G", "Github", Color::Gray);
     let help_shortcut = Shortcut::new("Ctrl+H", "Help", Color::Gray);
     let save_shortcut = Shortcut::new("Ctrl+S", "Save", Color::Yellow);
+    let frames_shortcut = Shortcut::new("Ctrl+F", "All frames", Color::Gray);
+    let settings_shortcut = Shortcut::new("Ctrl+O", "Settings", Color::Gray);
 
     let [header_area, main_area, footer_area] = Layout::vertical([
       Constraint::Length(1),
@@ -332,8 +637,9 @@ impl StateDependentWidget for HomeScreen {
       search_input.render(search_area, buf);
     }
 
-    let sel_song_i = match &self.focused_el {
-      Focusable::Table(i) | Focusable::Editor(i, ..) => Some(i),
+    // the focused file is found by its row, the table lists the files in scan order
+    let sel_row = match &self.focused_el {
+      Focusable::Table(i) | Focusable::Editor(i, ..) => state.shown_row(*i),
       _ => None,
     };
 
@@ -345,12 +651,14 @@ impl StateDependentWidget for HomeScreen {
             .map(|i| {
               let f = state.get_file(*i);
               let edited = f.tags.edited();
+              let selected = state.is_selected(*i);
               Row::new(
                 vec![
                   Cell::from(
                     Line::from(
                       Vec::from([
                         if edited { Span::from("▌").yellow() } else { Span::from(" ") },
+                        if selected { Span::from("✓ ").cyan() } else { Span::default() },
                         Span::from(f.name.clone()).style(
                           if edited {
                             Style::new().yellow()
@@ -403,7 +711,7 @@ impl StateDependentWidget for HomeScreen {
                 })
               )
               .title_bottom(
-                sel_song_i.map_or(Line::default(), |i|
+                sel_row.map_or(Line::default(), |i|
                   Line::from(
                     Vec::from([
                       Span::from(" "),
@@ -411,13 +719,18 @@ impl StateDependentWidget for HomeScreen {
                       Span::from("/").dark_gray(),
                       Span::from(state.shown_indexes.len().to_string().dark_gray()),
                       Span::from(" "),
+                      if state.selection.is_empty() {
+                        Span::default()
+                      } else {
+                        Span::from(format!("{} selected ", state.selection.len())).cyan()
+                      },
                     ])
                   ).centered()
                 )
               )
           )
       };
-      let mut table_state = &mut TableState::new().with_selected(sel_song_i.cloned());
+      let mut table_state = &mut TableState::new().with_selected(sel_row);
       <Table as StatefulWidget>::render(files_table, table_area, buf, table_state);
     }
 
@@ -436,197 +749,389 @@ impl StateDependentWidget for HomeScreen {
       let table_title_mod = Line::from(" Modified ").centered().render(mod_area, buf);
     }
 
-    let tags = match &self.focused_el {
-      Focusable::Table(i) | Focusable::Editor(i, _) => Some(&state.get_file(*i).tags),
+    let file = match &self.focused_el {
+      Focusable::Table(i) | Focusable::Editor(i, _) => Some(state.get_file(*i)),
       _ => None,
     };
+    let tags = file.map(|f| &f.tags);
+    // the selected files edited along with the focused one
+    let files = match &self.focused_el {
+      Focusable::Table(i) | Focusable::Editor(i, _) => {
+        state
+          .targets(*i)
+          .into_iter()
+          .map(|i| state.get_file(i))
+          .collect::<Vec<_>>()
+      }
+      _ => Vec::new(),
+    };
+    let edited = files.iter().any(|f| f.tags.edited());
 
     let editor_focused = match self.focused_el {
       Focusable::Editor(..) => true,
       _ => false,
     };
 
+    let [inputs_area, cover_column_area] = Layout::horizontal([
+      Constraint::Fill(1),
+      Constraint::Length(if tags.is_some() { 28 } else { 0 }),
+    ]).areas(editor_area);
+    let [cover_area] = Layout::vertical([Constraint::Length(15)])
+      .flex(Flex::Start)
+      .areas(cover_column_area);
+
+    if let Some(tags) = tags {
+      let block = Block::bordered()
+        .border_type(BorderType::Rounded)
+        .title_top("Cover")
+        .title_bottom(Line::from(format!(" {} ", version_name(tags.version))).centered())
+        .border_style(
+          Style::from(StyleFlags {
+            enabled: editor_focused,
+            valid: true,
+            highlighted: false,
+          })
+        );
+      let inner_area = block.inner(cover_area);
+      block.render(cover_area, buf);
+      let front_cover = tags.pictures
+        .iter()
+        .filter_map(|p| p.0.current())
+        .find(|p| p.picture_type == PictureType::CoverFront);
+      // images sent with escape sequences would be drawn on top of modals
+      let covered_by_modal =
+        ui_state.modals.last().is_some() &&
+        ui_state.graphics.borrow().protocol != GraphicsProtocol::HalfBlocks;
+      if !covered_by_modal {
+        CoverArt::new(front_cover, &ui_state.graphics).render(inner_area, buf);
+      }
+    }
+
     let [
       title_input_area,
       artist_input_area,
-      year_input_area,
+      album_input_area,
+      album_artist_input_area,
+      dates_area,
       genre_input_area,
-      lyrics_button_area,
+      numbers_area,
+      buttons_area,
+      artwork_area,
     ] = Layout::vertical([
+      Constraint::Length(3),
+      Constraint::Length(3),
+      Constraint::Length(3),
       Constraint::Length(3),
       Constraint::Length(3),
       Constraint::Length(3),
       Constraint::Length(3),
       Constraint::Length(5),
+      Constraint::Min(4),
     ])
       .flex(Flex::Start)
-      .areas(editor_area);
+      .areas(inputs_area);
+    let [date_input_area, original_date_input_area, release_date_input_area] = Layout::horizontal([
+      Constraint::Fill(1),
+      Constraint::Fill(1),
+      Constraint::Fill(1),
+    ]).areas(dates_area);
+    let [track_input_area, disc_input_area] = Layout::horizontal([
+      Constraint::Fill(1),
+      Constraint::Fill(1),
+    ]).areas(numbers_area);
+    let [lyrics_button_area, comments_button_area] = Layout::horizontal([
+      Constraint::Fill(1),
+      Constraint::Fill(1),
+    ]).areas(buttons_area);
 
-    {
-      let mut title_input = self.title_input.clone();
+    for (el, area) in [
+      (EditorFocusable::TitleInput, title_input_area),
+      (EditorFocusable::ArtistInput, artist_input_area),
+      (EditorFocusable::AlbumInput, album_input_area),
+      (EditorFocusable::AlbumArtistInput, album_artist_input_area),
+      (EditorFocusable::DateInput, date_input_area),
+      (EditorFocusable::OriginalDateInput, original_date_input_area),
+      (EditorFocusable::ReleaseDateInput, release_date_input_area),
+      (EditorFocusable::GenreInput, genre_input_area),
+      (EditorFocusable::TrackInput, track_input_area),
+      (EditorFocusable::DiscInput, disc_input_area),
+    ] {
+      let mut input = self.input(el).unwrap().clone();
+      let error = files.iter().find_map(|f| el.error(&f.tags));
+      let valid = error.is_none();
       let border_flags = StyleFlags {
         enabled: editor_focused,
-        valid: true,
+        valid,
         highlighted: match self.focused_el {
-          Focusable::Editor(_, ed_f) =>
-            match ed_f {
-              EditorFocusable::TitleInput => true,
-              _ => false,
-            }
+          Focusable::Editor(_, ed_f) => ed_f == el,
           _ => false,
         },
       };
-      title_input.set_style(
+      input.set_style(
         Style::from(StyleFlags {
           enabled: editor_focused,
-          valid: true,
-          highlighted: tags.map(|t| t.title.0.edited()).unwrap_or_default(),
+          valid,
+          highlighted: files
+            .iter()
+            .any(|f| el.tag(&f.tags).is_some_and(|t| t.0.edited())),
         })
       );
-      title_input.set_block(
-        title_input.block().cloned().unwrap_or_default().border_style(Style::from(border_flags))
-      );
-      title_input.toggle_cursor(border_flags.highlighted);
-      title_input.render(title_input_area, buf);
+      let mut block = input.block().cloned().unwrap_or_default();
+      if let Some(error) = error {
+        block = block.title_bottom(Line::from(error).red());
+      } else if let Some((t, f)) = tags.filter(|_| files.len() == 1).zip(el.id3v1_field()) {
+        if t.id3v1.present || t.id3v1.edited() {
+          block = block.title_bottom(id3v1_line(t, f, editor_focused).right_aligned());
+        }
+      }
+      input.set_block(block.border_style(Style::from(border_flags)));
+      input.toggle_cursor(border_flags.highlighted);
+      input.render(area, buf);
     }
 
-    {
-      let mut artist_input = self.artist_input.clone();
-      let flags = StyleFlags {
-        enabled: editor_focused,
-        valid: true,
-        highlighted: match self.focused_el {
-          Focusable::Editor(_, ed_f) =>
-            match ed_f {
-              EditorFocusable::ArtistInput => true,
-              _ => false,
-            }
-          _ => false,
-        },
+    for (el, title, area) in [
+      (EditorFocusable::LyricsButton, "Lyrics", lyrics_button_area),
+      (EditorFocusable::CommentsButton, "Comments", comments_button_area),
+    ] {
+      let (frames, valid, edited) = match (el, tags) {
+        (EditorFocusable::LyricsButton, Some(t)) => {
+          let valid = (0..t.lyrics.len()).all(|j| {
+            !t.lyrics_conflict(j) && t.lyrics[j].lang.valid()
+          });
+          (t.lyrics.len(), valid, t.lyrics_edited())
+        }
+        (_, Some(t)) => {
+          let valid = (0..t.comments.len()).all(|j| {
+            !t.comment_conflict(j) && t.comments[j].lang.valid()
+          });
+          (t.comments.len(), valid, t.comments_edited())
+        }
+        (_, None) => (0, true, false),
       };
-      artist_input.set_style(
-        Style::from(StyleFlags {
-          enabled: editor_focused,
-          valid: true,
-          highlighted: tags.map(|t| t.artist.0.edited()).unwrap_or_default(),
-        })
-      );
-      artist_input.set_block(
-        artist_input.block().cloned().unwrap_or_default().border_style(Style::from(flags))
-      );
-      artist_input.toggle_cursor(flags.highlighted);
-      artist_input.render(artist_input_area, buf);
+      Paragraph::new(
+        vec![
+          Line::default(),
+          Line::from(match frames {
+            0 | 1 => "Edit".to_string(),
+            n => format!("Edit ({} frames)", n),
+          }).style(
+            Style::from(StyleFlags {
+              enabled: editor_focused,
+              valid,
+              highlighted: edited,
+            })
+          ),
+          Line::default()
+        ]
+      )
+        .block(
+          Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_top(title)
+            .title_bottom(match tags {
+              Some(t) if
+                el == EditorFocusable::CommentsButton &&
+                (t.id3v1.present || t.id3v1.edited())
+              => id3v1_line(t, Id3v1Field::Comment, editor_focused).right_aligned(),
+              _ => Line::default(),
+            })
+            .border_style(
+              Style::from(StyleFlags {
+                enabled: editor_focused,
+                highlighted: matches!(self.focused_el, Focusable::Editor(_, ed_f) if ed_f == el),
+                valid: true,
+              })
+            )
+        )
+        .centered()
+        .render(area, buf);
     }
 
     {
-      let mut year_input = self.year_input.clone();
-      let flags = StyleFlags {
-        enabled: editor_focused,
-        valid: true,
-        highlighted: match self.focused_el {
-          Focusable::Editor(_, ed_f) =>
-            match ed_f {
-              EditorFocusable::YearInput => true,
-              _ => false,
-            }
-          _ => false,
-        },
+      let focused = match self.focused_el {
+        Focusable::Editor(_, EditorFocusable::ArtworkList) => true,
+        _ => false,
       };
-      year_input.set_style(
-        Style::from(StyleFlags {
-          enabled: editor_focused,
-          valid: true,
-          highlighted: tags.map(|t| t.year.0.edited()).unwrap_or_default(),
-        })
-      );
-      year_input.set_block(
-        year_input.block().cloned().unwrap_or_default().border_style(Style::from(flags))
-      );
-      year_input.toggle_cursor(flags.highlighted);
-      year_input.render(year_input_area, buf);
-    }
-
-    {
-      let mut genre_input = self.genre_input.clone();
-      let flags = StyleFlags {
-        enabled: editor_focused,
-        valid: true,
-        highlighted: match self.focused_el {
-          Focusable::Editor(_, ed_f) =>
-            match ed_f {
-              EditorFocusable::GenreInput => true,
-              _ => false,
-            }
-          _ => false,
-        },
-      };
-      genre_input.set_style(
-        Style::from(StyleFlags {
-          enabled: editor_focused,
-          valid: true,
-          highlighted: tags.map(|t| t.genre.0.edited()).unwrap_or_default(),
-        })
-      );
-      genre_input.set_block(
-        genre_input.block().cloned().unwrap_or_default().border_style(Style::from(flags))
-      );
-      genre_input.toggle_cursor(flags.highlighted);
-      genre_input.render(genre_input_area, buf);
-    }
-
-    let mut lyrics_button = Paragraph::new(
-      vec![
-        Line::default(),
-        Line::from("Edit").style(
-          Style::from(StyleFlags {
-            enabled: editor_focused,
-            valid: true,
-            highlighted: tags
-              .map(|t| (t.lyrics.lang.edited() || t.lyrics.desc.edited() || t.lyrics.text.edited()))
-              .unwrap_or_default(),
-          })
-        ),
-        Line::default()
-      ]
-    )
-      .block(
-        Block::bordered()
-          .border_type(BorderType::Rounded)
-          .title_top("Lyrics")
-          .border_style(
-            Style::from(StyleFlags {
+      let pictures = tags.map(|t| t.pictures.as_slice()).unwrap_or_default();
+      let artwork_table = Table::new(
+        pictures
+          .iter()
+          .map(|p| {
+            let picture = p.0.current().or(p.0.original.as_ref()).unwrap();
+            let style = Style::from(StyleFlags {
               enabled: editor_focused,
-              highlighted: match self.focused_el {
-                Focusable::Editor(_, ed_f) =>
-                  match ed_f {
-                    EditorFocusable::LyricsButton => true,
-                    _ => false,
-                  }
-                _ => false,
-              },
               valid: true,
-            })
-          )
+              highlighted: p.0.edited(),
+            });
+            let style = match p.0.state {
+              EditableState::Removed => style.crossed_out(),
+              _ => style,
+            };
+            Row::new(
+              vec![
+                Cell::from(picture.picture_type.to_string()),
+                Cell::from(picture.mime_type.clone()),
+                Cell::from(
+                  PictureEditableTag::dimensions(picture)
+                    .map(|(w, h)| format!("{}x{}", w, h))
+                    .unwrap_or("?".into())
+                ),
+                Cell::from(format_size(picture.data.len())),
+              ]
+            ).style(style)
+          })
+          .collect::<Vec<_>>(),
+        [Constraint::Fill(2), Constraint::Fill(1), Constraint::Fill(1), Constraint::Fill(1)]
       )
-      .centered()
-      .render(lyrics_button_area, buf);
+        .row_highlight_style(Style::new().on_dark_gray().bold())
+        .column_spacing(1)
+        .block(
+          Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_top("Artwork")
+            .title_bottom(
+              if focused {
+                Line::from(
+                  [
+                    Shortcut::new("A", "Add", Color::Gray).to_spans(),
+                    Shortcut::new("R", "Replace", Color::Gray).to_spans(),
+                    Shortcut::new("E", "Export", Color::Gray).to_spans(),
+                    Shortcut::new("T", "Type", Color::Gray).to_spans(),
+                    Shortcut::new("Del", "Remove", Color::Gray).to_spans(),
+                  ].join(&Span::from(" "))
+                ).centered()
+              } else {
+                Line::default()
+              }
+            )
+            .border_style(
+              Style::from(StyleFlags {
+                enabled: editor_focused,
+                highlighted: focused,
+                valid: true,
+              })
+            )
+        );
+      let mut table_state = TableState::new().with_selected(
+        if focused && !pictures.is_empty() {
+          Some(self.selected_picture.min(pictures.len() - 1))
+        } else {
+          None
+        }
+      );
+      <Table as StatefulWidget>::render(artwork_table, artwork_area, buf, &mut table_state);
+    }
 
+    let [title_area, scan_area] = Layout::horizontal([
+      Constraint::Fill(1),
+      Constraint::Length(if state.scan.done { 0 } else { 48 }),
+    ]).areas(header_area);
     let header_line = Line::from(
       Vec::from([
         Span::from(": ").dark_gray(),
         Span::from(PROJECT_NAME),
         Span::from(" :").dark_gray(),
       ])
-    ).render(header_area, buf);
+    ).render(title_area, buf);
+    if !state.scan.done {
+      let scan = &state.scan;
+      // files are reported found in batches and may be read before
+      let found = scan.found.max(scan.read);
+      Gauge::default()
+        .gauge_style(Style::new().cyan().on_dark_gray())
+        .ratio((scan.read as f64) / (found.max(1) as f64))
+        .label(match scan.errors {
+          0 => format!("Scanning {}/{}", scan.read, found),
+          1 => format!("Scanning {}/{}, 1 error", scan.read, found),
+          n => format!("Scanning {}/{}, {} errors", scan.read, found, n),
+        })
+        .render(scan_area, buf);
+    }
 
     let footer_line = Line::from(
       Vec::from([
-        if tags.is_some_and(|t| t.edited()) { save_shortcut.to_spans() } else { Vec::new() },
-        if tags.is_some_and(|t| t.edited()) {
+        match edited.then(|| files.iter().map(|f| f.tags.errors().len()).sum::<usize>()) {
+          Some(0) => save_shortcut.to_spans(),
+          Some(1) => Vec::from([Span::from("1 invalid field").red()]),
+          Some(n) => Vec::from([Span::from(format!("{} invalid fields", n)).red()]),
+          None => Vec::new(),
+        },
+        if edited {
           Vec::from([Span::from(" :: ").dark_gray()])
         } else {
           Vec::new()
         },
+        if tags.is_some() {
+          [
+            frames_shortcut.to_spans(),
+            Vec::from([Span::from(" :: ").dark_gray()]),
+            Shortcut::new("Ctrl+B", "History", Color::Gray).to_spans(),
+            Vec::from([Span::from(" :: ").dark_gray()]),
+          ].concat()
+        } else {
+          Vec::new()
+        },
+        match state.pending().len() {
+          0 => Vec::new(),
+          n => {
+            [
+              Shortcut::new("Ctrl+P", format!("{} pending", n), Color::Yellow).to_spans(),
+              Vec::from([Span::from(" :: ").dark_gray()]),
+            ].concat()
+          }
+        },
+        if state.log.entries().is_empty() {
+          Vec::new()
+        } else {
+          [
+            Shortcut::new("Ctrl+L", "Log", Color::Red).to_spans(),
+            Vec::from([Span::from(" :: ").dark_gray()]),
+          ].concat()
+        },
+        settings_shortcut.to_spans(),
+        Vec::from([Span::from(" :: ").dark_gray()]),
+        if matches!(self.focused_el, Focusable::Editor(_, EditorFocusable::GenreInput)) {
+          [
+            Shortcut::new("Enter", "Pick genre", Color::Gray).to_spans(),
+            Vec::from([Span::from(" :: ").dark_gray()]),
+          ].concat()
+        } else {
+          Vec::new()
+        },
+        if tags.is_some() && editor_focused {
+          [
+            Shortcut::new("Ctrl+U", "To ID3v1", Color::Gray).to_spans(),
+            Vec::from([Span::from(" :: ").dark_gray()]),
+          ].concat()
+        } else {
+          Vec::new()
+        },
+        if tags.is_some_and(|t| editor_focused && t.id3v1.present) {
+          [
+            Shortcut::new("Ctrl+D", "From ID3v1", Color::Gray).to_spans(),
+            Vec::from([Span::from(" :: ").dark_gray()]),
+          ].concat()
+        } else {
+          Vec::new()
+        },
+        if matches!(self.focused_el, Focusable::Table(_)) {
+          [
+            Shortcut::new("Space", "Select", Color::Gray).to_spans(),
+            Vec::from([Span::from(" :: ").dark_gray()]),
+            Shortcut::new("Ctrl+A", "Select all", Color::Gray).to_spans(),
+            Vec::from([Span::from(" :: ").dark_gray()]),
+          ].concat()
+        } else {
+          Vec::new()
+        },
+        if files.iter().any(|f| f.history.can_undo()) {
+          [
+            Shortcut::new("Ctrl+Z", "Undo", Color::Gray).to_spans(),
+            Vec::from([Span::from(" :: ").dark_gray()]),
+          ].concat()
+        } else {
+          Vec::new()
+        },
         help_shortcut.to_spans(),
         Vec::from([Span::from(" :: ").dark_gray()]),
         github_shortcut.to_spans(),
@@ -636,3 +1141,35 @@ impl StateDependentWidget for HomeScreen {
       .render(footer_area, buf);
   }
 }
+
+/// ID3v1 value of a field, red when it disagrees with ID3v2 and marked when ID3v2 does not fit.
+fn id3v1_line(tags: &SongTags, f: Id3v1Field, enabled: bool) -> Line<'static> {
+  let field = tags.id3v1.field(f);
+  let style = Style::from(StyleFlags {
+    enabled,
+    valid: !tags.id3v1_conflict(f),
+    highlighted: field.edited(),
+  });
+  Line::from(
+    Vec::from([
+      Span::from(" v1: ").dark_gray(),
+      Span::from(field.current().cloned().unwrap_or("—".into())).style(style),
+      match (tags.id3v1_truncated(f), f) {
+        (false, _) => Span::default(),
+        (true, Id3v1Field::Genre) => Span::from(" (not a v1 genre)").yellow(),
+        (true, _) => Span::from(format!(" (>{} chars)", f.max_len())).yellow(),
+      },
+      Span::from(" "),
+    ])
+  )
+}
+
+fn format_size(bytes: usize) -> String {
+  if bytes < 1024 {
+    format!("{} B", bytes)
+  } else if bytes < 1024 * 1024 {
+    format!("{:.1} KiB", (bytes as f64) / 1024.0)
+  } else {
+    format!("{:.1} MiB", (bytes as f64) / 1024.0 / 1024.0)
+  }
+}
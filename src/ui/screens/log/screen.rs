@@ -0,0 +1,158 @@
+use std::sync::mpsc::Sender;
+use crate::{
+  app::{ app::Command, state::State },
+  ui::{
+    home::screen::{ Focusable as HomeFocusable, HomeScreen },
+    shortcut::Shortcut,
+    ui_enums::Screen,
+    InputHandler,
+    StateDependentWidget,
+    UiState,
+  },
+};
+use crossterm::event::{ Event, KeyCode, KeyEventKind };
+use ratatui::{
+  buffer::Buffer,
+  layout::{ Constraint, Layout, Margin, Rect },
+  style::{ Color, Stylize },
+  text::{ Line, Span },
+  widgets::{ Block, BorderType, Paragraph, Widget, Wrap },
+};
+
+/// Lines skipped by PageUp and PageDown.
+const PAGE: usize = 10;
+
+/// Errors logged during the session and before it, the latest first.
+pub struct LogScreen {
+  /// Entries scrolled past from the top.
+  pub scroll: usize,
+}
+
+impl LogScreen {
+  pub fn new() -> Self {
+    Self { scroll: 0 }
+  }
+}
+
+impl InputHandler for LogScreen {
+  fn handle_input(
+    &self,
+    state: &State,
+    ui_state: &UiState,
+    event: Event,
+    sender: Sender<Command>
+  ) -> bool {
+    let last = state.log.entries().len().saturating_sub(1);
+    match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+        match key_event.code {
+          KeyCode::Esc => {
+            let home = HomeScreen::new(HomeFocusable::Search, None);
+            sender.send(Command::SetScreen(Screen::Home(home)));
+            true
+          }
+          KeyCode::Up => {
+            sender.send(Command::ScrollLog(self.scroll.saturating_sub(1)));
+            true
+          }
+          KeyCode::Down => {
+            sender.send(Command::ScrollLog((self.scroll + 1).min(last)));
+            true
+          }
+          KeyCode::PageUp => {
+            sender.send(Command::ScrollLog(self.scroll.saturating_sub(PAGE)));
+            true
+          }
+          KeyCode::PageDown => {
+            sender.send(Command::ScrollLog((self.scroll + PAGE).min(last)));
+            true
+          }
+          KeyCode::Home => {
+            sender.send(Command::ScrollLog(0));
+            true
+          }
+          _ => false,
+        }
+      }
+      _ => false,
+    }
+  }
+}
+
+impl StateDependentWidget for LogScreen {
+  fn render_from_state(&self, area: Rect, buf: &mut Buffer, state: &State, ui_state: &UiState)
+    where Self: Sized
+  {
+    let [header_area, main_area, footer_area] = Layout::vertical([
+      Constraint::Length(2),
+      Constraint::Fill(1),
+      Constraint::Length(1),
+    ]).areas(area);
+    let footer_area = footer_area.inner(Margin::new(1, 0));
+    let [title_area, path_area] = Layout::vertical([
+      Constraint::Length(1),
+      Constraint::Length(1),
+    ]).areas(header_area);
+
+    let entries = state.log.entries();
+    Line::from("Log").centered().render(title_area, buf);
+    Line::from(state.log.path().display().to_string())
+      .dark_gray()
+      .centered()
+      .render(path_area, buf);
+
+    let lines = entries
+      .iter()
+      .rev()
+      .skip(self.scroll)
+      .map(|entry| {
+        // the timestamp and level are dimmed, the message stands out
+        match entry.split_once(" ERROR ") {
+          Some((time, message)) => {
+            Line::from(
+              Vec::from([
+                Span::from(time.to_string()).dark_gray(),
+                Span::from(" ERROR ").red(),
+                Span::from(message.to_string()),
+              ])
+            )
+          }
+          None => Line::from(entry.clone()),
+        }
+      })
+      .collect::<Vec<_>>();
+    Paragraph::new(lines)
+      .wrap(Wrap::default())
+      .block(
+        Block::bordered()
+          .border_type(BorderType::Rounded)
+          .title_top(format!("Entries {}", entries.len()))
+          .title_bottom(
+            if entries.is_empty() {
+              Line::from(" Nothing logged ").dark_gray().centered()
+            } else {
+              Line::from(
+                format!(" {}/{} ", (self.scroll + 1).min(entries.len()), entries.len())
+              ).dark_gray().right_aligned()
+            }
+          )
+      )
+      .render(main_area, buf);
+
+    Paragraph::new(
+      Line::from(
+        [
+          Shortcut::new("↑↓", "Scroll", Color::Gray).to_spans(),
+          Vec::from([Span::from(" :: ").dark_gray()]),
+          Shortcut::new("PgUp/PgDn", "Page", Color::Gray).to_spans(),
+          Vec::from([Span::from(" :: ").dark_gray()]),
+          Shortcut::new("Home", "Latest", Color::Gray).to_spans(),
+          Vec::from([Span::from(" :: ").dark_gray()]),
+          Shortcut::new("Esc", "Back", Color::Gray).to_spans(),
+        ].concat()
+      )
+    )
+      .right_aligned()
+      .render(footer_area, buf);
+  }
+}
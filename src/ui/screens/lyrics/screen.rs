@@ -1,9 +1,15 @@
 use std::sync::mpsc::Sender;
 use crate::{
-  app::{ app::{ App, Command }, state::State, tag::LyricsEditableTag },
+  app::{
+    app::{ App, Command },
+    lrc,
+    state::State,
+    tag::{ LyricsEditableTag, SongTags },
+  },
   ui::{
     block::BlockTrait,
     home::{ self, screen::{ EditorFocusable, HomeScreen } },
+    modals::{ modal::enums::Modal, path::{ PathModal, PathPurpose } },
     shortcut::Shortcut,
     text_area::TextAreaTrait,
     ui_enums::{ self, Screen },
@@ -26,53 +32,194 @@ use ratatui::{
 };
 use tui_textarea::TextArea;
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Pane {
+  Left,
+  Right,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Focusable {
-  Lang,
-  Desc,
-  Text,
+  Frames,
+  Lang(Pane),
+  Desc(Pane),
+  Text(Pane),
+  Synced(Pane),
 }
 
-pub struct LyricsScreen {
-  // TODO: file name
-  pub index: usize,
-  pub lyrics: LyricsEditableTag,
-  pub focused_el: Focusable,
+impl Focusable {
+  pub fn pane(self) -> Option<Pane> {
+    match self {
+      Focusable::Frames => None,
+      Focusable::Lang(p) | Focusable::Desc(p) => Some(p),
+      Focusable::Text(p) | Focusable::Synced(p) => Some(p),
+    }
+  }
+}
+
+/// Inputs editing one USLT frame.
+pub struct LyricsPane {
+  /// Index into `SongTags::lyrics`, `None` until a frame is created by typing.
+  pub frame: Option<usize>,
   pub lang_input: TextArea<'static>,
   pub desc_input: TextArea<'static>,
   pub text_textarea: TextArea<'static>,
+  pub synced_textarea: TextArea<'static>,
 }
 
-impl LyricsScreen {
-  pub fn new(index: usize, lyrics: LyricsEditableTag) -> Self {
-    let focused_el = Focusable::Lang;
-    Self {
+impl LyricsPane {
+  fn new(frame: Option<usize>, lyrics: &[LyricsEditableTag]) -> Self {
+    let mut new = Self {
+      frame: None,
       lang_input: {
-        let mut input = TextArea::new(Vec::from([lyrics.lang.to_string()]));
+        let mut input = TextArea::new(Vec::new());
         input.set_block(Block::bordered().border_type(BorderType::Rounded).title_top("Lang"));
         input.set_cursor_line_style(Style::new());
 
         input
       },
       desc_input: {
-        let mut input = TextArea::new(Vec::from([lyrics.desc.to_string()]));
+        let mut input = TextArea::new(Vec::new());
         input.set_block(Block::bordered().border_type(BorderType::Rounded).title_top("Desc"));
         input.set_cursor_line_style(Style::new());
 
         input
       },
       text_textarea: {
-        let mut input = TextArea::new(Vec::from([lyrics.text.to_string()]));
+        let mut input = TextArea::new(Vec::new());
         input.set_block(Block::bordered().border_type(BorderType::Rounded).title_top("Text"));
         input.set_cursor_line_style(Style::new());
 
         input
       },
+      synced_textarea: {
+        let mut input = TextArea::new(Vec::new());
+        input.set_block(
+          Block::bordered().border_type(BorderType::Rounded).title_top("Synced [mm:ss.xx]")
+        );
+        input.set_cursor_line_style(Style::new());
+
+        input
+      },
+    };
+    new.set_frame(frame, lyrics);
+    new
+  }
+  /// Shows another frame and fills the inputs with its values.
+  pub fn set_frame(&mut self, frame: Option<usize>, lyrics: &[LyricsEditableTag]) {
+    self.frame = frame;
+    let frame = frame.and_then(|j| lyrics.get(j));
+    self.lang_input.set_text(frame.map(|l| l.lang.to_string()).unwrap_or_default());
+    self.desc_input.set_text(frame.map(|l| l.desc.to_string()).unwrap_or_default());
+    // loaded line by line, the cursor starts at the top of the lyrics
+    for (textarea, lines) in [
+      (&mut self.text_textarea, frame.map(|l| l.text_lines())),
+      (&mut self.synced_textarea, frame.map(|l| l.synced_lines())),
+    ] {
+      let mut new = TextArea::new(lines.unwrap_or_default());
+      new.set_block(textarea.block().cloned().unwrap_or_default());
+      new.set_cursor_line_style(Style::new());
+      *textarea = new;
+    }
+  }
+  pub fn input_mut(&mut self, el: Focusable) -> Option<&mut TextArea<'static>> {
+    match el {
+      Focusable::Frames => None,
+      Focusable::Lang(_) => Some(&mut self.lang_input),
+      Focusable::Desc(_) => Some(&mut self.desc_input),
+      Focusable::Text(_) => Some(&mut self.text_textarea),
+      Focusable::Synced(_) => Some(&mut self.synced_textarea),
+    }
+  }
+}
+
+pub struct LyricsScreen {
+  // TODO: file name
+  pub index: usize,
+  pub focused_el: Focusable,
+  /// Frame highlighted in the list of frames.
+  pub frame_cursor: usize,
+  /// The original on the left and an optional translation on the right.
+  pub panes: [LyricsPane; 2],
+  /// Whether the panes show synced lyrics instead of the unsynced text.
+  pub synced: bool,
+}
+
+impl LyricsScreen {
+  pub fn new(index: usize, lyrics: &[LyricsEditableTag]) -> Self {
+    let focused_el = Focusable::Lang(Pane::Left);
+    Self {
+      panes: [
+        LyricsPane::new(if lyrics.is_empty() { None } else { Some(0) }, lyrics),
+        LyricsPane::new(if lyrics.len() > 1 { Some(1) } else { None }, lyrics),
+      ],
+      frame_cursor: 0,
+      synced: false,
       focused_el,
-      lyrics,
       index,
     }
   }
+  pub fn pane(&self, pane: Pane) -> &LyricsPane {
+    match pane {
+      Pane::Left => &self.panes[0],
+      Pane::Right => &self.panes[1],
+    }
+  }
+  pub fn pane_mut(&mut self, pane: Pane) -> &mut LyricsPane {
+    match pane {
+      Pane::Left => &mut self.panes[0],
+      Pane::Right => &mut self.panes[1],
+    }
+  }
+  /// Refills the inputs after frames were reset or dropped, `dropped` is the index of a frame
+  /// that no longer exists.
+  pub fn sync(&mut self, lyrics: &[LyricsEditableTag], dropped: Option<usize>) {
+    for pane in self.panes.iter_mut() {
+      let frame = match (pane.frame, dropped) {
+        (Some(j), Some(d)) if j == d => None,
+        (Some(j), Some(d)) if j > d => Some(j - 1),
+        (frame, _) => frame.filter(|j| *j < lyrics.len()),
+      };
+      pane.set_frame(frame, lyrics);
+    }
+    self.frame_cursor = self.frame_cursor.min(lyrics.len().saturating_sub(1));
+    if self.focused_el.pane().is_some_and(|p| self.pane(p).frame.is_none()) {
+      self.focused_el = Focusable::Frames;
+    }
+  }
+  /// The text element of the pane in the current mode.
+  pub fn text_el(&self, pane: Pane) -> Focusable {
+    if self.synced { Focusable::Synced(pane) } else { Focusable::Text(pane) }
+  }
+  /// Elements in the order they are cycled through, the right pane is skipped when empty.
+  fn focus_order(&self) -> Vec<Focusable> {
+    let mut order = Vec::from([
+      Focusable::Frames,
+      Focusable::Lang(Pane::Left),
+      Focusable::Desc(Pane::Left),
+      self.text_el(Pane::Left),
+    ]);
+    if self.pane(Pane::Right).frame.is_some() {
+      order.extend([
+        Focusable::Lang(Pane::Right),
+        Focusable::Desc(Pane::Right),
+        self.text_el(Pane::Right),
+      ]);
+    }
+    order
+  }
+  fn next_focusable(&self, forward: bool) -> Focusable {
+    let order = self.focus_order();
+    let i = order
+      .iter()
+      .position(|el| *el == self.focused_el)
+      .unwrap_or(0);
+    if forward {
+      order[(i + 1) % order.len()]
+    } else {
+      order[(i + order.len() - 1) % order.len()]
+    }
+  }
 }
 
 impl InputHandler for LyricsScreen {
@@ -83,6 +230,7 @@ impl InputHandler for LyricsScreen {
     event: Event,
     sender: Sender<Command>
   ) -> bool {
+    let frames = state.get_file(self.index).tags.lyrics.len();
     match event {
       Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
         match (key_event.code, key_event.modifiers, self.focused_el) {
@@ -103,35 +251,72 @@ impl InputHandler for LyricsScreen {
             );
             true
           }
-          (KeyCode::Down, KeyModifiers::CONTROL, f_el) | (KeyCode::PageDown, _, f_el) => {
-            sender.send(
-              Command::FocusLyricsElement(match f_el {
-                Focusable::Lang => Focusable::Desc,
-                Focusable::Desc => Focusable::Text,
-                Focusable::Text => Focusable::Lang,
-              })
-            );
+          (KeyCode::Down, KeyModifiers::CONTROL, _) | (KeyCode::PageDown, _, _) => {
+            sender.send(Command::FocusLyricsElement(self.next_focusable(true)));
             true
           }
-          (KeyCode::Up, KeyModifiers::CONTROL, f_el) | (KeyCode::PageUp, _, f_el) => {
-            sender.send(
-              Command::FocusLyricsElement(match f_el {
-                Focusable::Lang => Focusable::Text,
-                Focusable::Desc => Focusable::Lang,
-                Focusable::Text => Focusable::Desc,
-              })
-            );
+          (KeyCode::Up, KeyModifiers::CONTROL, _) | (KeyCode::PageUp, _, _) => {
+            sender.send(Command::FocusLyricsElement(self.next_focusable(false)));
             true
           }
           (KeyCode::Char('r' | 'к'), KeyModifiers::CONTROL, f_el) => {
             sender.send(Command::ResetLyricsScreenTag(f_el));
             true
           }
+          (KeyCode::Char('z' | 'я'), KeyModifiers::CONTROL, _) => {
+            sender.send(Command::Undo);
+            true
+          }
+          (KeyCode::Char('y' | 'н'), KeyModifiers::CONTROL, _) => {
+            sender.send(Command::Redo);
+            true
+          }
+          (KeyCode::Char('t' | 'е'), KeyModifiers::CONTROL, _) => {
+            sender.send(Command::ToggleSyncedLyrics);
+            true
+          }
+          (code, KeyModifiers::NONE, Focusable::Frames) => {
+            let j = self.frame_cursor;
+            match code {
+              KeyCode::Left if frames > 0 => {
+                sender.send(Command::SetLyricsFrameCursor(if j > 0 { j - 1 } else { frames - 1 }));
+              }
+              KeyCode::Right if frames > 0 => {
+                sender.send(Command::SetLyricsFrameCursor(if j + 1 >= frames { 0 } else { j + 1 }));
+              }
+              KeyCode::Enter if j < frames => {
+                sender.send(Command::OpenLyricsFrame(Pane::Left, Some(j)));
+              }
+              KeyCode::Char('t') if j < frames => {
+                let open = if self.pane(Pane::Right).frame == Some(j) { None } else { Some(j) };
+                sender.send(Command::OpenLyricsFrame(Pane::Right, open));
+              }
+              KeyCode::Char('a') => {
+                sender.send(Command::AddLyricsFrame);
+              }
+              KeyCode::Char('i') => {
+                let purpose = PathPurpose::ImportLrc(self.index, (j < frames).then_some(j));
+                let path = lrc_path(&state.get_file(self.index).path);
+                sender.send(Command::OpenModal(Modal::Path(PathModal::new(purpose, path))));
+              }
+              KeyCode::Char('e') if j < frames => {
+                let purpose = PathPurpose::ExportLrc(self.index, j);
+                let path = lrc_path(&state.get_file(self.index).path);
+                sender.send(Command::OpenModal(Modal::Path(PathModal::new(purpose, path))));
+              }
+              KeyCode::Delete if j < frames => {
+                sender.send(Command::RemoveLyricsFrame(j));
+              }
+              _ => {
+                return false;
+              }
+            }
+            true
+          }
           _ => {
             sender.send(Command::HandleLyricsScreenInput(key_event.clone(), self.focused_el));
             true
           }
-          _ => false,
         }
       }
       _ => false,
@@ -139,15 +324,19 @@ impl InputHandler for LyricsScreen {
   }
 }
 
+/// The `.lrc` file next to the MP3.
+fn lrc_path(mp3_path: &str) -> String {
+  format!("{}.lrc", mp3_path.strip_suffix(".mp3").unwrap_or(mp3_path))
+}
+
 impl StateDependentWidget for LyricsScreen {
   fn render_from_state(&self, area: Rect, buf: &mut Buffer, state: &State, ui_state: &UiState)
     where Self: Sized
   {
-    let [header_area, lang_area, desc_area, text_area, footer_area] = Layout::vertical(
+    let [header_area, frames_area, panes_area, footer_area] = Layout::vertical(
       vec![
         Constraint::Length(2),
         Constraint::Length(3),
-        Constraint::Length(3),
         Constraint::Fill(1),
         Constraint::Length(1)
       ]
@@ -161,70 +350,165 @@ impl StateDependentWidget for LyricsScreen {
     let tags = &state.get_file(self.index).tags;
 
     {
-      let mut lang_input = self.lang_input.clone();
-      let border_flags = StyleFlags {
-        enabled: true,
-        valid: true,
-        highlighted: self.focused_el == Focusable::Lang,
-      };
-      let text_flags = StyleFlags {
-        enabled: true,
-        valid: true,
-        highlighted: tags.lyrics.lang.edited(),
-      };
-      lang_input.set_style(Style::from(text_flags));
-      lang_input.set_block(
-        lang_input.block().cloned().unwrap_or_default().border_style(Style::from(border_flags))
-      );
-      lang_input.toggle_cursor(border_flags.highlighted);
-      lang_input.render(lang_area, buf);
+      let focused = self.focused_el == Focusable::Frames;
+      let mut spans = Vec::new();
+      for (j, lyrics) in tags.lyrics.iter().enumerate() {
+        let (lang, desc) = lyrics.key();
+        let mut label = if desc.is_empty() { lang } else { format!("{}: {}", lang, desc) };
+        if lyrics.synced.current().is_some() {
+          label = format!("{} ⏱", label);
+        }
+        if self.pane(Pane::Left).frame == Some(j) {
+          label = format!("◧ {}", label);
+        } else if self.pane(Pane::Right).frame == Some(j) {
+          label = format!("◨ {}", label);
+        }
+        let mut style = Style::from(StyleFlags {
+          enabled: true,
+          valid: !tags.lyrics_conflict(j) && lyrics.lang.valid(),
+          highlighted: lyrics.edited(),
+        });
+        if lyrics.removed() {
+          style = style.crossed_out();
+        }
+        if focused && j == self.frame_cursor {
+          style = style.reversed();
+        }
+        spans.push(Span::from(format!(" {} ", label)).style(style));
+        spans.push(Span::from("|").dark_gray());
+      }
+      spans.pop();
+      Paragraph::new(Line::from(spans))
+        .block(
+          Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_top("Frames")
+            .border_style(
+              Style::from(StyleFlags {
+                enabled: true,
+                valid: true,
+                highlighted: focused,
+              })
+            )
+        )
+        .render(frames_area, buf);
     }
 
-    {
-      let mut desc_input = self.desc_input.clone();
-      let border_flags = StyleFlags {
-        enabled: true,
-        valid: true,
-        highlighted: self.focused_el == Focusable::Desc,
-      };
-      let text_flags = StyleFlags {
-        enabled: true,
-        valid: true,
-        highlighted: tags.lyrics.desc.edited(),
-      };
-      desc_input.set_style(Style::from(text_flags));
-      desc_input.set_block(
-        desc_input.block().cloned().unwrap_or_default().border_style(Style::from(border_flags))
-      );
-      desc_input.toggle_cursor(border_flags.highlighted);
-      desc_input.render(desc_area, buf);
-    }
+    let right_shown = self.pane(Pane::Right).frame.is_some();
+    let [left_area, right_area] = Layout::horizontal([
+      Constraint::Fill(1),
+      Constraint::Fill(if right_shown { 1 } else { 0 }),
+    ]).areas(panes_area);
 
-    {
-      let mut text_textarea = self.text_textarea.clone();
-      let border_flags = StyleFlags {
-        enabled: true,
-        valid: true,
-        highlighted: self.focused_el == Focusable::Text,
-      };
-      let text_flags = StyleFlags {
-        enabled: true,
-        valid: true,
-        highlighted: tags.lyrics.text.edited(),
-      };
-      text_textarea.set_style(Style::from(text_flags));
-      text_textarea.set_block(
-        text_textarea.block().cloned().unwrap_or_default().border_style(Style::from(border_flags))
-      );
-      text_textarea.toggle_cursor(border_flags.highlighted);
-      text_textarea.render(text_area, buf);
+    for (pane, pane_area) in [
+      (Pane::Left, left_area),
+      (Pane::Right, right_area),
+    ] {
+      if pane == Pane::Right && !right_shown {
+        continue;
+      }
+      let lyrics = self.pane(pane).frame.and_then(|j| tags.lyrics.get(j).map(|l| (j, l)));
+      let [lang_area, desc_area, text_area] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(3),
+        Constraint::Fill(1),
+      ]).areas(pane_area);
+
+      for (el, area) in [
+        (Focusable::Lang(pane), lang_area),
+        (Focusable::Desc(pane), desc_area),
+        (self.text_el(pane), text_area),
+      ] {
+        let mut input = match el {
+          Focusable::Lang(_) => self.pane(pane).lang_input.clone(),
+          Focusable::Desc(_) => self.pane(pane).desc_input.clone(),
+          Focusable::Synced(_) => self.pane(pane).synced_textarea.clone(),
+          _ => self.pane(pane).text_textarea.clone(),
+        };
+        let edited = lyrics.is_some_and(|(_, l)| {
+          match el {
+            Focusable::Lang(_) => l.lang.edited(),
+            Focusable::Desc(_) => l.desc.edited(),
+            Focusable::Synced(_) => l.synced.edited(),
+            _ => l.text.edited(),
+          }
+        });
+        let valid = match el {
+          Focusable::Text(_) => true,
+          Focusable::Synced(_) => {
+            let untimed = lrc::parse(&input.lines().join("\n")).err();
+            if let Some(n) = untimed {
+              input.set_block(
+                input
+                  .block()
+                  .cloned()
+                  .unwrap_or_default()
+                  .title_bottom(format!("Line {} has no timestamp", n))
+              );
+            }
+            untimed.is_none()
+          }
+          _ => {
+            let error = lyrics.and_then(|(j, l)| {
+              let error = if matches!(el, Focusable::Lang(_)) { l.lang.error() } else { None };
+              error.or_else(|| tags.lyrics_error(j).map(String::from))
+            });
+            if let Some(error) = &error {
+              input.set_block(
+                input.block().cloned().unwrap_or_default().title_bottom(error.clone())
+              );
+            }
+            error.is_none()
+          }
+        };
+        let border_flags = StyleFlags {
+          enabled: true,
+          valid,
+          highlighted: self.focused_el == el,
+        };
+        let text_flags = StyleFlags {
+          enabled: true,
+          valid,
+          highlighted: edited,
+        };
+        input.set_style(Style::from(text_flags));
+        input.set_block(
+          input.block().cloned().unwrap_or_default().border_style(Style::from(border_flags))
+        );
+        input.toggle_cursor(border_flags.highlighted);
+        input.render(area, buf);
+      }
     }
 
     let footer_par = Paragraph::new(
       Line::from(
         [
+          if self.focused_el == Focusable::Frames {
+            [
+              Shortcut::new("A", "Add", Color::Gray).to_spans(),
+              Vec::from([Span::from(" :: ").dark_gray()]),
+              Shortcut::new("Del", "Remove", Color::Gray).to_spans(),
+              Vec::from([Span::from(" :: ").dark_gray()]),
+              Shortcut::new("Enter", "Open", Color::Gray).to_spans(),
+              Vec::from([Span::from(" :: ").dark_gray()]),
+              Shortcut::new("T", "Side by side", Color::Gray).to_spans(),
+              Vec::from([Span::from(" :: ").dark_gray()]),
+              Shortcut::new("I", "Import LRC", Color::Gray).to_spans(),
+              Vec::from([Span::from(" :: ").dark_gray()]),
+              Shortcut::new("E", "Export LRC", Color::Gray).to_spans(),
+              Vec::from([Span::from(" :: ").dark_gray()]),
+            ].concat()
+          } else {
+            Vec::new()
+          },
           Shortcut::new("Esc", "Back", Color::Gray).to_spans(),
           Vec::from([Span::from(" :: ").dark_gray()]),
+          Shortcut::new(
+            "Ctrl+T",
+            if self.synced { "Unsynced" } else { "Synced" },
+            Color::Gray
+          ).to_spans(),
+          Vec::from([Span::from(" :: ").dark_gray()]),
           Shortcut::new("Ctrl+R", "Reset field", Color::Gray).to_spans(),
         ].concat()
       )
@@ -1,2 +1,8 @@
+pub mod comments;
+pub mod frames;
+pub mod history;
 pub mod home;
+pub mod log;
 pub mod lyrics;
+pub mod pending;
+pub mod settings;
@@ -0,0 +1,355 @@
+use std::sync::mpsc::Sender;
+use crate::{
+  app::{ app::Command, state::State },
+  ui::{
+    home::screen::{ EditorFocusable, Focusable as HomeFocusable, HomeScreen },
+    shortcut::Shortcut,
+    ui_enums::Screen,
+    InputHandler,
+    StateDependentWidget,
+    UiState,
+    StyleFlags,
+  },
+};
+use crossterm::event::{ Event, KeyCode, KeyEventKind, KeyModifiers };
+use ratatui::{
+  buffer::Buffer,
+  layout::{ Constraint, Flex, Layout, Margin, Rect },
+  style::{ Color, Style, Stylize },
+  text::{ Line, Span },
+  widgets::{
+    Block,
+    BorderType,
+    Gauge,
+    List,
+    ListItem,
+    ListState,
+    Paragraph,
+    StatefulWidget,
+    Widget,
+    Wrap,
+  },
+};
+
+/// Files with unsaved edits, saved or reverted together.
+pub struct PendingScreen {
+  /// Position of the file under the cursor among the pending files.
+  pub cursor: usize,
+  /// Files marked for saving or reverting, indexes of the library.
+  pub marked: Vec<usize>,
+  /// Files saved so far and the number of files being saved.
+  pub progress: Option<(usize, usize)>,
+  /// Name and result of each file of the last save.
+  pub report: Vec<(String, Result<(), String>)>,
+}
+
+impl PendingScreen {
+  pub fn new() -> Self {
+    Self {
+      cursor: 0,
+      marked: Vec::new(),
+      progress: None,
+      report: Vec::new(),
+    }
+  }
+  /// The marked files, the one under the cursor when none is marked.
+  fn chosen(&self, pending: &[usize]) -> Vec<usize> {
+    let marked = pending
+      .iter()
+      .copied()
+      .filter(|i| self.marked.contains(i))
+      .collect::<Vec<_>>();
+    match (marked.is_empty(), pending.get(self.cursor)) {
+      (true, Some(&i)) => vec![i],
+      _ => marked,
+    }
+  }
+}
+
+impl InputHandler for PendingScreen {
+  fn handle_input(
+    &self,
+    state: &State,
+    ui_state: &UiState,
+    event: Event,
+    sender: Sender<Command>
+  ) -> bool {
+    // nothing changes until the save is done
+    if self.progress.is_some() {
+      return false;
+    }
+    let pending = state.pending();
+    let cursor = pending.get(self.cursor).copied();
+    match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+        match (key_event.code, key_event.modifiers) {
+          (KeyCode::Esc, _) => {
+            let el = cursor.map_or(HomeFocusable::Search, HomeFocusable::Table);
+            let tags = cursor.map(|i| &state.get_file(i).tags);
+            sender.send(Command::SetScreen(Screen::Home(HomeScreen::new(el, tags))));
+            true
+          }
+          (KeyCode::Enter, _) => {
+            let Some(i) = cursor else {
+              return false;
+            };
+            sender.send(
+              Command::SetScreen(
+                Screen::Home(
+                  HomeScreen::new(
+                    HomeFocusable::Editor(i, EditorFocusable::TitleInput),
+                    Some(&state.get_file(i).tags)
+                  )
+                )
+              )
+            );
+            true
+          }
+          (KeyCode::Up, _) if !pending.is_empty() => {
+            let j = if self.cursor == 0 { pending.len() - 1 } else { self.cursor - 1 };
+            sender.send(Command::SetPendingCursor(j));
+            true
+          }
+          (KeyCode::Down, _) if !pending.is_empty() => {
+            sender.send(Command::SetPendingCursor((self.cursor + 1) % pending.len()));
+            true
+          }
+          (KeyCode::Char(' '), _) => {
+            let Some(i) = cursor else {
+              return false;
+            };
+            sender.send(Command::TogglePendingMark(i));
+            true
+          }
+          (KeyCode::Char('a' | 'ф'), KeyModifiers::CONTROL) => {
+            sender.send(Command::MarkAllPending);
+            true
+          }
+          (KeyCode::Char('s' | 'ы'), KeyModifiers::NONE) if !pending.is_empty() => {
+            sender.send(Command::SavePending(self.chosen(&pending)));
+            true
+          }
+          (KeyCode::Char('S' | 'Ы'), _) if !pending.is_empty() => {
+            sender.send(Command::SavePending(pending));
+            true
+          }
+          (KeyCode::Char('r' | 'к'), KeyModifiers::NONE) if !pending.is_empty() => {
+            sender.send(Command::RevertPending(self.chosen(&pending)));
+            true
+          }
+          (KeyCode::Char('R' | 'К'), _) if !pending.is_empty() => {
+            sender.send(Command::RevertPending(pending));
+            true
+          }
+          _ => false,
+        }
+      }
+      _ => false,
+    }
+  }
+}
+
+impl StateDependentWidget for PendingScreen {
+  fn render_from_state(&self, area: Rect, buf: &mut Buffer, state: &State, ui_state: &UiState)
+    where Self: Sized
+  {
+    let report_height = if self.report.is_empty() { 0 } else { self.report.len().min(6) + 2 };
+    let [header_area, main_area, report_area, footer_area] = Layout::vertical([
+      Constraint::Length(2),
+      Constraint::Fill(1),
+      Constraint::Length(report_height as u16),
+      Constraint::Length(1),
+    ]).areas(area);
+    let footer_area = footer_area.inner(Margin::new(1, 0));
+    let [list_area, changes_area] = Layout::horizontal([
+      Constraint::Length(40),
+      Constraint::Fill(1),
+    ]).areas(main_area);
+    let [title_area, progress_area] = Layout::vertical([
+      Constraint::Length(1),
+      Constraint::Length(1),
+    ]).areas(header_area);
+
+    let pending = state.pending();
+    Line::from("Pending Changes").centered().render(title_area, buf);
+    match self.progress {
+      Some((done, total)) => {
+        let [progress_area] = Layout::horizontal([Constraint::Max(40)])
+          .flex(Flex::Center)
+          .areas(progress_area);
+        Gauge::default()
+          .gauge_style(Style::new().yellow().on_dark_gray())
+          .ratio((done as f64) / (total.max(1) as f64))
+          .label(format!("Saving {}/{}", done, total))
+          .render(progress_area, buf);
+      }
+      None => {
+        Line::from(match pending.len() {
+          1 => "1 file".to_string(),
+          n => format!("{} files", n),
+        })
+          .dark_gray()
+          .centered()
+          .render(progress_area, buf);
+      }
+    }
+
+    {
+      let items = pending
+        .iter()
+        .map(|&i| {
+          let file = state.get_file(i);
+          let marked = self.marked.contains(&i);
+          ListItem::new(
+            Line::from(
+              Vec::from([
+                if marked { Span::from("✓ ").cyan() } else { Span::from("  ") },
+                Span::from(file.name.clone()),
+                Span::from(format!(" {}", file.tags.changes().len())).dark_gray(),
+              ])
+            )
+          ).style(
+            Style::from(StyleFlags {
+              enabled: true,
+              valid: file.tags.valid(),
+              highlighted: true,
+            })
+          )
+        })
+        .collect::<Vec<_>>();
+      let list = List::new(items)
+        .highlight_style(Style::new().on_dark_gray().bold())
+        .block(
+          Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_top("Files")
+            .title_bottom(
+              if pending.is_empty() {
+                Line::from(" No pending changes ").dark_gray().centered()
+              } else if self.marked.is_empty() {
+                Line::default()
+              } else {
+                Line::from(format!(" {} marked ", self.marked.len())).cyan().centered()
+              }
+            )
+            .border_style(
+              Style::from(StyleFlags {
+                enabled: true,
+                valid: true,
+                highlighted: true,
+              })
+            )
+        );
+      let selected = (!pending.is_empty()).then(|| self.cursor.min(pending.len() - 1));
+      let mut list_state = ListState::default().with_selected(selected);
+      <List as StatefulWidget>::render(list, list_area, buf, &mut list_state);
+    }
+
+    {
+      let file = pending.get(self.cursor).map(|&i| state.get_file(i));
+      let lines = file
+        .map(|f| {
+          f.tags
+            .changes()
+            .into_iter()
+            .map(|change| {
+              Line::from(
+                Vec::from([
+                  Span::from(format!("{}: ", change.field)).dark_gray(),
+                  summary(change.original.as_ref()),
+                  Span::from(" → ").dark_gray(),
+                  summary(change.current.as_ref()).yellow(),
+                ])
+              )
+            })
+            .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+      Paragraph::new(lines)
+        .wrap(Wrap::default())
+        .block(
+          Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_top(file.map_or("Changes".to_string(), |f| f.name.clone()))
+            .title_bottom(
+              Line::from(
+                file
+                  .map(|f| f.tags.errors())
+                  .unwrap_or_default()
+                  .first()
+                  .cloned()
+                  .unwrap_or_default()
+              ).red()
+            )
+        )
+        .render(changes_area, buf);
+    }
+
+    if !self.report.is_empty() {
+      let failed = self.report
+        .iter()
+        .filter(|(_, res)| res.is_err())
+        .count();
+      let lines = self.report
+        .iter()
+        .map(|(name, res)| {
+          match res {
+            Ok(_) => Line::from(Vec::from([Span::from("✓ ").green(), Span::from(name.clone())])),
+            Err(e) => {
+              Line::from(
+                Vec::from([
+                  Span::from("✗ ").red(),
+                  Span::from(name.clone()),
+                  Span::from(format!(": {}", e.replace('\n', "; "))).red(),
+                ])
+              )
+            }
+          }
+        })
+        .collect::<Vec<_>>();
+      Paragraph::new(lines)
+        .block(
+          Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_top(
+              match failed {
+                0 => format!("Saved {} files", self.report.len()),
+                n => format!("Saved {} of {} files", self.report.len() - n, self.report.len()),
+              }
+            )
+        )
+        .render(report_area, buf);
+    }
+
+    Paragraph::new(
+      Line::from(
+        [
+          Shortcut::new("Space", "Mark", Color::Gray).to_spans(),
+          Vec::from([Span::from(" :: ").dark_gray()]),
+          Shortcut::new("S", "Save", Color::Yellow).to_spans(),
+          Vec::from([Span::from(" :: ").dark_gray()]),
+          Shortcut::new("Shift+S", "Save all", Color::Yellow).to_spans(),
+          Vec::from([Span::from(" :: ").dark_gray()]),
+          Shortcut::new("R", "Revert", Color::Gray).to_spans(),
+          Vec::from([Span::from(" :: ").dark_gray()]),
+          Shortcut::new("Shift+R", "Revert all", Color::Gray).to_spans(),
+          Vec::from([Span::from(" :: ").dark_gray()]),
+          Shortcut::new("Enter", "Edit", Color::Gray).to_spans(),
+          Vec::from([Span::from(" :: ").dark_gray()]),
+          Shortcut::new("Esc", "Back", Color::Gray).to_spans(),
+        ].concat()
+      )
+    )
+      .right_aligned()
+      .render(footer_area, buf);
+  }
+}
+
+/// A value on one line, multiline values are counted in lines.
+fn summary(value: Option<&String>) -> Span<'static> {
+  match value {
+    None => Span::from("—").dark_gray(),
+    Some(v) if v.contains('\n') => Span::from(format!("{} lines", v.lines().count())),
+    Some(v) => Span::from(v.clone()),
+  }
+}
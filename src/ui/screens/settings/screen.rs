@@ -0,0 +1,302 @@
+use std::sync::mpsc::Sender;
+use crate::{
+  app::{ app::Command, state::State, tag::WriteOptions },
+  ui::{
+    home::{ self, screen::HomeScreen },
+    shortcut::Shortcut,
+    ui_enums::Screen,
+    InputHandler,
+    StateDependentWidget,
+    UiState,
+    StyleFlags,
+  },
+};
+use crossterm::event::{ Event, KeyCode, KeyEventKind, KeyModifiers };
+use id3::Version;
+use ratatui::{
+  buffer::Buffer,
+  layout::{ Constraint, Flex, Layout, Margin, Rect },
+  style::{ Color, Style, Stylize },
+  text::{ Line, Span },
+  widgets::{ Block, BorderType, Paragraph, Widget },
+};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Row {
+  FileVersion,
+  FileEncoding,
+  Version,
+  Encoding,
+  KeepTimes,
+}
+
+impl Row {
+  pub const ALL: [Row; 5] = [
+    Row::FileVersion,
+    Row::FileEncoding,
+    Row::Version,
+    Row::Encoding,
+    Row::KeepTimes,
+  ];
+  /// Whether the row belongs to the settings of the selected file.
+  pub fn per_file(self) -> bool {
+    matches!(self, Row::FileVersion | Row::FileEncoding)
+  }
+  /// Value of the row in `options`, rows that are not write options have none.
+  pub fn value(self, options: &WriteOptions) -> String {
+    match self {
+      Row::FileVersion | Row::Version => options.version.to_string(),
+      Row::FileEncoding | Row::Encoding => options.encoding.to_string(),
+      Row::KeepTimes => String::new(),
+    }
+  }
+  /// Moves the row's write option to the next or previous value.
+  pub fn cycle(self, options: &mut WriteOptions, forward: bool) {
+    match self {
+      Row::FileVersion | Row::Version => {
+        options.version = options.version.cycle(forward);
+      }
+      Row::FileEncoding | Row::Encoding => {
+        options.encoding = options.encoding.cycle(forward);
+      }
+      Row::KeepTimes => {}
+    }
+  }
+}
+
+pub struct SettingsScreen {
+  /// File whose settings are shown along the global ones.
+  pub index: Option<usize>,
+  pub row: Row,
+}
+
+impl SettingsScreen {
+  pub fn new(index: Option<usize>) -> Self {
+    Self {
+      index,
+      row: if index.is_some() { Row::FileVersion } else { Row::Version },
+    }
+  }
+  fn rows(&self) -> Vec<Row> {
+    Row::ALL.into_iter()
+      .filter(|r| self.index.is_some() || !r.per_file())
+      .collect()
+  }
+}
+
+pub fn version_name(version: Option<Version>) -> String {
+  match version {
+    Some(Version::Id3v22) => "ID3v2.2".into(),
+    Some(Version::Id3v23) => "ID3v2.3".into(),
+    Some(Version::Id3v24) => "ID3v2.4".into(),
+    None => "No tag".into(),
+  }
+}
+
+impl InputHandler for SettingsScreen {
+  fn handle_input(
+    &self,
+    state: &State,
+    ui_state: &UiState,
+    event: Event,
+    sender: Sender<Command>
+  ) -> bool {
+    let rows = self.rows();
+    let i = rows.iter().position(|r| *r == self.row).unwrap_or_default();
+    match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+        match (key_event.code, key_event.modifiers) {
+          (KeyCode::Esc, _) => {
+            sender.send(
+              Command::SetScreen(
+                Screen::Home(
+                  HomeScreen::new(
+                    match self.index {
+                      Some(i) => home::screen::Focusable::Table(i),
+                      None => home::screen::Focusable::Search,
+                    },
+                    self.index.map(|i| &state.get_file(i).tags)
+                  )
+                )
+              )
+            );
+            true
+          }
+          (KeyCode::Up, _) => {
+            sender.send(Command::SelectSettingsRow(rows[(i + rows.len() - 1) % rows.len()]));
+            true
+          }
+          (KeyCode::Down, _) => {
+            sender.send(Command::SelectSettingsRow(rows[(i + 1) % rows.len()]));
+            true
+          }
+          (KeyCode::Left, _) => {
+            sender.send(Command::CycleSetting(self.row, false));
+            true
+          }
+          (KeyCode::Right | KeyCode::Enter, _) => {
+            sender.send(Command::CycleSetting(self.row, true));
+            true
+          }
+          (KeyCode::Delete, _) if self.row.per_file() => {
+            sender.send(Command::UseGlobalWriteOptions);
+            true
+          }
+          (KeyCode::Char('r' | 'к'), KeyModifiers::CONTROL) => {
+            sender.send(Command::ResetSetting(self.row));
+            true
+          }
+          (KeyCode::Char('z' | 'я'), KeyModifiers::CONTROL) if self.index.is_some() => {
+            sender.send(Command::Undo);
+            true
+          }
+          (KeyCode::Char('y' | 'н'), KeyModifiers::CONTROL) if self.index.is_some() => {
+            sender.send(Command::Redo);
+            true
+          }
+          _ => false,
+        }
+      }
+      _ => false,
+    }
+  }
+}
+
+impl StateDependentWidget for SettingsScreen {
+  fn render_from_state(&self, area: Rect, buf: &mut Buffer, state: &State, ui_state: &UiState)
+    where Self: Sized
+  {
+    let [header_area, main_area, footer_area] = Layout::vertical([
+      Constraint::Length(2),
+      Constraint::Fill(1),
+      Constraint::Length(1),
+    ]).areas(area);
+    let footer_area = footer_area.inner(Margin::new(1, 0));
+    let [main_area] = Layout::horizontal([Constraint::Max(60)])
+      .flex(Flex::Center)
+      .areas(main_area);
+    let [file_area, global_area] = Layout::vertical([
+      Constraint::Length(if self.index.is_some() { 6 } else { 0 }),
+      Constraint::Length(5),
+    ])
+      .flex(Flex::Start)
+      .areas(main_area);
+
+    Paragraph::new(vec![Line::from("Settings"), Line::from("").gray()])
+      .centered()
+      .render(header_area, buf);
+
+    let defaults = &state.settings.write_options;
+    let row_line = |row: Row, label: &'static str, value: Span<'static>| {
+      let selected = self.row == row;
+      let arrows = |s: &'static str| {
+        if selected { Span::from(s) } else { Span::from(" ") }
+      };
+      Line::from(
+        Vec::from([
+          Span::from(format!("{:<12}", label)).dark_gray(),
+          arrows("◂ "),
+          value,
+          arrows(" ▸"),
+        ])
+      ).style(if selected { Style::new().bold() } else { Style::new() })
+    };
+
+    if let Some(index) = self.index {
+      let file = state.get_file(index);
+      let tags = &file.tags;
+      let options = tags.write_options.current();
+      let value = |row: Row| {
+        match options {
+          Some(options) =>
+            Span::from(row.value(options)).style(
+              Style::from(StyleFlags {
+                enabled: true,
+                valid: true,
+                highlighted: tags.write_options.edited(),
+              })
+            ),
+          None => Span::from(format!("Global ({})", row.value(defaults))).dark_gray(),
+        }
+      };
+      Paragraph::new(
+        Vec::from([
+          Line::from(
+            Vec::from([
+              Span::from(format!("{:<12}", "Read as")).dark_gray(),
+              Span::from(version_name(tags.version)),
+            ])
+          ),
+          row_line(Row::FileVersion, "Write as", value(Row::FileVersion)),
+          row_line(Row::FileEncoding, "Encoding", value(Row::FileEncoding)),
+          Line::from(
+            Vec::from([
+              Span::from(format!("{:<12}", "Result")).dark_gray(),
+              Span::from(version_name(Some(tags.write_version(defaults)))),
+            ])
+          ),
+        ])
+      )
+        .block(
+          Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title_top(file.name.clone())
+            .border_style(
+              Style::from(StyleFlags {
+                enabled: true,
+                valid: true,
+                highlighted: self.row.per_file(),
+              })
+            )
+        )
+        .render(file_area, buf);
+    }
+
+    Paragraph::new(
+      Vec::from([
+        row_line(Row::Version, "Write as", Span::from(Row::Version.value(defaults))),
+        row_line(Row::Encoding, "Encoding", Span::from(Row::Encoding.value(defaults))),
+        row_line(
+          Row::KeepTimes,
+          "Keep times",
+          Span::from(if state.settings.keep_times { "On" } else { "Off" })
+        ),
+      ])
+    )
+      .block(
+        Block::bordered()
+          .border_type(BorderType::Rounded)
+          .title_top("All files")
+          .border_style(
+            Style::from(StyleFlags {
+              enabled: true,
+              valid: true,
+              highlighted: !self.row.per_file(),
+            })
+          )
+      )
+      .render(global_area, buf);
+
+    Paragraph::new(
+      Line::from(
+        [
+          if self.row.per_file() {
+            [
+              Shortcut::new("Del", "Use global", Color::Gray).to_spans(),
+              Vec::from([Span::from(" :: ").dark_gray()]),
+            ].concat()
+          } else {
+            Vec::new()
+          },
+          Shortcut::new("←→", "Change", Color::Gray).to_spans(),
+          Vec::from([Span::from(" :: ").dark_gray()]),
+          Shortcut::new("Esc", "Back", Color::Gray).to_spans(),
+          Vec::from([Span::from(" :: ").dark_gray()]),
+          Shortcut::new("Ctrl+R", "Reset", Color::Gray).to_spans(),
+        ].concat()
+      )
+    )
+      .right_aligned()
+      .render(footer_area, buf);
+  }
+}
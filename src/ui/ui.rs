@@ -1,4 +1,4 @@
-use std::{ io::Stdout, sync::mpsc::Sender };
+use std::{ cell::RefCell, io::Stdout, sync::mpsc::Sender };
 use bitflags::{ bitflags, bitflags_match, Flags };
 use crossterm::event::{ Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers };
 use ratatui::{
@@ -13,8 +13,18 @@ use ratatui::{
 use tui_textarea::TextArea;
 use crate::app::{ app::{ App, Command }, state::State, tag::SongTags };
 use super::{
+  cover::Graphics,
   modals::modal::{ enums::Modal, Modals },
-  screens::{ home::{ self, screen::HomeScreen }, lyrics::screen::LyricsScreen },
+  screens::{
+    comments::screen::CommentsScreen,
+    frames::screen::FramesScreen,
+    history::screen::HistoryScreen,
+    home::{ self, screen::HomeScreen },
+    log::screen::LogScreen,
+    lyrics::screen::LyricsScreen,
+    pending::screen::PendingScreen,
+    settings::screen::SettingsScreen,
+  },
 };
 
 pub trait StateDependentWidget {
@@ -34,18 +44,35 @@ pub trait InputHandler {
 
 pub mod ui_enums {
   use kinded::Kinded;
-  use crate::ui::screens::{ home::screen::HomeScreen, lyrics::screen::LyricsScreen };
+  use crate::ui::screens::{
+    comments::screen::CommentsScreen,
+    frames::screen::FramesScreen,
+    history::screen::HistoryScreen,
+    home::screen::HomeScreen,
+    log::screen::LogScreen,
+    lyrics::screen::LyricsScreen,
+    pending::screen::PendingScreen,
+    settings::screen::SettingsScreen,
+  };
 
   #[derive(Kinded)]
   pub enum Screen {
     Home(HomeScreen),
     Lyrics(LyricsScreen),
+    Comments(CommentsScreen),
+    Frames(FramesScreen),
+    Settings(SettingsScreen),
+    Pending(PendingScreen),
+    Log(LogScreen),
+    History(HistoryScreen),
   }
 }
 
 pub struct UiState {
   pub modals: Modals,
   pub screen: ui_enums::Screen,
+  /// Widgets register images here while rendering, they are sent to the terminal after the draw.
+  pub graphics: RefCell<Graphics>,
 }
 
 pub struct Ui {
@@ -60,6 +87,7 @@ impl Ui {
       state: UiState {
         modals: Modals::new(),
         screen: ui_enums::Screen::Home(HomeScreen::new(home::screen::Focusable::Search, None)),
+        graphics: RefCell::new(Graphics::new()),
       },
     }
   }
@@ -77,12 +105,39 @@ impl Ui {
           ui_enums::Screen::Lyrics(screen) => {
             screen.handle_input(state, &self.state, event, sender);
           }
+          ui_enums::Screen::Comments(screen) => {
+            screen.handle_input(state, &self.state, event, sender);
+          }
+          ui_enums::Screen::Frames(screen) => {
+            screen.handle_input(state, &self.state, event, sender);
+          }
+          ui_enums::Screen::Settings(screen) => {
+            screen.handle_input(state, &self.state, event, sender);
+          }
+          ui_enums::Screen::Pending(screen) => {
+            screen.handle_input(state, &self.state, event, sender);
+          }
+          ui_enums::Screen::Log(screen) => {
+            screen.handle_input(state, &self.state, event, sender);
+          }
+          ui_enums::Screen::History(screen) => {
+            screen.handle_input(state, &self.state, event, sender);
+          }
         }
       }
       _ => {}
     }
   }
   pub fn render(&mut self, state: &State) {
+    self.draw(state);
+    if self.state.graphics.borrow().needs_clear() {
+      self.term.clear();
+      self.draw(state);
+    }
+    self.state.graphics.get_mut().flush(self.term.backend_mut());
+  }
+  fn draw(&mut self, state: &State) {
+    self.state.graphics.get_mut().begin_frame();
     self.term.draw(|frame| {
       match &self.state.screen {
         ui_enums::Screen::Home(screen) => {
@@ -91,6 +146,24 @@ impl Ui {
         ui_enums::Screen::Lyrics(screen) => {
           screen.render_from_state(frame.area(), frame.buffer_mut(), state, &self.state);
         }
+        ui_enums::Screen::Comments(screen) => {
+          screen.render_from_state(frame.area(), frame.buffer_mut(), state, &self.state);
+        }
+        ui_enums::Screen::Frames(screen) => {
+          screen.render_from_state(frame.area(), frame.buffer_mut(), state, &self.state);
+        }
+        ui_enums::Screen::Settings(screen) => {
+          screen.render_from_state(frame.area(), frame.buffer_mut(), state, &self.state);
+        }
+        ui_enums::Screen::Pending(screen) => {
+          screen.render_from_state(frame.area(), frame.buffer_mut(), state, &self.state);
+        }
+        ui_enums::Screen::Log(screen) => {
+          screen.render_from_state(frame.area(), frame.buffer_mut(), state, &self.state);
+        }
+        ui_enums::Screen::History(screen) => {
+          screen.render_from_state(frame.area(), frame.buffer_mut(), state, &self.state);
+        }
       }
       for modal in self.state.modals.iter() {
         modal.render_ref(frame.area(), frame.buffer_mut());
@@ -108,6 +181,11 @@ impl Ui {
         };
       }
       ui_enums::Screen::Lyrics(lyrics_screen) => Some(lyrics_screen.index),
+      ui_enums::Screen::Comments(comments_screen) => Some(comments_screen.index),
+      ui_enums::Screen::Frames(frames_screen) => Some(frames_screen.index),
+      ui_enums::Screen::Settings(settings_screen) => settings_screen.index,
+      ui_enums::Screen::History(history_screen) => Some(history_screen.index),
+      ui_enums::Screen::Pending(_) | ui_enums::Screen::Log(_) => None,
     }
   }
   pub fn song_tags<'a>(&'a self, state: &'a State) -> Option<&'a SongTags> {
@@ -0,0 +1,355 @@
+use std::{
+  cell::RefCell,
+  env,
+  io::{ self, Write },
+  sync::{ Arc, Weak },
+};
+use base64::{ engine::general_purpose::STANDARD as BASE64, Engine };
+use crossterm::{ cursor::{ MoveTo, RestorePosition, SavePosition }, queue, terminal };
+use id3::frame::Picture;
+use image::{ imageops::FilterType, DynamicImage, ImageFormat, RgbImage };
+use ratatui::{
+  buffer::Buffer,
+  layout::Rect,
+  style::{ Color, Stylize },
+  text::Line,
+  widgets::{ Paragraph, Widget, Wrap },
+};
+
+/// How images are drawn in the terminal.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GraphicsProtocol {
+  Kitty,
+  Sixel,
+  HalfBlocks,
+}
+
+impl GraphicsProtocol {
+  /// Guesses the protocol from the environment. `TAGCHR_GRAPHICS` set to `kitty`, `sixel` or
+  /// `halfblocks` overrides the guess.
+  pub fn detect() -> Self {
+    let var = |name: &str| env::var(name).unwrap_or_default().to_lowercase();
+    match var("TAGCHR_GRAPHICS").as_str() {
+      "kitty" => {
+        return GraphicsProtocol::Kitty;
+      }
+      "sixel" => {
+        return GraphicsProtocol::Sixel;
+      }
+      "halfblocks" => {
+        return GraphicsProtocol::HalfBlocks;
+      }
+      _ => {}
+    }
+    let term = var("TERM");
+    let term_program = var("TERM_PROGRAM");
+    if
+      env::var("KITTY_WINDOW_ID").is_ok() ||
+      term.contains("kitty") ||
+      term.contains("ghostty") ||
+      ["wezterm", "ghostty"].contains(&term_program.as_str())
+    {
+      GraphicsProtocol::Kitty
+    } else if
+      term.contains("sixel") ||
+      ["foot", "foot-extra", "mlterm", "yaft-256color", "contour"].contains(&term.as_str()) ||
+      ["iterm.app", "mintty", "contour"].contains(&term_program.as_str())
+    {
+      GraphicsProtocol::Sixel
+    } else {
+      GraphicsProtocol::HalfBlocks
+    }
+  }
+}
+
+enum Encoded {
+  /// Top and bottom pixel colors of every cell, row by row.
+  Cells(Vec<Vec<(Color, Option<Color>)>>),
+  /// Escape sequence drawing the image at the top left corner of the area.
+  Escape(String),
+  Invalid,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct Placement {
+  /// Address of the picture, it stays unique while the picture is cached.
+  key: usize,
+  area: Rect,
+}
+
+/// The last encoded picture with the area it was requested for and the part of it the image takes.
+struct Cached {
+  /// Keeps the address of the picture from being reused by another one.
+  picture: Weak<Picture>,
+  area: Rect,
+  image_area: Rect,
+  encoded: Encoded,
+}
+
+/// Encodes cover images for the terminal and remembers which image is currently on screen, so
+/// escape sequences are only sent when the picture or its area changes.
+pub struct Graphics {
+  pub protocol: GraphicsProtocol,
+  cache: Option<Cached>,
+  requested: Option<Placement>,
+  shown: Option<Placement>,
+}
+
+impl Graphics {
+  pub fn new() -> Self {
+    Self {
+      protocol: GraphicsProtocol::detect(),
+      cache: None,
+      requested: None,
+      shown: None,
+    }
+  }
+  /// Must be called before every draw, images not rendered during the draw are taken down.
+  pub fn begin_frame(&mut self) {
+    self.requested = None;
+  }
+  /// A removed sixel image stays painted over the cells until the whole screen is redrawn.
+  pub fn needs_clear(&self) -> bool {
+    self.protocol == GraphicsProtocol::Sixel &&
+      self.shown.is_some() &&
+      self.shown != self.requested
+  }
+  /// Sends escape sequences for the image requested during the last draw.
+  pub fn flush(&mut self, w: &mut impl Write) -> io::Result<()> {
+    if self.shown == self.requested {
+      return Ok(());
+    }
+    if self.protocol == GraphicsProtocol::Kitty && self.shown.is_some() {
+      write!(w, "\x1b_Ga=d,d=a,q=2\x1b\\")?;
+    }
+    let cached = self.cache.as_ref().map(|c| (c.picture.as_ptr() as usize, &c.encoded));
+    if let (Some(placement), Some((key, Encoded::Escape(seq)))) = (self.requested, cached) {
+      if placement.key == key {
+        queue!(w, SavePosition, MoveTo(placement.area.x, placement.area.y))?;
+        w.write_all(seq.as_bytes())?;
+        queue!(w, RestorePosition)?;
+      }
+    }
+    w.flush()?;
+    self.shown = self.requested;
+    Ok(())
+  }
+  fn render_picture(&mut self, picture: &Arc<Picture>, area: Rect, buf: &mut Buffer) {
+    let key = Arc::as_ptr(picture) as usize;
+    let cached = self.cache.as_ref().is_some_and(|c| {
+      c.picture.as_ptr() as usize == key &&
+        c.area.width == area.width &&
+        c.area.height == area.height
+    });
+    if !cached {
+      let (encoded, image_area) = match image::load_from_memory(&picture.data) {
+        Ok(image) => self.encode(&image, area),
+        Err(_) => (Encoded::Invalid, Rect::default()),
+      };
+      self.cache = Some(Cached { picture: Arc::downgrade(picture), area, image_area, encoded });
+    }
+    let Cached { image_area, encoded, .. } = self.cache.as_ref().unwrap();
+    let image_area = Rect {
+      x: area.x + image_area.x,
+      y: area.y + image_area.y,
+      ..*image_area
+    };
+    match encoded {
+      Encoded::Cells(rows) => {
+        for (y, row) in rows.iter().enumerate() {
+          for (x, (top, bottom)) in row.iter().enumerate() {
+            let cell = &mut buf[(image_area.x + (x as u16), image_area.y + (y as u16))];
+            cell.set_symbol("▀").set_fg(*top).set_bg(bottom.unwrap_or(Color::Reset));
+          }
+        }
+      }
+      Encoded::Escape(_) => {
+        for y in image_area.top()..image_area.bottom() {
+          for x in image_area.left()..image_area.right() {
+            buf[(x, y)].set_skip(true);
+          }
+        }
+        self.requested = Some(Placement { key, area: image_area });
+      }
+      Encoded::Invalid => {
+        Paragraph::new(Line::from("Unsupported image").dark_gray().centered())
+          .wrap(Wrap::default())
+          .render(area, buf);
+      }
+    }
+  }
+  /// Encodes the image to fit `area`, returning it with the offset and size it takes in cells.
+  fn encode(&self, image: &DynamicImage, area: Rect) -> (Encoded, Rect) {
+    if self.protocol == GraphicsProtocol::HalfBlocks {
+      // every cell holds two vertically stacked pixels that are roughly square
+      let image = image
+        .resize(
+          (area.width as u32).max(1),
+          (area.height as u32).max(1) * 2,
+          FilterType::Triangle
+        )
+        .to_rgb8();
+      let rgb = |x: u32, y: u32| {
+        let [r, g, b] = image.get_pixel(x, y).0;
+        Color::Rgb(r, g, b)
+      };
+      let rows = (0..image.height().div_ceil(2))
+        .map(|y| {
+          (0..image.width())
+            .map(|x| {
+              let bottom = (y * 2 + 1 < image.height()).then(|| rgb(x, y * 2 + 1));
+              (rgb(x, y * 2), bottom)
+            })
+            .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+      let size = Rect::new(0, 0, image.width() as u16, image.height().div_ceil(2) as u16);
+      return (Encoded::Cells(rows), centered(size, area));
+    }
+    let (cell_w, cell_h) = cell_size();
+    let image = image
+      .resize(
+        (area.width as u32).max(1) * cell_w,
+        (area.height as u32).max(1) * cell_h,
+        FilterType::Triangle
+      )
+      .to_rgb8();
+    let size = Rect::new(
+      0,
+      0,
+      image.width().div_ceil(cell_w) as u16,
+      image.height().div_ceil(cell_h) as u16
+    );
+    let seq = match self.protocol {
+      GraphicsProtocol::Kitty => kitty(&image, size.width, size.height),
+      _ => sixel(&image),
+    };
+    match seq {
+      Some(seq) => (Encoded::Escape(seq), centered(size, area)),
+      None => (Encoded::Invalid, Rect::default()),
+    }
+  }
+}
+
+fn centered(size: Rect, area: Rect) -> Rect {
+  Rect {
+    x: area.width.saturating_sub(size.width) / 2,
+    y: area.height.saturating_sub(size.height) / 2,
+    width: size.width.min(area.width),
+    height: size.height.min(area.height),
+  }
+}
+
+/// Pixel size of a terminal cell, assumes 10x20 when the terminal does not report it.
+fn cell_size() -> (u32, u32) {
+  match terminal::window_size() {
+    Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => {
+      (((size.width / size.columns) as u32).max(1), ((size.height / size.rows) as u32).max(1))
+    }
+    _ => (10, 20),
+  }
+}
+
+fn kitty(image: &RgbImage, cols: u16, rows: u16) -> Option<String> {
+  let mut png = io::Cursor::new(Vec::new());
+  image.write_to(&mut png, ImageFormat::Png).ok()?;
+  let data = BASE64.encode(png.get_ref());
+  let chunks = data.as_bytes().chunks(4096).collect::<Vec<_>>();
+  let mut seq = String::new();
+  for (i, chunk) in chunks.iter().enumerate() {
+    let more = if i + 1 < chunks.len() { 1 } else { 0 };
+    let chunk = std::str::from_utf8(chunk).ok()?;
+    if i == 0 {
+      seq += &format!("\x1b_Ga=T,f=100,q=2,C=1,c={},r={},m={};{}\x1b\\", cols, rows, more, chunk);
+    } else {
+      seq += &format!("\x1b_Gm={};{}\x1b\\", more, chunk);
+    }
+  }
+  Some(seq)
+}
+
+/// Encodes the image as sixels using a fixed 6x6x6 color cube.
+fn sixel(image: &RgbImage) -> Option<String> {
+  let level = |v: u8| ((v as u32) * 5 + 127) / 255;
+  let index = |x: u32, y: u32| {
+    let [r, g, b] = image.get_pixel(x, y).0;
+    (level(r) * 36 + level(g) * 6 + level(b)) as usize
+  };
+  let mut seq = format!("\x1bPq\"1;1;{};{}", image.width(), image.height());
+  for i in 0..216 {
+    let percent = |v: usize| (v * 100) / 5;
+    seq += &format!("#{};2;{};{};{}", i, percent(i / 36), percent((i / 6) % 6), percent(i % 6));
+  }
+  for band in (0..image.height()).step_by(6) {
+    let band_height = (image.height() - band).min(6);
+    let mut colors = Vec::new();
+    for y in band..band + band_height {
+      for x in 0..image.width() {
+        let color = index(x, y);
+        if !colors.contains(&color) {
+          colors.push(color);
+        }
+      }
+    }
+    for color in colors {
+      seq += &format!("#{}", color);
+      let mut run: Option<(u8, usize)> = None;
+      let mut push = |seq: &mut String, (c, n): (u8, usize)| {
+        if n > 3 {
+          *seq += &format!("!{}{}", n, c as char);
+        } else {
+          seq.extend(std::iter::repeat(c as char).take(n));
+        }
+      };
+      for x in 0..image.width() {
+        let bits = (0..band_height)
+          .filter(|dy| index(x, band + dy) == color)
+          .fold(0u8, |bits, dy| bits | (1 << dy));
+        let c = 63 + bits;
+        run = match run {
+          Some((prev, n)) if prev == c => Some((prev, n + 1)),
+          Some(prev) => {
+            push(&mut seq, prev);
+            Some((c, 1))
+          }
+          None => Some((c, 1)),
+        };
+      }
+      if let Some(run) = run {
+        push(&mut seq, run);
+      }
+      seq += "$";
+    }
+    seq += "-";
+  }
+  seq += "\x1b\\";
+  Some(seq)
+}
+
+/// Embedded cover of the selected file, drawn with the terminal's graphics protocol.
+pub struct CoverArt<'a> {
+  picture: Option<&'a Arc<Picture>>,
+  graphics: &'a RefCell<Graphics>,
+}
+
+impl<'a> CoverArt<'a> {
+  pub fn new(picture: Option<&'a Arc<Picture>>, graphics: &'a RefCell<Graphics>) -> Self {
+    Self { picture, graphics }
+  }
+}
+
+impl Widget for CoverArt<'_> {
+  fn render(self, area: Rect, buf: &mut Buffer) {
+    if area.is_empty() {
+      return;
+    }
+    match self.picture {
+      Some(picture) => self.graphics.borrow_mut().render_picture(picture, area, buf),
+      None => {
+        Paragraph::new(Line::from("No front cover").dark_gray().centered())
+          .wrap(Wrap::default())
+          .render(area, buf);
+      }
+    }
+  }
+}
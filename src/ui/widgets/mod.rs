@@ -3,3 +3,4 @@ pub mod text_area;
 pub mod widget;
 pub mod shortcut;
 pub mod modals;
+pub mod cover;
@@ -0,0 +1,223 @@
+use std::sync::mpsc::Sender;
+
+use crossterm::event::{ Event, KeyCode, KeyEventKind };
+use ratatui::{
+  buffer::Buffer,
+  layout::{ Constraint, Flex, Layout, Margin, Rect },
+  style::{ Style, Stylize },
+  text::{ Line, Span },
+  widgets::{ Block, BorderType, Borders, Clear, Paragraph, Table, Widget, WidgetRef },
+};
+use crate::{
+  app::{ app::Command, state::State, tag::{ Change, Conflict, Id3v1Action } },
+  ui::{ InputHandler, UiState },
+};
+use super::{ modal::{ Modal, ModalOption, ModalOptions }, save_tags::change_lines };
+
+/// Asks what to do with edits of files something else changed since they were read.
+pub struct ExternalChangesModal {
+  files: usize,
+  /// What changed on disk in every file, fields changed on both sides side by side.
+  lines: Vec<Line<'static>>,
+  scroll: usize,
+  options: ModalOptions,
+}
+
+impl ExternalChangesModal {
+  /// `changes` holds the name of each file of `indexes` with the fields changed on disk and the
+  /// ones among them edited here too.
+  pub fn new(
+    indexes: Vec<usize>,
+    id3v1_action: Id3v1Action,
+    changes: &[(String, Vec<Change>, Vec<Conflict>)]
+  ) -> Self {
+    Self {
+      files: changes.len(),
+      lines: merge_lines(changes),
+      scroll: 0,
+      options: ModalOptions::new([
+        ModalOption::new("Reload and re-apply", Command::ReloadTags(indexes.clone())),
+        ModalOption::new("Overwrite", Command::OverwriteTags(indexes, id3v1_action)),
+        ModalOption::new("Cancel", Command::CloseLastModal),
+      ]),
+    }
+  }
+}
+
+impl InputHandler for ExternalChangesModal {
+  fn handle_input(
+    &self,
+    state: &State,
+    ui_state: &UiState,
+    event: Event,
+    sender: Sender<Command>
+  ) -> bool {
+    match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+        let last = self.lines.len().saturating_sub(1);
+        match key_event.code {
+          KeyCode::Esc => {
+            sender.send(Command::CloseLastModal);
+            true
+          }
+          KeyCode::Up => {
+            sender.send(Command::SetModalCursor(self.scroll.saturating_sub(1)));
+            true
+          }
+          KeyCode::Down => {
+            sender.send(Command::SetModalCursor((self.scroll + 1).min(last)));
+            true
+          }
+          KeyCode::PageUp => {
+            sender.send(Command::SetModalCursor(self.scroll.saturating_sub(10)));
+            true
+          }
+          KeyCode::PageDown => {
+            sender.send(Command::SetModalCursor((self.scroll + 10).min(last)));
+            true
+          }
+          KeyCode::Left => {
+            sender.send(Command::SetModalOption(self.options.prev()));
+            true
+          }
+          KeyCode::Right => {
+            sender.send(Command::SetModalOption(self.options.next()));
+            true
+          }
+          KeyCode::Enter => {
+            sender.send(Command::ExecuteModalOption(self.options.current()));
+            sender.send(Command::CloseLastModal);
+            true
+          }
+          _ => false,
+        }
+      }
+      _ => false,
+    }
+  }
+}
+
+impl Modal for ExternalChangesModal {
+  fn options(&self) -> Option<&ModalOptions> {
+    Some(&self.options)
+  }
+  fn options_mut(&mut self) -> Option<&mut ModalOptions> {
+    Some(&mut self.options)
+  }
+  fn cursor_mut(&mut self) -> Option<&mut usize> {
+    Some(&mut self.scroll)
+  }
+}
+
+/// The fields changed on both sides of each file with the three values, then the other fields
+/// changed on disk.
+fn merge_lines(changes: &[(String, Vec<Change>, Vec<Conflict>)]) -> Vec<Line<'static>> {
+  let mut lines = Vec::new();
+  for (name, external, conflicts) in changes {
+    if !lines.is_empty() {
+      lines.push(Line::default());
+    }
+    lines.push(Line::from(name.clone()).bold());
+    if external.is_empty() {
+      lines.push(Line::from("No field changed, the audio or the file times did").dark_gray());
+    }
+    if !conflicts.is_empty() {
+      lines.push(Line::from("Changed on both sides").yellow());
+    }
+    for conflict in conflicts {
+      lines.push(Line::from(format!("{}:", conflict.field)).dark_gray());
+      for (label, value, style) in [
+        ("was", &conflict.original, Style::new().dark_gray()),
+        ("yours", &conflict.mine, Style::new().yellow()),
+        ("on disk", &conflict.theirs, Style::new().cyan()),
+      ] {
+        lines.push(
+          Line::from(
+            Vec::from([
+              Span::from(format!("  {:<9}", label)).dark_gray(),
+              summary(value.as_ref()).patch_style(style),
+            ])
+          )
+        );
+      }
+    }
+    let others = external
+      .iter()
+      .filter(|c| !conflicts.iter().any(|conflict| conflict.field == c.field))
+      .collect::<Vec<_>>();
+    if !others.is_empty() {
+      lines.push(Line::from("Changed on disk").dark_gray());
+    }
+    for change in others {
+      lines.extend(change_lines(change));
+    }
+  }
+  lines
+}
+
+/// A value on one line, multiline values are counted in lines.
+fn summary(value: Option<&String>) -> Span<'static> {
+  match value {
+    None => Span::from("—"),
+    Some(v) if v.contains('\n') => Span::from(format!("{} lines", v.lines().count())),
+    Some(v) => Span::from(v.clone()),
+  }
+}
+
+impl WidgetRef for ExternalChangesModal {
+  fn render_ref(&self, area: Rect, buf: &mut Buffer) where Self: Sized {
+    let [area] = Layout::vertical([Constraint::Max((self.lines.len() as u16).saturating_add(10))])
+      .flex(Flex::Center)
+      .areas(
+        Layout::horizontal([Constraint::Max(90)])
+          .flex(Flex::Center)
+          .areas::<1>(area)[0]
+      );
+    let [summary_area, diff_area, options_area] = Layout::vertical([
+      Constraint::Length(2),
+      Constraint::Fill(1),
+      Constraint::Length(1),
+    ])
+      .spacing(1)
+      .areas(area.inner(Margin::new(1, 1)));
+
+    Clear.render(area, buf);
+    Block::bordered()
+      .border_type(BorderType::Rounded)
+      .title_top(Line::from(" Changed on disk ").centered())
+      .render(area, buf);
+    Paragraph::new(
+      Vec::from([
+        Line::from(
+          Vec::from([
+            Span::from(match self.files {
+              1 => "1 file".to_string(),
+              n => format!("{} files", n),
+            }).yellow(),
+            Span::from(" changed on disk since it was read"),
+          ])
+        ),
+        Line::from("Re-applying keeps your edits on top of the file as it is now").dark_gray(),
+      ])
+    )
+      .centered()
+      .render(summary_area, buf);
+    let scrollable = self.lines.len() > (diff_area.height as usize);
+    Paragraph::new(self.lines.clone())
+      .scroll((self.scroll as u16, 0))
+      .block(
+        Block::bordered()
+          .borders(Borders::TOP | Borders::BOTTOM)
+          .border_style(Style::new().dark_gray())
+          .title_bottom(
+            if scrollable {
+              Line::from(" ↑↓ Scroll ").dark_gray().right_aligned()
+            } else {
+              Line::default()
+            }
+          )
+      )
+      .render(diff_area.inner(Margin::new(1, 0)), buf);
+    Table::from(&self.options).render(options_area.inner(Margin::new(1, 0)), buf);
+  }
+}
@@ -0,0 +1,203 @@
+use std::sync::mpsc::Sender;
+
+use crossterm::event::{ Event, KeyCode, KeyEventKind };
+use ratatui::{
+  buffer::Buffer,
+  layout::{ Constraint, Flex, Layout, Margin, Rect },
+  style::{ Color, Style, Stylize },
+  text::{ Line, Span },
+  widgets::{
+    Block,
+    BorderType,
+    Clear,
+    List,
+    ListItem,
+    ListState,
+    StatefulWidget,
+    Widget,
+    WidgetRef,
+  },
+};
+use tui_textarea::TextArea;
+use crate::{
+  app::{ app::Command, genre, state::State },
+  ui::{ shortcut::Shortcut, text_area::TextAreaTrait, InputHandler, UiState },
+};
+use super::modal::{ Modal, ModalOptions };
+
+/// Picks a genre for a file, suggesting standard genres and the ones used in the library.
+pub struct GenreModal {
+  index: usize,
+  /// Genres of the file as shown in the Genre input.
+  current: String,
+  /// Genres of the library with the number of files using them.
+  library: Vec<(String, usize)>,
+  input: TextArea<'static>,
+  cursor: usize,
+}
+
+impl GenreModal {
+  pub fn new(index: usize, current: String, library: Vec<(String, usize)>) -> Self {
+    Self {
+      index,
+      current,
+      library,
+      input: {
+        let mut input = TextArea::new(Vec::new());
+        input.set_block(Block::bordered().border_type(BorderType::Rounded).title_top("Search"));
+        input.set_cursor_line_style(Style::new());
+        input
+      },
+      cursor: 0,
+    }
+  }
+  fn suggestions(&self) -> Vec<(String, usize)> {
+    genre::suggest(&self.input.lines()[0], &self.library)
+  }
+  /// The suggestion under the cursor, the typed text when nothing matches.
+  fn choice(&self) -> Option<String> {
+    match self.suggestions().into_iter().nth(self.cursor) {
+      Some((genre, _)) => Some(genre),
+      None => Some(self.input.lines()[0].trim().to_string()).filter(|g| !g.is_empty()),
+    }
+  }
+}
+
+impl Modal for GenreModal {
+  fn options(&self) -> Option<&ModalOptions> {
+    None
+  }
+  fn options_mut(&mut self) -> Option<&mut ModalOptions> {
+    None
+  }
+  fn input_mut(&mut self) -> Option<&mut TextArea<'static>> {
+    Some(&mut self.input)
+  }
+  fn cursor_mut(&mut self) -> Option<&mut usize> {
+    Some(&mut self.cursor)
+  }
+}
+
+impl InputHandler for GenreModal {
+  fn handle_input(
+    &self,
+    state: &State,
+    ui_state: &UiState,
+    event: Event,
+    sender: Sender<Command>
+  ) -> bool {
+    match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+        let suggestions = self.suggestions().len();
+        match key_event.code {
+          KeyCode::Esc => {
+            sender.send(Command::CloseLastModal);
+            true
+          }
+          KeyCode::Up if suggestions > 0 => {
+            let cursor = if self.cursor == 0 { suggestions - 1 } else { self.cursor - 1 };
+            sender.send(Command::SetModalCursor(cursor));
+            true
+          }
+          KeyCode::Down if suggestions > 0 => {
+            sender.send(Command::SetModalCursor((self.cursor + 1) % suggestions));
+            true
+          }
+          code @ (KeyCode::Enter | KeyCode::Tab) => {
+            let Some(genre) = self.choice() else {
+              return false;
+            };
+            sender.send(Command::CloseLastModal);
+            sender.send(Command::PickGenre(self.index, genre, code == KeyCode::Tab));
+            true
+          }
+          _ => {
+            sender.send(Command::HandleModalInput(key_event));
+            true
+          }
+        }
+      }
+      _ => false,
+    }
+  }
+}
+
+impl WidgetRef for GenreModal {
+  fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+    let [area] = Layout::vertical([Constraint::Max(20)])
+      .flex(Flex::Center)
+      .areas(
+        Layout::horizontal([Constraint::Max(50)])
+          .flex(Flex::Center)
+          .areas::<1>(area)[0]
+      );
+    let [current_area, input_area, list_area, hint_area] = Layout::vertical([
+      Constraint::Length(1),
+      Constraint::Length(3),
+      Constraint::Fill(1),
+      Constraint::Length(1),
+    ]).areas(area.inner(Margin::new(1, 1)));
+
+    Clear.render(area, buf);
+    Block::bordered()
+      .border_type(BorderType::Rounded)
+      .title_top(Line::from(" Genre ").centered())
+      .render(area, buf);
+    Line::from(
+      Vec::from([
+        Span::from("Current: ").dark_gray(),
+        Span::from(if self.current.is_empty() { "—".to_string() } else { self.current.clone() }),
+      ])
+    ).render(current_area, buf);
+    let mut input = self.input.clone();
+    input.toggle_cursor(true);
+    input.render(input_area, buf);
+
+    let items = self
+      .suggestions()
+      .into_iter()
+      .map(|(genre, count)| {
+        ListItem::new(
+          Line::from(
+            Vec::from([
+              Span::from(genre),
+              if count > 0 {
+                Span::from(format!("  {} in library", count)).dark_gray()
+              } else {
+                Span::default()
+              },
+            ])
+          )
+        )
+      })
+      .collect::<Vec<_>>();
+    let empty = items.is_empty();
+    let list = List::new(items)
+      .highlight_style(Style::new().on_dark_gray().bold())
+      .block(
+        Block::bordered()
+          .border_type(BorderType::Rounded)
+          .title_bottom(
+            if empty {
+              Line::from(" Enter uses the typed genre ").dark_gray().centered()
+            } else {
+              Line::default()
+            }
+          )
+      );
+    let mut list_state = ListState::default().with_selected(Some(self.cursor));
+    <List as StatefulWidget>::render(list, list_area, buf, &mut list_state);
+
+    Line::from(
+      [
+        Shortcut::new("Enter", "Set", Color::Gray).to_spans(),
+        Vec::from([Span::from(" :: ").dark_gray()]),
+        Shortcut::new("Tab", "Add", Color::Gray).to_spans(),
+        Vec::from([Span::from(" :: ").dark_gray()]),
+        Shortcut::new("Esc", "Cancel", Color::Gray).to_spans(),
+      ].concat()
+    )
+      .right_aligned()
+      .render(hint_area, buf);
+  }
+}
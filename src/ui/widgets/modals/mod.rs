@@ -2,3 +2,7 @@ pub mod modal;
 pub mod help;
 pub mod save_tags;
 pub mod save_result;
+pub mod path;
+pub mod genre;
+pub mod quit;
+pub mod external_changes;
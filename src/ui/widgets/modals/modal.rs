@@ -8,12 +8,17 @@ use ratatui::{
   widgets::{ Block, BorderType, Borders, Cell, Clear, Paragraph, Row, Table, Widget, WidgetRef },
   Frame,
 };
+use tui_textarea::TextArea;
 use crate::{ app::{ app::Command, state::State }, ui::{ InputHandler, UiState } };
 use super::{ help::HelpModal, save_result::SaveTagsResultModal, save_tags::ConfirmSaveTagsModal };
 
 pub mod enums {
   use crate::ui::modals::{
+    external_changes::ExternalChangesModal,
+    genre::GenreModal,
     help::HelpModal,
+    path::PathModal,
+    quit::QuitModal,
     save_result::SaveTagsResultModal,
     save_tags::ConfirmSaveTagsModal,
   };
@@ -22,6 +27,10 @@ pub mod enums {
     ConfirmSaveTags(ConfirmSaveTagsModal),
     SaveTagsResult(SaveTagsResultModal),
     Help(HelpModal),
+    Path(PathModal),
+    Genre(GenreModal),
+    Quit(QuitModal),
+    ExternalChanges(ExternalChangesModal),
   }
 }
 
@@ -99,6 +108,14 @@ impl ModalOption {
 pub trait Modal: WidgetRef + InputHandler {
   fn options(&self) -> Option<&ModalOptions>;
   fn options_mut(&mut self) -> Option<&mut ModalOptions>;
+  /// Text input of modals that ask the user to type something.
+  fn input_mut(&mut self) -> Option<&mut TextArea<'static>> {
+    None
+  }
+  /// Highlighted entry of modals listing entries that follow the input.
+  fn cursor_mut(&mut self) -> Option<&mut usize> {
+    None
+  }
 }
 
 pub struct Modals(Vec<Box<dyn Modal>>);
@@ -123,6 +140,10 @@ impl Modals {
       enums::Modal::Help(modal) => Box::new(modal),
       enums::Modal::ConfirmSaveTags(modal) => Box::new(modal),
       enums::Modal::SaveTagsResult(modal) => Box::new(modal),
+      enums::Modal::Path(modal) => Box::new(modal),
+      enums::Modal::Genre(modal) => Box::new(modal),
+      enums::Modal::Quit(modal) => Box::new(modal),
+      enums::Modal::ExternalChanges(modal) => Box::new(modal),
     });
   }
   pub fn iter(&self) -> impl Iterator<Item = &Box<dyn Modal>> {
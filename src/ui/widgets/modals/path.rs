@@ -0,0 +1,142 @@
+use std::sync::mpsc::Sender;
+
+use crossterm::event::{ Event, KeyCode, KeyEventKind };
+use ratatui::{
+  buffer::Buffer,
+  layout::{ Constraint, Flex, Layout, Margin, Rect },
+  style::{ Color, Style, Stylize },
+  text::{ Line, Span },
+  widgets::{ Block, BorderType, Clear, Widget, WidgetRef },
+};
+use tui_textarea::TextArea;
+use crate::{
+  app::{ app::Command, state::State },
+  ui::{ shortcut::Shortcut, text_area::TextAreaTrait, InputHandler, UiState },
+};
+use super::modal::{ Modal, ModalOptions };
+
+/// What the entered path is used for.
+#[derive(Clone, Copy)]
+pub enum PathPurpose {
+  ImportPicture(usize),
+  ReplacePicture(usize, usize),
+  ExportPicture(usize, usize),
+  ImportLrc(usize, Option<usize>),
+  ExportLrc(usize, usize),
+}
+
+pub struct PathModal {
+  purpose: PathPurpose,
+  input: TextArea<'static>,
+}
+
+impl PathModal {
+  pub fn new(purpose: PathPurpose, path: impl Into<String>) -> Self {
+    Self {
+      purpose,
+      input: {
+        let mut input = TextArea::new(Vec::new());
+        input.set_block(Block::bordered().border_type(BorderType::Rounded).title_top("Path"));
+        input.set_cursor_line_style(Style::new());
+        input.set_text(path.into());
+        input
+      },
+    }
+  }
+  fn title(&self) -> &'static str {
+    match self.purpose {
+      PathPurpose::ImportPicture(..) => " Add picture ",
+      PathPurpose::ReplacePicture(..) => " Replace picture ",
+      PathPurpose::ExportPicture(..) => " Export picture ",
+      PathPurpose::ImportLrc(..) => " Import LRC ",
+      PathPurpose::ExportLrc(..) => " Export LRC ",
+    }
+  }
+}
+
+impl Modal for PathModal {
+  fn options(&self) -> Option<&ModalOptions> {
+    None
+  }
+  fn options_mut(&mut self) -> Option<&mut ModalOptions> {
+    None
+  }
+  fn input_mut(&mut self) -> Option<&mut TextArea<'static>> {
+    Some(&mut self.input)
+  }
+}
+
+impl InputHandler for PathModal {
+  fn handle_input(
+    &self,
+    state: &State,
+    ui_state: &UiState,
+    event: Event,
+    sender: Sender<Command>
+  ) -> bool {
+    match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+        match key_event.code {
+          KeyCode::Esc => {
+            sender.send(Command::CloseLastModal);
+            true
+          }
+          KeyCode::Enter => {
+            let path = self.input.lines()[0].trim().to_string();
+            if path.is_empty() {
+              return false;
+            }
+            sender.send(Command::CloseLastModal);
+            sender.send(match self.purpose {
+              PathPurpose::ImportPicture(i) => Command::ImportPicture(i, None, path),
+              PathPurpose::ReplacePicture(i, j) => Command::ImportPicture(i, Some(j), path),
+              PathPurpose::ExportPicture(i, j) => Command::ExportPicture(i, j, path),
+              PathPurpose::ImportLrc(i, j) => Command::ImportLrc(i, j, path),
+              PathPurpose::ExportLrc(i, j) => Command::ExportLrc(i, j, path),
+            });
+            true
+          }
+          _ => {
+            sender.send(Command::HandleModalInput(key_event));
+            true
+          }
+        }
+      }
+      _ => false,
+    }
+  }
+}
+
+impl WidgetRef for PathModal {
+  fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+    let [area] = Layout::vertical([Constraint::Length(7)])
+      .flex(Flex::Center)
+      .areas(
+        Layout::horizontal([Constraint::Max(70)])
+          .flex(Flex::Center)
+          .areas::<1>(area)[0]
+      );
+    let [input_area, hint_area] = Layout::vertical([
+      Constraint::Length(3),
+      Constraint::Length(1),
+    ]).areas(area.inner(Margin::new(1, 1)));
+
+    Clear.render(area, buf);
+    Block::bordered()
+      .border_type(BorderType::Rounded)
+      .title_top(Line::from(self.title()).centered())
+      .render(area, buf);
+    let mut input = self.input.clone();
+    input.toggle_cursor(true);
+    input.render(input_area, buf);
+    Line::from(
+      [
+        Shortcut::new("Enter", "Confirm", Color::Gray).to_spans(),
+        Vec::from([Span::from(" :: ").dark_gray()]),
+        Shortcut::new("Esc", "Cancel", Color::Gray).to_spans(),
+      ].concat()
+    )
+      .right_aligned()
+      .render(hint_area, buf);
+  }
+}
@@ -0,0 +1,133 @@
+use std::sync::mpsc::Sender;
+
+use crossterm::event::{ Event, KeyCode, KeyEventKind };
+use ratatui::{
+  buffer::Buffer,
+  layout::{ Constraint, Flex, Layout, Margin, Rect },
+  style::{ Style, Stylize },
+  text::{ Line, Span },
+  widgets::{ Block, BorderType, Borders, Clear, Paragraph, Table, Widget, WidgetRef, Wrap },
+};
+use crate::{ app::{ app::Command, state::State }, ui::{ InputHandler, UiState } };
+use super::modal::{ Modal, ModalOption, ModalOptions };
+
+/// Asks what to do with unsaved edits before quitting.
+pub struct QuitModal {
+  /// Number of files with unsaved edits.
+  unsaved: usize,
+  options: ModalOptions,
+}
+
+impl QuitModal {
+  pub fn new(unsaved: usize) -> Self {
+    Self {
+      unsaved,
+      options: ModalOptions::new([
+        ModalOption::new("Save all and quit", Command::SaveAllAndQuit),
+        ModalOption::new("Discard and quit", Command::ForceQuit),
+        ModalOption::new("Cancel", Command::CloseLastModal),
+      ]),
+    }
+  }
+}
+
+impl InputHandler for QuitModal {
+  fn handle_input(
+    &self,
+    state: &State,
+    ui_state: &UiState,
+    event: Event,
+    sender: Sender<Command>
+  ) -> bool {
+    match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+        match key_event.code {
+          KeyCode::Esc => {
+            sender.send(Command::CloseLastModal);
+            true
+          }
+          KeyCode::Left => {
+            sender.send(Command::SetModalOption(self.options.prev()));
+            true
+          }
+          KeyCode::Right => {
+            sender.send(Command::SetModalOption(self.options.next()));
+            true
+          }
+          KeyCode::Enter => {
+            sender.send(Command::ExecuteModalOption(self.options.current()));
+            sender.send(Command::CloseLastModal);
+            true
+          }
+          _ => false,
+        }
+      }
+      _ => false,
+    }
+  }
+}
+
+impl Modal for QuitModal {
+  fn options(&self) -> Option<&ModalOptions> {
+    Some(&self.options)
+  }
+  fn options_mut(&mut self) -> Option<&mut ModalOptions> {
+    Some(&mut self.options)
+  }
+}
+
+impl WidgetRef for QuitModal {
+  fn render_ref(&self, area: Rect, buf: &mut Buffer) where Self: Sized {
+    let [area] = Layout::vertical([Constraint::Max(9)])
+      .flex(Flex::Center)
+      .areas(
+        Layout::horizontal([Constraint::Max(64)])
+          .flex(Flex::Center)
+          .areas::<1>(area)[0]
+      );
+    let [content_area, options_area] = Layout::vertical([
+      Constraint::Fill(1),
+      Constraint::Length(1),
+    ])
+      .spacing(1)
+      .areas(area.inner(Margin::new(1, 1)));
+
+    Clear.render(area, buf);
+    Block::bordered()
+      .border_type(BorderType::Rounded)
+      .title_top(Line::from(" Quit ").centered())
+      .render(area, buf);
+    Paragraph::new(
+      Vec::from([
+        Line::default(),
+        Line::from(
+          Vec::from([
+            Span::from(match self.unsaved {
+              1 => "1 file".to_string(),
+              n => format!("{} files", n),
+            }).yellow(),
+            Span::from(if self.unsaved == 1 { " has" } else { " have" }),
+            Span::from(" unsaved edits"),
+          ])
+        ),
+        Line::from("Discarded edits cannot be brought back").dark_gray(),
+      ])
+    )
+      .centered()
+      .wrap(Wrap::default())
+      .render(content_area, buf);
+    Block::bordered()
+      .borders(Borders::TOP)
+      .border_style(Style::new())
+      .render(
+        Rect {
+          x: content_area.x,
+          y: content_area.y + content_area.height,
+          width: content_area.width,
+          height: 1,
+        },
+        buf
+      );
+    Table::from(&self.options).render(options_area.inner(Margin::new(1, 0)), buf);
+  }
+}
@@ -6,7 +6,7 @@ use ratatui::{
   layout::{ Constraint, Flex, Layout, Margin, Offset, Rect },
   style::{ Style, Stylize },
   text::Line,
-  widgets::{ Block, BorderType, Borders, Clear, Paragraph, Table, Widget, WidgetRef },
+  widgets::{ Block, BorderType, Borders, Clear, Paragraph, Table, Widget, WidgetRef, Wrap },
 };
 use crate::{ app::{ app::Command, state::State }, ui::{ InputHandler, UiState } };
 
@@ -14,6 +14,8 @@ use super::modal::{ Modal, ModalOption, ModalOptions };
 
 pub struct SaveTagsResultModal {
   res: String,
+  /// The error in full, one line per file when several were saved.
+  details: Vec<String>,
   pub options: ModalOptions,
 }
 
@@ -22,7 +24,11 @@ impl SaveTagsResultModal {
     Self {
       res: match res.clone() {
         Ok(_) => "Saved".into(),
-        Err(err) => "Something went wrong".into(),
+        Err(_) => "Something went wrong".into(),
+      },
+      details: match &res {
+        Ok(_) => Vec::new(),
+        Err(err) => err.lines().map(String::from).collect(),
       },
       options: ModalOptions::new([
         ModalOption::new(
@@ -35,6 +41,29 @@ impl SaveTagsResultModal {
       ]),
     }
   }
+  /// Result of something other than a save, `done` is shown when it went fine and `failed` above
+  /// the error otherwise.
+  pub fn message(done: &str, failed: &str, res: Result<(), String>) -> Self {
+    let mut modal = Self::new(res.clone());
+    modal.res = if res.is_ok() { done.into() } else { failed.into() };
+    modal.options = ModalOptions::new([ModalOption::new("OK", Command::CloseLastModal)]);
+    modal
+  }
+  /// Result of saving several files, `results` holds the name of each file with its result.
+  pub fn batch(results: Vec<(String, Result<(), String>)>) -> Self {
+    let total = results.len();
+    let failed = results
+      .into_iter()
+      .filter_map(|(name, res)| res.err().map(|e| format!("{}: {}", name, e.replace('\n', "; "))))
+      .collect::<Vec<_>>();
+    let mut modal = Self::new(if failed.is_empty() { Ok(()) } else { Err(String::new()) });
+    modal.res = match failed.len() {
+      0 => format!("Saved {} files", total),
+      n => format!("Saved {} of {} files", total - n, total),
+    };
+    modal.details = failed;
+    modal
+  }
 }
 
 impl Modal for SaveTagsResultModal {
@@ -71,10 +100,17 @@ impl InputHandler for SaveTagsResultModal {
 
 impl WidgetRef for SaveTagsResultModal {
   fn render_ref(&self, area: Rect, buf: &mut Buffer) {
-    let [area] = Layout::vertical([Constraint::Max(7)])
+    let width: u16 = if self.details.is_empty() { 40 } else { 72 };
+    // lines of the details once wrapped to the content width
+    let details = self.details
+      .iter()
+      .map(|line| line.chars().count().max(1).div_ceil((width - 4) as usize))
+      .sum::<usize>()
+      .min(12) as u16;
+    let [area] = Layout::vertical([Constraint::Max(7 + details)])
       .flex(Flex::Center)
       .areas(
-        Layout::horizontal([Constraint::Max(40)])
+        Layout::horizontal([Constraint::Max(width)])
           .flex(Flex::Center)
           .areas::<1>(area)[0]
       );
@@ -87,7 +123,17 @@ impl WidgetRef for SaveTagsResultModal {
 
     Clear.render(area, buf);
     Block::bordered().border_type(BorderType::Rounded).render(area, buf);
-    Paragraph::new(Line::from(self.res.clone()).centered()).render(
+    Paragraph::new(
+      [
+        Vec::from([Line::from(self.res.clone()).centered()]),
+        self.details
+          .iter()
+          .map(|line| Line::from(line.clone()).red())
+          .collect(),
+      ].concat()
+    )
+      .wrap(Wrap::default())
+      .render(
       content_area.inner(Margin::new(1, 1)),
       buf
     );
@@ -20,24 +20,51 @@ use ratatui::{
     Wrap,
   },
 };
-use crate::{ app::{ app::Command, state::State }, ui::{ InputHandler, UiState } };
+use crate::{
+  app::{ app::Command, diff::{ self, DiffLine }, state::State, tag::{ Change, Id3v1Action } },
+  ui::{ InputHandler, UiState },
+};
 use super::modal::{ self, Modal, ModalOption, ModalOptions };
 
+/// Unchanged lines kept around the changed lines of a long field.
+const CONTEXT: usize = 1;
+
+/// Shows what will be written before the files are saved.
 pub struct ConfirmSaveTagsModal {
-  index: usize,
+  indexes: Vec<usize>,
   song_title: String,
+  has_id3v1: bool,
+  /// Diff of the changes of every file.
+  lines: Vec<Line<'static>>,
+  scroll: usize,
   options: ModalOptions,
 }
 
 impl ConfirmSaveTagsModal {
-  pub fn new(index: usize, song_title: impl Into<String>) -> Self {
+  /// Saves the files `indexes`, `song_title` names them and `changes` holds the name and changes
+  /// of each. `has_id3v1` offers to strip the ID3v1 tag instead of writing one.
+  pub fn new(
+    indexes: Vec<usize>,
+    song_title: impl Into<String>,
+    has_id3v1: bool,
+    changes: &[(String, Vec<Change>)]
+  ) -> Self {
+    let save = |action| Command::SaveTags(indexes.clone(), action);
     Self {
-      index,
       song_title: song_title.into(),
+      has_id3v1,
+      lines: diff_lines(changes),
+      scroll: 0,
       options: ModalOptions::new([
-        ModalOption::new("Save", Command::SaveTags(index)),
+        ModalOption::new("Save", save(Id3v1Action::Update)),
+        if has_id3v1 {
+          ModalOption::new("Strip ID3v1", save(Id3v1Action::Strip))
+        } else {
+          ModalOption::new("Add ID3v1", save(Id3v1Action::Write))
+        },
         ModalOption::new("Cancel", Command::CloseLastModal),
       ]),
+      indexes,
     }
   }
 }
@@ -52,11 +79,28 @@ impl InputHandler for ConfirmSaveTagsModal {
   ) -> bool {
     match event {
       Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+        let last = self.lines.len().saturating_sub(1);
         match key_event.code {
           KeyCode::Esc => {
             sender.send(Command::CloseLastModal);
             true
           }
+          KeyCode::Up => {
+            sender.send(Command::SetModalCursor(self.scroll.saturating_sub(1)));
+            true
+          }
+          KeyCode::Down => {
+            sender.send(Command::SetModalCursor((self.scroll + 1).min(last)));
+            true
+          }
+          KeyCode::PageUp => {
+            sender.send(Command::SetModalCursor(self.scroll.saturating_sub(10)));
+            true
+          }
+          KeyCode::PageDown => {
+            sender.send(Command::SetModalCursor((self.scroll + 10).min(last)));
+            true
+          }
           KeyCode::Left => {
             sender.send(Command::SetModalOption(self.options.prev()));
             true
@@ -85,18 +129,95 @@ impl Modal for ConfirmSaveTagsModal {
   fn options_mut(&mut self) -> Option<&mut ModalOptions> {
     Some(&mut self.options)
   }
+  fn cursor_mut(&mut self) -> Option<&mut usize> {
+    Some(&mut self.scroll)
+  }
+}
+
+/// Lines of the diff of each file, headed by the file name when there are several files.
+fn diff_lines(changes: &[(String, Vec<Change>)]) -> Vec<Line<'static>> {
+  let mut lines = Vec::new();
+  for (name, file_changes) in changes {
+    if changes.len() > 1 {
+      if !lines.is_empty() {
+        lines.push(Line::default());
+      }
+      lines.push(Line::from(name.clone()).bold());
+    }
+    for change in file_changes {
+      lines.extend(change_lines(change));
+    }
+  }
+  lines
+}
+
+/// "Field: original → new" for short values, a line-level diff under the field name for values
+/// spanning several lines.
+pub fn change_lines(change: &Change) -> Vec<Line<'static>> {
+  let field = Span::from(format!("{}: ", change.field)).dark_gray();
+  let multiline = [&change.original, &change.current]
+    .iter()
+    .any(|v| v.as_ref().is_some_and(|v| v.contains('\n')));
+  if !multiline {
+    let value = match (&change.original, &change.current) {
+      (Some(original), Some(current)) =>
+        Vec::from([
+          Span::from(original.clone()).dark_gray(),
+          Span::from(" → ").dark_gray(),
+          Span::from(current.clone()).yellow(),
+        ]),
+      (Some(original), None) => Vec::from([Span::from(original.clone()).red().crossed_out()]),
+      (None, current) => Vec::from([Span::from(current.clone().unwrap_or_default()).green()]),
+    };
+    return Vec::from([Line::from([Vec::from([field]), value].concat())]);
+  }
+  let diff = match (&change.original, &change.current) {
+    (Some(original), Some(current)) => diff::lines(original, current),
+    (Some(original), None) => original.split('\n').map(|l| DiffLine::Removed(l.into())).collect(),
+    (None, current) => {
+      let current = current.clone().unwrap_or_default();
+      current.split('\n').map(|l| DiffLine::Added(l.into())).collect()
+    }
+  };
+  let changed = |j: usize| !matches!(diff.get(j), Some(DiffLine::Same(_)) | None);
+  let mut lines = Vec::from([Line::from(field)]);
+  let mut skipped = 0;
+  for (j, line) in diff.iter().enumerate() {
+    let near_change = (j.saturating_sub(CONTEXT)..=j + CONTEXT).any(changed);
+    if !near_change {
+      skipped += 1;
+      continue;
+    }
+    if skipped > 0 {
+      lines.push(Line::from(format!("  ⋯ {} unchanged lines", skipped)).dark_gray());
+      skipped = 0;
+    }
+    lines.push(match line {
+      DiffLine::Same(l) => Line::from(format!("  {}", l)).dark_gray(),
+      DiffLine::Removed(l) =>
+        Line::from(
+          Vec::from([Span::from("- ").red(), Span::from(l.clone()).red().crossed_out()])
+        ),
+      DiffLine::Added(l) => Line::from(format!("+ {}", l)).green(),
+    });
+  }
+  if skipped > 0 {
+    lines.push(Line::from(format!("  ⋯ {} unchanged lines", skipped)).dark_gray());
+  }
+  lines
 }
 
 impl WidgetRef for ConfirmSaveTagsModal {
   fn render_ref(&self, area: Rect, buf: &mut Buffer) where Self: Sized {
-    let [area] = Layout::vertical([Constraint::Max(10)])
+    let [area] = Layout::vertical([Constraint::Max((self.lines.len() as u16).saturating_add(10))])
       .flex(Flex::Center)
       .areas(
-        Layout::horizontal([Constraint::Max(40)])
+        Layout::horizontal([Constraint::Max(90)])
           .flex(Flex::Center)
           .areas::<1>(area)[0]
       );
-    let [content_area, options_area] = Layout::vertical([
+    let [summary_area, diff_area, options_area] = Layout::vertical([
+      Constraint::Length(2),
       Constraint::Fill(1),
       Constraint::Length(1),
     ])
@@ -106,34 +227,39 @@ impl WidgetRef for ConfirmSaveTagsModal {
     Clear.render(area, buf);
     Block::bordered()
       .border_type(BorderType::Rounded)
-      .title_top(Line::from(" Confirmation ").centered())
+      .title_top(Line::from(" Review changes ").centered())
       .render(area, buf);
     Paragraph::new(
       Vec::from([
-        Line::default(),
         Line::from({
           Vec::from([
             Span::from("ID3 tags will be saved for "),
             Span::from(self.song_title.clone()).yellow(),
           ])
         }),
+        Line::from(
+          if self.has_id3v1 { "ID3v1 tag will be updated" } else { "File has no ID3v1 tag" }
+        ).dark_gray(),
       ])
     )
       .centered()
-      .wrap(Wrap::default())
-      .render(area.inner(Margin::new(1, 1)), buf);
-    Block::bordered()
-      .borders(Borders::TOP)
-      .border_style(Style::new())
-      .render(
-        Rect {
-          x: content_area.x,
-          y: content_area.y + content_area.height,
-          width: content_area.width,
-          height: 1,
-        },
-        buf
-      );
+      .render(summary_area, buf);
+    let scrollable = self.lines.len() > (diff_area.height as usize);
+    Paragraph::new(self.lines.clone())
+      .scroll((self.scroll as u16, 0))
+      .block(
+        Block::bordered()
+          .borders(Borders::TOP | Borders::BOTTOM)
+          .border_style(Style::new().dark_gray())
+          .title_bottom(
+            if scrollable {
+              Line::from(" ↑↓ Scroll ").dark_gray().right_aligned()
+            } else {
+              Line::default()
+            }
+          )
+      )
+      .render(diff_area.inner(Margin::new(1, 0)), buf);
     Table::from(&self.options).render(options_area.inner(Margin::new(1, 0)), buf);
   }
 }
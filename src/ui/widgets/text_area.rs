@@ -48,7 +48,7 @@ impl TextAreaTrait for TextArea<'_> {
         if text.is_err() {
           return false;
         }
-        let mut text = text.unwrap();
+        let mut text = text.unwrap().replace("\r\n", "\n").replace('\r', "\n");
         let text = if multiline { text } else { text.to_single_line() };
         self.insert_str(text);
         true
@@ -69,6 +69,14 @@ impl TextAreaTrait for TextArea<'_> {
         self.move_cursor(CursorMove::Back);
         false
       }
+      Input { key: Key::Up, ctrl: false, alt: false, .. } if multiline => {
+        self.move_cursor(CursorMove::Up);
+        false
+      }
+      Input { key: Key::Down, ctrl: false, alt: false, .. } if multiline => {
+        self.move_cursor(CursorMove::Down);
+        false
+      }
       Input { key: Key::MouseScrollDown, .. } => {
         self.scroll((1, 0));
         false